@@ -0,0 +1,34 @@
+//! Concatenates files to standard output, like `cat`.
+//!
+//! ```console
+//! $ cargo run --example cat -- Cargo.toml - README.md < LICENSE-MIT
+//! ```
+//!
+//! Compressed inputs are decompressed transparently when the corresponding feature is enabled.
+
+use std::io;
+
+use clap::Parser as _;
+use clap_file::{Input, Output};
+
+#[derive(Debug, clap::Parser)]
+struct Args {
+    /// Output file. If `-` is specified, writes to standard output.
+    #[arg(short, long, default_value = "-")]
+    output: Output,
+    /// Input files. If `-` is specified, reads from standard input.
+    #[arg(default_value = "-")]
+    inputs: Vec<Input>,
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::parse();
+    for input in &args.inputs {
+        if input.copy_to(&args.output)?.is_none() {
+            // The reader of standard output has exited, e.g. `cat | head`.
+            return Ok(());
+        }
+    }
+    args.output.close()?;
+    Ok(())
+}
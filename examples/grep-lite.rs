@@ -0,0 +1,49 @@
+//! Prints lines that contain a fixed string, like `grep -F`.
+//!
+//! ```console
+//! $ cargo run --example grep-lite -- -n clap Cargo.toml
+//! ```
+
+use std::io::{self, Write as _};
+
+use clap::Parser as _;
+use clap_file::{Input, Output};
+
+#[derive(Debug, clap::Parser)]
+struct Args {
+    /// Prints the lines that do not contain the pattern.
+    #[arg(short = 'v', long)]
+    invert_match: bool,
+    /// Prefixes each line with its line number.
+    #[arg(short = 'n', long)]
+    line_number: bool,
+    /// Output file. If `-` is specified, writes to standard output.
+    #[arg(short, long, default_value = "-")]
+    output: Output,
+    /// The string to search for.
+    pattern: String,
+    /// Input file. If `-` is specified, reads from standard input.
+    #[arg(default_value = "-")]
+    input: Input,
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::parse();
+    let mut number = 0;
+    let processed = args.input.process_lines(&args.output, |line, output| {
+        number += 1;
+        if line.contains(&args.pattern) == args.invert_match {
+            return Ok(());
+        }
+        if args.line_number {
+            write!(output, "{number}:")?;
+        }
+        writeln!(output, "{line}")
+    })?;
+    if processed.is_none() {
+        // The reader of standard output has exited, e.g. `grep-lite foo | head`.
+        return Ok(());
+    }
+    args.output.close()?;
+    Ok(())
+}
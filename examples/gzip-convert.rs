@@ -0,0 +1,41 @@
+//! Converts files between compression formats, choosing the formats from the file extensions.
+//!
+//! ```console
+//! $ cargo run --features gzip,zstd --example gzip-convert -- access.log.gz access.log.zst
+//! $ cargo run --features gzip --example gzip-convert -- --level 9 access.log access.log.gz
+//! ```
+//!
+//! Inputs are decompressed and outputs are compressed according to their extensions,
+//! and standard input is decompressed if it starts with the magic bytes of a supported format.
+
+use std::io;
+
+use clap::Parser as _;
+use clap_file::{CompressionLevel, Input, Output};
+
+#[derive(Debug, clap::Parser)]
+struct Args {
+    /// Compression level of the output.
+    #[arg(short, long)]
+    level: Option<CompressionLevel>,
+    /// Input file. If `-` is specified, reads from standard input.
+    input: Input,
+    /// Output file. If `-` is specified, writes uncompressed data to standard output.
+    output: Output,
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::parse();
+    let input = args.input.decompress_auto()?;
+    let mut output = args.output;
+    if let (Some(level), Some(compression)) = (args.level, output.compression()) {
+        output = output.with_compression(compression, level)?;
+    }
+    let Some(size) = input.copy_to(&output)? else {
+        // The reader of standard output has exited, e.g. `gzip-convert a.gz - | head`.
+        return Ok(());
+    };
+    output.close()?;
+    eprintln!("converted {size} bytes");
+    Ok(())
+}
@@ -0,0 +1,43 @@
+//! Pretty-prints JSON documents, or JSON Lines (NDJSON) records with `--lines`.
+//!
+//! ```console
+//! $ echo '{"name":"clap-file","keywords":[]}' | cargo run --features serde --example json-pretty
+//! ```
+
+use std::io;
+
+use clap::Parser as _;
+use clap_file::{Input, Output};
+
+#[derive(Debug, clap::Parser)]
+struct Args {
+    /// Reads one JSON value per line.
+    #[arg(short, long)]
+    lines: bool,
+    /// Output file. If `-` is specified, writes to standard output.
+    #[arg(short, long, default_value = "-")]
+    output: Output,
+    /// Input file. If `-` is specified, reads from standard input.
+    #[arg(default_value = "-")]
+    input: Input,
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::parse();
+    let mut output = args.output.lock();
+    if args.lines {
+        for value in args.input.json_lines::<serde_json::Value>() {
+            write_pretty(&mut output, &value?)?;
+        }
+    } else {
+        write_pretty(&mut output, &serde_json::from_reader(args.input.lock())?)?;
+    }
+    drop(output);
+    args.output.close()?;
+    Ok(())
+}
+
+fn write_pretty(output: &mut impl io::Write, value: &serde_json::Value) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *output, value)?;
+    writeln!(output)
+}
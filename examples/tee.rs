@@ -0,0 +1,53 @@
+//! Copies standard input to standard output and to files, like `tee`.
+//!
+//! ```console
+//! $ echo "Hello, world!" | cargo run --example tee -- a.txt b.txt.gz
+//! ```
+
+use std::io::{self, BufRead as _, Write as _};
+
+use clap::Parser as _;
+use clap_file::{Input, Output};
+
+#[derive(Debug, clap::Parser)]
+struct Args {
+    /// Appends to the files instead of overwriting them.
+    #[arg(short, long)]
+    append: bool,
+    /// Output files.
+    files: Vec<String>,
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::parse();
+    let mut outputs = vec![Output::stdout()];
+    for file in &args.files {
+        let output = if args.append {
+            Output::append(file.into())?
+        } else {
+            file.parse()?
+        };
+        outputs.push(output);
+    }
+
+    let input = Input::stdin();
+    let mut reader = input.lock();
+    let mut writers = outputs.iter().map(Output::lock).collect::<Vec<_>>();
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        for writer in &mut writers {
+            writer.write_all(buf)?;
+        }
+        let len = buf.len();
+        reader.consume(len);
+    }
+    drop(writers);
+
+    for output in outputs {
+        output.close()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use std::{
+    fs,
+    io::{self, Write},
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use crate::{cleanup::Registration, error, logging::debug, Compression, ErrorKind, Output};
+
+/// An output sink that writes to a temporary file and replaces the target file only when committed.
+///
+/// The temporary file is created in the same directory as the target, so that [`AtomicOutput::commit`]
+/// can atomically rename it over the target. If the [`AtomicOutput`] is dropped without being committed
+/// (e.g. because the program failed mid-write), the temporary file is deleted and the target is left untouched.
+///
+/// When parsed from a string, `-` writes to standard output; committing it only flushes the output.
+///
+/// If the target path has the extension of a compression format that supports compression (e.g. `.gz` or `.zst`),
+/// the data is compressed as with [`Output::compress`], and the compressed stream is finished when committed.
+/// Combined with deleting uncommitted files, this ensures that no truncated archive is left behind when the program fails or panics.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::AtomicOutput;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Output file. If `-` is specified, writes to standard output.
+///     output: AtomicOutput,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     writeln!(args.output.lock(), "Hello, world!")?;
+///     args.output.commit()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AtomicOutput {
+    output: Output,
+    temp: Option<Arc<TempFile>>,
+}
+
+#[derive(Debug)]
+struct TempFile {
+    path: PathBuf,
+    target: PathBuf,
+    committed: AtomicBool,
+    _registration: Registration,
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !*self.committed.get_mut() {
+            debug!(
+                "removing uncommitted temporary file `{}`",
+                self.path.display()
+            );
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Returns a unique path for a temporary file next to `target`, e.g. `.name.<pid>.<n>.tmp`.
+pub(crate) fn temp_path(target: &Path) -> io::Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let Some(file_name) = target.file_name() else {
+        return Err(error::new(
+            ErrorKind::InvalidInput,
+            format!("output path `{}` has no file name", target.display()),
+        ));
+    };
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    Ok(target.with_file_name(temp_name))
+}
+
+impl AtomicOutput {
+    /// Creates a new [`AtomicOutput`] instance that writes to standard output.
+    pub fn stdout() -> Self {
+        Self {
+            output: Output::stdout(),
+            temp: None,
+        }
+    }
+
+    /// Creates a temporary file next to the given path and creates a new [`AtomicOutput`] instance that writes to it.
+    ///
+    /// The target file is not touched until [`AtomicOutput::commit`] is called.
+    pub fn create(path: PathBuf) -> io::Result<Self> {
+        // The temporary file has no meaningful extension, so choose the compression by the target path
+        let mut builder = Output::builder();
+        builder.create_new(true).compress(false);
+        let (temp_path, mut output) = loop {
+            let temp_path = temp_path(&path)?;
+            match builder.open(temp_path.clone()) {
+                Ok(output) => break (temp_path, output),
+                // A file left by a previous process with the same ID
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        if let Some(compression) = Compression::for_output_path(&path) {
+            output = output.compress(compression)?;
+        }
+        Ok(Self {
+            output,
+            temp: Some(Arc::new(TempFile {
+                _registration: Registration::new(&temp_path),
+                path: temp_path,
+                target: path,
+                committed: AtomicBool::new(false),
+            })),
+        })
+    }
+
+    /// Returns the path of the target file, or `None` if this [`AtomicOutput`] writes to standard output.
+    ///
+    /// Note that [`Output::path`] returns the path of the temporary file.
+    pub fn target_path(&self) -> Option<&Path> {
+        self.temp.as_ref().map(|temp| temp.target.as_path())
+    }
+
+    /// Flushes the output and renames the temporary file over the target file.
+    ///
+    /// If the output is compressed, the compressed stream is finished first.
+    /// If this [`AtomicOutput`] writes to standard output, this only flushes the output.
+    pub fn commit(self) -> io::Result<()> {
+        self.output.lock().close()?;
+        if let Some(temp) = &self.temp {
+            debug!(
+                "committing `{}` to `{}`",
+                temp.path.display(),
+                temp.target.display()
+            );
+            fs::rename(&temp.path, &temp.target)?;
+            temp.committed.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Like [`AtomicOutput::commit`], but also makes the replacement durable.
+    ///
+    /// The temporary file is synchronized to the storage device with [`File::sync_all`](std::fs::File::sync_all)
+    /// before it is renamed, and on Unix, the parent directory is synchronized after the rename,
+    /// so that the new contents survive a crash or power loss once this method returns.
+    /// Backup and database-export tools need this guarantee.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap_file::AtomicOutput;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let output = AtomicOutput::create("backup.db".into())?;
+    ///     output.lock().write_all(b"data")?;
+    ///     output.commit_durable()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn commit_durable(self) -> io::Result<()> {
+        self.output.lock().close()?;
+        self.output.sync_all()?;
+        if let Some(temp) = &self.temp {
+            debug!(
+                "committing `{}` to `{}` durably",
+                temp.path.display(),
+                temp.target.display()
+            );
+            fs::rename(&temp.path, &temp.target)?;
+            temp.committed.store(true, Ordering::Relaxed);
+            #[cfg(unix)]
+            if let Some(parent) = temp.target.parent() {
+                let parent = if parent.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    parent
+                };
+                fs::File::open(parent)?.sync_all()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers this [`AtomicOutput`] to be committed when the process is interrupted by `Ctrl-C`, until the returned guard is dropped.
+    ///
+    /// This installs the handler described in [`install_interrupt_handler`](crate::install_interrupt_handler).
+    /// Without this, interrupted atomic outputs are rolled back, leaving the target file untouched.
+    /// Commit on interrupt when partial results are useful, e.g. for long-running exports that can be resumed.
+    #[cfg(feature = "signal")]
+    pub fn commit_on_interrupt(&self) -> io::Result<crate::InterruptGuard> {
+        let output = self.clone();
+        crate::InterruptGuard::register(move || {
+            if output
+                .temp
+                .as_ref()
+                .is_some_and(|temp| temp.committed.load(Ordering::Relaxed))
+            {
+                return Ok(());
+            }
+            output.commit()
+        })
+    }
+
+    /// Discards the written data and deletes the temporary file.
+    ///
+    /// This is equivalent to dropping the [`AtomicOutput`], but makes the intent explicit.
+    /// If clones of this [`AtomicOutput`] exist, the temporary file is deleted when the last one is dropped.
+    pub fn abort(self) {}
+}
+
+impl Deref for AtomicOutput {
+    type Target = Output;
+
+    fn deref(&self) -> &Self::Target {
+        &self.output
+    }
+}
+
+impl FromStr for AtomicOutput {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            return Ok(Self::stdout());
+        }
+        Self::create(PathBuf::from(s))
+    }
+}
+
+impl Write for AtomicOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.output.write_all(buf)
+    }
+}
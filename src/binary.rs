@@ -0,0 +1,187 @@
+use std::io::{self, Read, Write};
+
+use crate::{error, ErrorKind};
+
+macro_rules! read_num {
+    ($($(#[$attr:meta])* $name:ident => $ty:ty, $from:ident;)*) => {
+        $(
+            $(#[$attr])*
+            fn $name(&mut self) -> io::Result<$ty> {
+                let mut buf = [0; std::mem::size_of::<$ty>()];
+                self.read_exact(&mut buf)?;
+                Ok(<$ty>::$from(buf))
+            }
+        )*
+    };
+}
+
+macro_rules! write_num {
+    ($($(#[$attr:meta])* $name:ident => $ty:ty, $to:ident;)*) => {
+        $(
+            $(#[$attr])*
+            fn $name(&mut self, n: $ty) -> io::Result<()> {
+                self.write_all(&n.$to())
+            }
+        )*
+    };
+}
+
+/// Extension methods for reading fixed-size binary numbers.
+///
+/// This trait is implemented for all types that implement [`Read`], including [`LockedInput`](crate::LockedInput),
+/// so binary-format tools can parse headers without an extra dependency.
+/// Each method reads exactly the size of the number, and fails with [`io::ErrorKind::UnexpectedEof`] if the input ends before that.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io;
+///
+/// use clap_file::{BinaryReadExt as _, Input};
+///
+/// fn main() -> io::Result<()> {
+///     let path = std::env::temp_dir().join(format!("clap-file-binary-{}.png", std::process::id()));
+///     std::fs::write(&path, b"\x89PNG\r\n\x1a\n")?;
+///     let input = Input::open(path.clone())?;
+///     let mut reader = input.lock();
+///     // `\x89PNG`
+///     assert_eq!(reader.read_u32_be()?, 0x8950_4e47);
+///     // `\r\n`
+///     assert_eq!(reader.read_u16_le()?, 0x0a0d);
+///     # drop(reader);
+///     # drop(input);
+///     # std::fs::remove_file(&path)?;
+///     Ok(())
+/// }
+/// ```
+pub trait BinaryReadExt: Read {
+    read_num! {
+        /// Reads an unsigned 8-bit integer.
+        read_u8 => u8, from_le_bytes;
+        /// Reads a signed 8-bit integer.
+        read_i8 => i8, from_le_bytes;
+        /// Reads an unsigned 16-bit integer in little-endian byte order.
+        read_u16_le => u16, from_le_bytes;
+        /// Reads an unsigned 16-bit integer in big-endian byte order.
+        read_u16_be => u16, from_be_bytes;
+        /// Reads a signed 16-bit integer in little-endian byte order.
+        read_i16_le => i16, from_le_bytes;
+        /// Reads a signed 16-bit integer in big-endian byte order.
+        read_i16_be => i16, from_be_bytes;
+        /// Reads an unsigned 32-bit integer in little-endian byte order.
+        read_u32_le => u32, from_le_bytes;
+        /// Reads an unsigned 32-bit integer in big-endian byte order.
+        read_u32_be => u32, from_be_bytes;
+        /// Reads a signed 32-bit integer in little-endian byte order.
+        read_i32_le => i32, from_le_bytes;
+        /// Reads a signed 32-bit integer in big-endian byte order.
+        read_i32_be => i32, from_be_bytes;
+        /// Reads an unsigned 64-bit integer in little-endian byte order.
+        read_u64_le => u64, from_le_bytes;
+        /// Reads an unsigned 64-bit integer in big-endian byte order.
+        read_u64_be => u64, from_be_bytes;
+        /// Reads a signed 64-bit integer in little-endian byte order.
+        read_i64_le => i64, from_le_bytes;
+        /// Reads a signed 64-bit integer in big-endian byte order.
+        read_i64_be => i64, from_be_bytes;
+        /// Reads a 32-bit floating point number in little-endian byte order.
+        read_f32_le => f32, from_le_bytes;
+        /// Reads a 32-bit floating point number in big-endian byte order.
+        read_f32_be => f32, from_be_bytes;
+        /// Reads a 64-bit floating point number in little-endian byte order.
+        read_f64_le => f64, from_le_bytes;
+        /// Reads a 64-bit floating point number in big-endian byte order.
+        read_f64_be => f64, from_be_bytes;
+    }
+}
+
+impl<R> BinaryReadExt for R where R: Read + ?Sized {}
+
+/// Extension methods for writing fixed-size binary numbers and records.
+///
+/// This trait is implemented for all types that implement [`Write`], including [`LockedOutput`](crate::LockedOutput),
+/// completing the binary-record story on the write side of [`BinaryReadExt`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io;
+///
+/// use clap_file::{BinaryWriteExt as _, Output};
+///
+/// fn main() -> io::Result<()> {
+///     let output = Output::create("header.bin".into())?;
+///     let mut writer = output.lock();
+///     writer.write_u32_be(0xcafe_babe)?;
+///     writer.write_u16_le(1)?;
+///     Ok(())
+/// }
+/// ```
+pub trait BinaryWriteExt: Write {
+    write_num! {
+        /// Writes an unsigned 8-bit integer.
+        write_u8 => u8, to_le_bytes;
+        /// Writes a signed 8-bit integer.
+        write_i8 => i8, to_le_bytes;
+        /// Writes an unsigned 16-bit integer in little-endian byte order.
+        write_u16_le => u16, to_le_bytes;
+        /// Writes an unsigned 16-bit integer in big-endian byte order.
+        write_u16_be => u16, to_be_bytes;
+        /// Writes a signed 16-bit integer in little-endian byte order.
+        write_i16_le => i16, to_le_bytes;
+        /// Writes a signed 16-bit integer in big-endian byte order.
+        write_i16_be => i16, to_be_bytes;
+        /// Writes an unsigned 32-bit integer in little-endian byte order.
+        write_u32_le => u32, to_le_bytes;
+        /// Writes an unsigned 32-bit integer in big-endian byte order.
+        write_u32_be => u32, to_be_bytes;
+        /// Writes a signed 32-bit integer in little-endian byte order.
+        write_i32_le => i32, to_le_bytes;
+        /// Writes a signed 32-bit integer in big-endian byte order.
+        write_i32_be => i32, to_be_bytes;
+        /// Writes an unsigned 64-bit integer in little-endian byte order.
+        write_u64_le => u64, to_le_bytes;
+        /// Writes an unsigned 64-bit integer in big-endian byte order.
+        write_u64_be => u64, to_be_bytes;
+        /// Writes a signed 64-bit integer in little-endian byte order.
+        write_i64_le => i64, to_le_bytes;
+        /// Writes a signed 64-bit integer in big-endian byte order.
+        write_i64_be => i64, to_be_bytes;
+        /// Writes a 32-bit floating point number in little-endian byte order.
+        write_f32_le => f32, to_le_bytes;
+        /// Writes a 32-bit floating point number in big-endian byte order.
+        write_f32_be => f32, to_be_bytes;
+        /// Writes a 64-bit floating point number in little-endian byte order.
+        write_f64_le => f64, to_le_bytes;
+        /// Writes a 64-bit floating point number in big-endian byte order.
+        write_f64_be => f64, to_be_bytes;
+    }
+
+    /// Writes `data` as a sequence of fixed-size records of `record_size` bytes.
+    ///
+    /// Each record is written with a single [`Write::write_all`] call.
+    /// If the length of `data` is not a multiple of `record_size`, nothing is written and
+    /// an error of kind [`io::ErrorKind::InvalidInput`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `record_size` is 0.
+    fn write_all_chunked(&mut self, data: &[u8], record_size: usize) -> io::Result<()> {
+        assert!(record_size != 0, "record size must be non-zero");
+        if data.len() % record_size != 0 {
+            return Err(error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "data length {} is not a multiple of the record size {record_size}",
+                    data.len()
+                ),
+            ));
+        }
+        for record in data.chunks_exact(record_size) {
+            self.write_all(record)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W> BinaryWriteExt for W where W: Write + ?Sized {}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+/// A byte order mark (BOM), which identifies the Unicode encoding of the text following it.
+///
+/// BOMs are detected and stripped by [`InputBuilder::strip_bom`](crate::InputBuilder::strip_bom) and [`Input::strip_bom`](crate::Input::strip_bom),
+/// and the detected one is reported by [`LockedInput::detected_encoding`](crate::LockedInput::detected_encoding).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Bom {
+    /// The UTF-8 BOM (`EF BB BF`).
+    Utf8,
+    /// The UTF-16 little-endian BOM (`FF FE`).
+    Utf16Le,
+    /// The UTF-16 big-endian BOM (`FE FF`).
+    Utf16Be,
+}
+
+impl Bom {
+    /// Detects the BOM at the start of `bytes`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use clap_file::Bom;
+    ///
+    /// assert_eq!(Bom::from_bytes(b"\xef\xbb\xbfhello"), Some(Bom::Utf8));
+    /// assert_eq!(Bom::from_bytes(b"\xff\xfeh\0"), Some(Bom::Utf16Le));
+    /// assert_eq!(Bom::from_bytes(b"hello"), None);
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        [Self::Utf8, Self::Utf16Le, Self::Utf16Be]
+            .into_iter()
+            .find(|bom| bytes.starts_with(bom.as_bytes()))
+    }
+
+    /// Returns the bytes of this BOM.
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Utf8 => b"\xef\xbb\xbf",
+            Self::Utf16Le => b"\xff\xfe",
+            Self::Utf16Be => b"\xfe\xff",
+        }
+    }
+
+    /// Returns the name of the encoding this BOM identifies, e.g. `UTF-16LE`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+        }
+    }
+}
+
+impl fmt::Display for Bom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(feature = "encoding")]
+impl From<Bom> for crate::Encoding {
+    fn from(bom: Bom) -> Self {
+        match bom {
+            Bom::Utf8 => Self::UTF_8,
+            Bom::Utf16Le => Self::UTF_16LE,
+            Bom::Utf16Be => Self::UTF_16BE,
+        }
+    }
+}
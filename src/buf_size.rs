@@ -0,0 +1,36 @@
+use std::{fs::File, io::IsTerminal as _};
+
+/// Buffer capacity used for regular files.
+///
+/// A large buffer mainly helps writers that issue many small writes, by reducing the number of `write` calls.
+/// Line-by-line reads from the page cache are dominated by the parsing cost and gain little.
+pub(crate) const REGULAR_FILE: usize = 256 * 1024;
+
+/// Buffer capacity used for pipes (FIFOs), matching the default pipe buffer size on Linux.
+pub(crate) const PIPE: usize = 64 * 1024;
+
+/// Buffer capacity used for terminals and other files whose type is unknown.
+pub(crate) const DEFAULT: usize = 8 * 1024;
+
+/// Chooses a buffer capacity suitable for the type of `file`.
+pub(crate) fn for_file(file: &File) -> usize {
+    if file.is_terminal() {
+        return DEFAULT;
+    }
+    match file.metadata() {
+        Ok(metadata) if metadata.is_file() => REGULAR_FILE,
+        Ok(metadata) if is_fifo(&metadata) => PIPE,
+        _ => DEFAULT,
+    }
+}
+
+#[cfg(unix)]
+fn is_fifo(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt as _;
+    metadata.file_type().is_fifo()
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
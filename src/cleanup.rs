@@ -0,0 +1,94 @@
+use std::{
+    fs, panic,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use crate::logging::debug;
+
+// Files created by this crate that must not outlive the process, with the IDs of their registrations.
+static REGISTERED: Mutex<Vec<(usize, PathBuf)>> = Mutex::new(Vec::new());
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+fn registered() -> MutexGuard<'static, Vec<(usize, PathBuf)>> {
+    REGISTERED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Installs a panic hook that removes the temporary files created by this crate when the process panics.
+///
+/// Temporary files, such as the spool files of [`SpooledInput`](crate::SpooledInput), the copies made by [`InputSnapshot`](crate::InputSnapshot),
+/// and the uncommitted files of [`AtomicOutput`](crate::AtomicOutput), are removed when they are dropped. However, they are left behind if
+/// the program is built with `panic = "abort"`, or if a thread other than the owner panics and the process exits
+/// without unwinding the owner. This hook removes them after the previously installed hook (which prints the panic message) returns.
+///
+/// Since all temporary files are removed regardless of the panicking thread, only install this hook in programs
+/// where a panic terminates the process. Calling this function more than once has no effect.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::AtomicOutput;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     output: AtomicOutput,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     clap_file::install_panic_cleanup();
+///     let args = Args::parse();
+///     writeln!(args.output.lock(), "Hello, world!")?;
+///     args.output.commit()?;
+///     Ok(())
+/// }
+/// ```
+pub fn install_panic_cleanup() {
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    debug!("installing panic hook for removing temporary files");
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+        remove_all("panic");
+    }));
+}
+
+/// Removes all registered temporary files, as the process is about to terminate due to `reason`.
+pub(crate) fn remove_all(reason: &str) {
+    for (_, path) in registered().drain(..) {
+        debug!(
+            "removing temporary file `{}` due to {reason}",
+            path.display()
+        );
+        let _ = fs::remove_file(&path);
+    }
+}
+
+/// A temporary file registered to be removed by the panic hook installed with [`install_panic_cleanup`].
+///
+/// The file is unregistered when this is dropped.
+#[derive(Debug)]
+pub(crate) struct Registration(usize);
+
+impl Registration {
+    pub(crate) fn new(path: &Path) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        registered().push((id, path.to_owned()));
+        Self(id)
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        registered().retain(|(id, _)| *id != self.0);
+    }
+}
@@ -0,0 +1,101 @@
+use std::{io, path::PathBuf};
+
+use crate::{error, ErrorKind};
+
+#[cfg(windows)]
+const NULL_PATH: &str = "NUL";
+#[cfg(not(windows))]
+const NULL_PATH: &str = "/dev/null";
+
+/// Specifies what the value parsers do when `-` is given but the standard stream is closed.
+///
+/// Some service managers start processes with standard input or output closed.
+/// Without a check, such a process fails on the first read or write with a "bad file descriptor" error,
+/// far from the argument that caused it.
+///
+/// On Unix, the Rust runtime may reopen closed standard streams as `/dev/null` before `main` runs;
+/// such streams are not detected as closed.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::{ClosedStdio, Output, OutputValueParser};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Output file. If standard output is closed, the output is discarded.
+///     #[arg(value_parser = OutputValueParser::new().closed_stdio(ClosedStdio::Null))]
+///     output: Output,
+/// }
+///
+/// let args = Args::parse_from(["prog", "-"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum ClosedStdio {
+    /// Fails to parse the argument with an error saying that the stream is closed.
+    #[default]
+    Error,
+    /// Reads from or writes to the null device instead, so that reads return EOF and writes are discarded.
+    Null,
+}
+
+impl ClosedStdio {
+    /// Returns the path of the null device to open instead of the stream, or `None` if the stream is open.
+    pub(crate) fn fallback(self, closed: bool, name: &str) -> io::Result<Option<PathBuf>> {
+        if !closed {
+            return Ok(None);
+        }
+        match self {
+            Self::Error => Err(error::new(
+                ErrorKind::StdioClosed,
+                format!("{name} is closed"),
+            )),
+            Self::Null => Ok(Some(PathBuf::from(NULL_PATH))),
+        }
+    }
+}
+
+/// Returns `true` if standard input is closed.
+pub(crate) fn is_stdin_closed() -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsFd as _;
+        is_fd_closed(io::stdin().as_fd())
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::io::AsRawHandle as _;
+        io::stdin().as_raw_handle().is_null()
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        false
+    }
+}
+
+/// Returns `true` if standard output is closed.
+pub(crate) fn is_stdout_closed() -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsFd as _;
+        is_fd_closed(io::stdout().as_fd())
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::io::AsRawHandle as _;
+        io::stdout().as_raw_handle().is_null()
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        false
+    }
+}
+
+#[cfg(unix)]
+fn is_fd_closed(fd: std::os::fd::BorrowedFd<'_>) -> bool {
+    // Duplicating the descriptor is the only portable way to check its validity without `libc`.
+    fd.try_clone_to_owned()
+        .is_err_and(|e| ErrorKind::of(&e) == ErrorKind::StdioClosed)
+}
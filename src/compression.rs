@@ -0,0 +1,847 @@
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    num::NonZeroUsize,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use crate::{error, logging::debug, ErrorKind};
+
+static CODECS: RwLock<Vec<&'static dyn Codec>> = RwLock::new(Vec::new());
+
+fn codecs() -> Vec<&'static dyn Codec> {
+    CODECS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// A compression format that inputs can be decompressed from, and outputs compressed to.
+///
+/// Support for each format is enabled by the cargo feature of the same name (e.g. `gzip`).
+/// Formats whose feature is disabled are still recognized, but fail to decompress.
+/// Other formats can be added with [`register_codec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Compression {
+    /// The gzip format (`.gz`), enabled by the `gzip` feature.
+    Gzip,
+    /// The Zstandard format (`.zst`), enabled by the `zstd` feature.
+    Zstd,
+    /// The xz format (`.xz`), enabled by the `xz` feature. Only decompression is supported.
+    Xz,
+    /// The bzip2 format (`.bz2`), enabled by the `bzip2` feature. Only decompression is supported.
+    Bzip2,
+    /// A format implemented by an application, returned by [`register_codec`].
+    Custom(CustomCodec),
+}
+
+impl Compression {
+    /// Returns the compression format indicated by the extension of `path`, if any.
+    ///
+    /// Registered codecs take precedence over the built-in formats.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    ///
+    /// use clap_file::Compression;
+    ///
+    /// assert_eq!(Compression::from_path(Path::new("access.log.gz")), Some(Compression::Gzip));
+    /// assert_eq!(Compression::from_path(Path::new("dump.sql.zst")), Some(Compression::Zstd));
+    /// assert_eq!(Compression::from_path(Path::new("linux.tar.xz")), Some(Compression::Xz));
+    /// assert_eq!(Compression::from_path(Path::new("access.log")), None);
+    /// ```
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?;
+        Self::all().find(|compression| extension.eq_ignore_ascii_case(compression.extension()))
+    }
+
+    /// Returns the format that outputs at `path` are compressed to: the first one indicated by the extension that can compress.
+    ///
+    /// Unlike [`Compression::from_path`], this skips registered codecs that only support decompression,
+    /// so that they do not make outputs with the extension of a built-in format uncompressed.
+    pub(crate) fn for_output_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?;
+        Self::all().find(|compression| {
+            extension.eq_ignore_ascii_case(compression.extension()) && compression.can_compress()
+        })
+    }
+
+    /// Returns the compression format indicated by the magic bytes at the start of `bytes`, if any.
+    ///
+    /// At least the first 6 bytes of the data are needed to recognize all built-in formats.
+    /// Registered codecs take precedence over the built-in formats.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use clap_file::Compression;
+    ///
+    /// assert_eq!(Compression::from_magic(b"\x1f\x8b\x08\x00"), Some(Compression::Gzip));
+    /// assert_eq!(Compression::from_magic(b"BZh91AY&SY"), Some(Compression::Bzip2));
+    /// assert_eq!(Compression::from_magic(b"Hello, world!"), None);
+    /// ```
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        if let Some(codec) = codecs()
+            .into_iter()
+            .rev()
+            .find(|codec| codec.matches_magic(bytes))
+        {
+            return Some(Self::Custom(CustomCodec(codec)));
+        }
+        match bytes {
+            [0x1f, 0x8b, ..] => Some(Self::Gzip),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Self::Zstd),
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Self::Xz),
+            [b'B', b'Z', b'h', b'1'..=b'9', ..] => Some(Self::Bzip2),
+            _ => None,
+        }
+    }
+
+    /// Returns the registered codecs, most recently registered first, followed by the built-in formats.
+    fn all() -> impl Iterator<Item = Self> {
+        codecs()
+            .into_iter()
+            .rev()
+            .map(|codec| Self::Custom(CustomCodec(codec)))
+            .chain([Self::Gzip, Self::Zstd, Self::Xz, Self::Bzip2])
+    }
+
+    /// Returns the name of this compression format.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Xz => "xz",
+            Self::Bzip2 => "bzip2",
+            Self::Custom(codec) => codec.0.name(),
+        }
+    }
+
+    /// Returns the file extension of this compression format, without the leading `.`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Gzip => "gz",
+            Self::Zstd => "zst",
+            Self::Xz => "xz",
+            Self::Bzip2 => "bz2",
+            Self::Custom(codec) => codec.0.extension(),
+        }
+    }
+
+    /// Returns `true` if the cargo feature for this compression format is enabled.
+    ///
+    /// Registered codecs are always supported.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Self::Gzip => cfg!(feature = "gzip"),
+            Self::Zstd => cfg!(feature = "zstd"),
+            Self::Xz => cfg!(feature = "xz"),
+            Self::Bzip2 => cfg!(feature = "bzip2"),
+            Self::Custom(_) => true,
+        }
+    }
+
+    /// Returns `true` if outputs can be compressed to this format.
+    pub(crate) fn can_compress(self) -> bool {
+        match self {
+            Self::Gzip => cfg!(feature = "gzip"),
+            Self::Zstd => cfg!(feature = "zstd"),
+            Self::Xz | Self::Bzip2 => false,
+            Self::Custom(codec) => codec.0.can_compress(),
+        }
+    }
+
+    // Unused when the features of all formats are enabled
+    #[allow(dead_code)]
+    fn unsupported(self) -> io::Error {
+        error::new(
+            ErrorKind::Unsupported,
+            format!(
+                "{} compression is not supported; enable the `{}` feature of clap-file",
+                self.name(),
+                self.name()
+            ),
+        )
+    }
+
+    pub(crate) fn decoder<R>(self, reader: R) -> io::Result<Decoder>
+    where
+        R: Read + Send + 'static,
+    {
+        match self {
+            Self::Gzip => {
+                // Like `gzip -d`, decompress all members of multi-member files (e.g. concatenated `.gz` files)
+                #[cfg(feature = "gzip")]
+                return Ok(Decoder(Box::new(flate2::read::MultiGzDecoder::new(reader))));
+                #[cfg(not(feature = "gzip"))]
+                {
+                    drop(reader);
+                    Err(self.unsupported())
+                }
+            }
+            Self::Zstd => {
+                #[cfg(feature = "zstd")]
+                return Ok(Decoder(Box::new(zstd::stream::read::Decoder::new(reader)?)));
+                #[cfg(not(feature = "zstd"))]
+                {
+                    drop(reader);
+                    Err(self.unsupported())
+                }
+            }
+            Self::Xz => {
+                // Like `xz -d`, decompress all streams of concatenated files
+                #[cfg(feature = "xz")]
+                return Ok(Decoder(Box::new(xz2::read::XzDecoder::new_multi_decoder(
+                    reader,
+                ))));
+                #[cfg(not(feature = "xz"))]
+                {
+                    drop(reader);
+                    Err(self.unsupported())
+                }
+            }
+            Self::Bzip2 => {
+                // Like `bzip2 -d`, decompress all streams of concatenated files
+                #[cfg(feature = "bzip2")]
+                return Ok(Decoder(Box::new(bzip2::read::MultiBzDecoder::new(reader))));
+                #[cfg(not(feature = "bzip2"))]
+                {
+                    drop(reader);
+                    Err(self.unsupported())
+                }
+            }
+            Self::Custom(codec) => Ok(Decoder(codec.0.decoder(Box::new(reader))?)),
+        }
+    }
+
+    /// Creates an encoder that writes compressed data to `writer` with the given options.
+    pub(crate) fn encoder<W>(self, writer: W, options: EncoderOptions) -> io::Result<Encoder>
+    where
+        W: Write + Send + 'static,
+    {
+        let EncoderOptions {
+            level,
+            threads,
+            frame_size,
+        } = options;
+        let abandoned = Arc::new(AtomicBool::new(false));
+        let writer = Sink {
+            writer,
+            abandoned: Arc::clone(&abandoned),
+        };
+        match self {
+            Self::Gzip => {
+                #[cfg(feature = "gzip")]
+                {
+                    let level = match level {
+                        Some(level @ 0..=9) => flate2::Compression::new(level as u32),
+                        Some(level) => {
+                            return Err(error::new(
+                                ErrorKind::InvalidInput,
+                                format!("gzip compression level must be between 0 and 9, but {level} was given"),
+                            ))
+                        }
+                        None => flate2::Compression::default(),
+                    };
+                    if let Some(threads) = threads.filter(|threads| threads.get() > 1) {
+                        return Ok(Encoder::new(
+                            crate::parallel_gzip::ParallelGzEncoder::new(writer, level, threads),
+                            abandoned,
+                        ));
+                    }
+                    Ok(Encoder::new(
+                        flate2::write::GzEncoder::new(writer, level),
+                        abandoned,
+                    ))
+                }
+                #[cfg(not(feature = "gzip"))]
+                {
+                    drop((writer, level, threads, frame_size, abandoned));
+                    Err(self.unsupported())
+                }
+            }
+            Self::Zstd => {
+                #[cfg(feature = "zstd")]
+                {
+                    let level = level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL);
+                    let range = zstd::compression_level_range();
+                    if !range.contains(&level) {
+                        return Err(error::new(
+                            ErrorKind::InvalidInput,
+                            format!(
+                                "zstd compression level must be between {} and {}, but {level} was given",
+                                range.start(),
+                                range.end()
+                            ),
+                        ));
+                    }
+                    if let Some(frame_size) = frame_size {
+                        return Ok(Encoder::new(
+                            crate::seekable_zstd::SeekableZstdEncoder::new(
+                                writer, level, frame_size, threads,
+                            )?,
+                            abandoned,
+                        ));
+                    }
+                    let mut encoder = zstd::stream::write::Encoder::new(writer, level)?;
+                    if let Some(threads) = threads.filter(|threads| threads.get() > 1) {
+                        let workers = u32::try_from(threads.get()).unwrap_or(u32::MAX);
+                        encoder.multithread(workers)?;
+                    }
+                    Ok(Encoder::new(encoder, abandoned))
+                }
+                #[cfg(not(feature = "zstd"))]
+                {
+                    drop((writer, level, threads, frame_size, abandoned));
+                    Err(self.unsupported())
+                }
+            }
+            Self::Custom(codec) if codec.0.can_compress() => Ok(Encoder::new(
+                codec.0.encoder(Box::new(writer), level)?,
+                abandoned,
+            )),
+            Self::Xz | Self::Bzip2 | Self::Custom(_) => {
+                drop((writer, level, threads, frame_size, abandoned));
+                Err(error::new(
+                    ErrorKind::Unsupported,
+                    format!("compressing outputs with {} is not supported", self.name()),
+                ))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A compression format implemented by an application, such as LZ4, Snappy, or a proprietary format.
+///
+/// Codecs registered with [`register_codec`] are detected from file extensions and magic bytes in the same way as the built-in formats,
+/// so inputs and outputs with their extension are decompressed and compressed transparently.
+///
+/// # Examples
+///
+/// A codec that "compresses" data by inverting its bits:
+///
+/// ```rust
+/// use std::io::{self, Read, Write};
+///
+/// use clap_file::{Codec, CodecEncoder};
+///
+/// struct Invert<T>(T);
+///
+/// impl<R: Read> Read for Invert<R> {
+///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+///         let n = self.0.read(buf)?;
+///         buf[..n].iter_mut().for_each(|b| *b = !*b);
+///         Ok(n)
+///     }
+/// }
+///
+/// impl<W: Write> Write for Invert<W> {
+///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+///         let inverted = buf.iter().map(|b| !b).collect::<Vec<_>>();
+///         self.0.write_all(&inverted)?;
+///         Ok(buf.len())
+///     }
+///
+///     fn flush(&mut self) -> io::Result<()> {
+///         self.0.flush()
+///     }
+/// }
+///
+/// impl<W: Write + Send> CodecEncoder for Invert<W> {
+///     fn finish(&mut self) -> io::Result<()> {
+///         self.0.flush()
+///     }
+/// }
+///
+/// struct InvertCodec;
+///
+/// impl Codec for InvertCodec {
+///     fn name(&self) -> &str {
+///         "invert"
+///     }
+///
+///     fn extension(&self) -> &str {
+///         "inv"
+///     }
+///
+///     fn decoder(&self, reader: Box<dyn Read + Send>) -> io::Result<Box<dyn Read + Send>> {
+///         Ok(Box::new(Invert(reader)))
+///     }
+///
+///     fn can_compress(&self) -> bool {
+///         true
+///     }
+///
+///     fn encoder(
+///         &self,
+///         writer: Box<dyn Write + Send>,
+///         _level: Option<i32>,
+///     ) -> io::Result<Box<dyn CodecEncoder>> {
+///         Ok(Box::new(Invert(writer)))
+///     }
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let compression = clap_file::register_codec(InvertCodec);
+///     assert_eq!(compression.name(), "invert");
+///
+///     let path = std::env::temp_dir().join(format!("clap-file-doc-{}.txt.inv", std::process::id()));
+///     let output: clap_file::Output = path.to_str().unwrap().parse()?;
+///     write!(output.lock(), "Hello, world!")?;
+///     output.close()?;
+///     assert_eq!(std::fs::read(&path)?, b"Hello, world!".map(|b| !b));
+///
+///     let input = clap_file::Input::open(path.clone())?;
+///     assert_eq!(io::read_to_string(input.lock())?, "Hello, world!");
+///     # std::fs::remove_file(&path)?;
+///     Ok(())
+/// }
+/// ```
+pub trait Codec: Send + Sync + 'static {
+    /// Returns the name of this format, e.g. `lz4`, which is shown in messages.
+    fn name(&self) -> &str;
+
+    /// Returns the file extension of this format, without the leading `.`, e.g. `lz4`.
+    fn extension(&self) -> &str;
+
+    /// Returns `true` if `bytes`, the start of some data, begin with the magic bytes of this format.
+    ///
+    /// This is used by [`Input::decompress_auto`](crate::Input::decompress_auto). The default implementation returns `false`.
+    fn matches_magic(&self, bytes: &[u8]) -> bool {
+        let _ = bytes;
+        false
+    }
+
+    /// Creates a reader that decompresses the data read from `reader`.
+    fn decoder(&self, reader: Box<dyn Read + Send>) -> io::Result<Box<dyn Read + Send>>;
+
+    /// Returns `true` if outputs can be compressed to this format with [`Codec::encoder`].
+    ///
+    /// The default implementation returns `false`, for formats that only support decompression.
+    /// Outputs with the extension of such a codec are compressed with the next format that has the extension
+    /// and can compress, e.g. a built-in format or an earlier registered codec.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Read, Write};
+    ///
+    /// use clap_file::{Codec, CodecEncoder, Output};
+    ///
+    /// // Inverts every byte, in both directions.
+    /// struct Invert<T>(T);
+    ///
+    /// impl<R: Read> Read for Invert<R> {
+    ///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    ///         let n = self.0.read(buf)?;
+    ///         buf[..n].iter_mut().for_each(|b| *b = !*b);
+    ///         Ok(n)
+    ///     }
+    /// }
+    ///
+    /// impl<W: Write> Write for Invert<W> {
+    ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    ///         self.0.write_all(&buf.iter().map(|b| !b).collect::<Vec<_>>())?;
+    ///         Ok(buf.len())
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         self.0.flush()
+    ///     }
+    /// }
+    ///
+    /// impl<W: Write + Send> CodecEncoder for Invert<W> {
+    ///     fn finish(&mut self) -> io::Result<()> {
+    ///         self.0.flush()
+    ///     }
+    /// }
+    ///
+    /// struct InvertCodec;
+    ///
+    /// impl Codec for InvertCodec {
+    ///     fn name(&self) -> &str {
+    ///         "invert"
+    ///     }
+    ///
+    ///     fn extension(&self) -> &str {
+    ///         "inv"
+    ///     }
+    ///
+    ///     fn decoder(&self, reader: Box<dyn Read + Send>) -> io::Result<Box<dyn Read + Send>> {
+    ///         Ok(Box::new(Invert(reader)))
+    ///     }
+    ///
+    ///     fn can_compress(&self) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn encoder(
+    ///         &self,
+    ///         writer: Box<dyn Write + Send>,
+    ///         _level: Option<i32>,
+    ///     ) -> io::Result<Box<dyn CodecEncoder>> {
+    ///         Ok(Box::new(Invert(writer)))
+    ///     }
+    /// }
+    ///
+    /// // Another decoder for the same format, which cannot compress.
+    /// struct InvertDecoder;
+    ///
+    /// impl Codec for InvertDecoder {
+    ///     fn name(&self) -> &str {
+    ///         "invert-decoder"
+    ///     }
+    ///
+    ///     fn extension(&self) -> &str {
+    ///         "inv"
+    ///     }
+    ///
+    ///     fn decoder(&self, reader: Box<dyn Read + Send>) -> io::Result<Box<dyn Read + Send>> {
+    ///         Ok(Box::new(Invert(reader)))
+    ///     }
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     clap_file::register_codec(InvertCodec);
+    ///     clap_file::register_codec(InvertDecoder);
+    ///
+    ///     let path = std::env::temp_dir().join(format!("clap-file-doc-decode-only-{}.inv", std::process::id()));
+    ///     let output = Output::create(path.clone())?;
+    ///     write!(output.lock(), "Hello, world!")?;
+    ///     output.close()?;
+    ///     // Compressed with `InvertCodec`, although `InvertDecoder` takes precedence for decompression
+    ///     assert_eq!(std::fs::read(&path)?, b"Hello, world!".map(|b| !b));
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    fn can_compress(&self) -> bool {
+        false
+    }
+
+    /// Creates an encoder that writes compressed data to `writer`, using the default level of the format if `level` is `None`.
+    ///
+    /// This is called only if [`Codec::can_compress`] returns `true`. The default implementation returns an error.
+    fn encoder(
+        &self,
+        writer: Box<dyn Write + Send>,
+        level: Option<i32>,
+    ) -> io::Result<Box<dyn CodecEncoder>> {
+        drop((writer, level));
+        Err(error::new(
+            ErrorKind::Unsupported,
+            format!("compressing outputs with {} is not supported", self.name()),
+        ))
+    }
+}
+
+/// A writer created by [`Codec::encoder`] that compresses the data written to it.
+pub trait CodecEncoder: Write + Send {
+    /// Writes the end of the compressed stream and flushes the underlying writer.
+    ///
+    /// The encoder is not written to or flushed afterwards.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// A handle to a codec registered with [`register_codec`].
+///
+/// Handles compare equal if they refer to the same registration.
+#[derive(Clone, Copy)]
+pub struct CustomCodec(&'static dyn Codec);
+
+impl CustomCodec {
+    fn addr(self) -> *const u8 {
+        self.0 as *const dyn Codec as *const u8
+    }
+}
+
+impl fmt::Debug for CustomCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CustomCodec").field(&self.0.name()).finish()
+    }
+}
+
+impl PartialEq for CustomCodec {
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+impl Eq for CustomCodec {}
+
+impl std::hash::Hash for CustomCodec {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.addr().hash(state);
+    }
+}
+
+/// Registers a user-supplied compression format, and returns the [`Compression`] that refers to it.
+///
+/// From then on, inputs and outputs whose paths have the extension of `codec` are decompressed and compressed with it,
+/// as with the built-in formats, and [`Input::decompress_auto`](crate::Input::decompress_auto) detects it by its magic bytes.
+/// Registered codecs take precedence over the built-in formats and over codecs registered before them,
+/// so this can also replace the implementation of a built-in format.
+///
+/// Codecs are never unregistered, so register each one once, at the start of `main` before parsing arguments.
+///
+/// See [`Codec`] for an example.
+pub fn register_codec(codec: impl Codec) -> Compression {
+    let codec: &'static dyn Codec = Box::leak(Box::new(codec));
+    debug!(
+        "registering {} codec for `.{}` files",
+        codec.name(),
+        codec.extension()
+    );
+    CODECS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(codec);
+    Compression::Custom(CustomCodec(codec))
+}
+
+/// A compression level, which can be parsed from a command-line argument.
+///
+/// The range of valid levels depends on the compression format, and is checked when the compressed output is created.
+/// For gzip, the level ranges from 0 (no compression) to 9 (smallest).
+/// For Zstandard, the level ranges from 1 (fastest) to 22 (smallest), and negative levels are even faster.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::{Compression, CompressionLevel, Output};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Compression level.
+///     #[arg(long, default_value = "3")]
+///     compress_level: CompressionLevel,
+///     /// Output file. If `-` is specified, writes to standard output.
+///     output: Output,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     let output = args
+///         .output
+///         .with_compression(Compression::Zstd, args.compress_level)?;
+///     writeln!(output.lock(), "Hello, world!")?;
+///     output.close()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompressionLevel(i32);
+
+impl CompressionLevel {
+    /// Creates a new [`CompressionLevel`] with the given value.
+    pub const fn new(level: i32) -> Self {
+        Self(level)
+    }
+
+    /// Returns the value of this [`CompressionLevel`].
+    pub const fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl From<i32> for CompressionLevel {
+    fn from(value: i32) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for CompressionLevel {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(Self).map_err(|_| {
+            error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid compression level `{s}`; expected an integer"),
+            )
+        })
+    }
+}
+
+impl fmt::Display for CompressionLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A reader that decompresses the data read from an underlying reader.
+pub(crate) struct Decoder(Box<dyn Read + Send>);
+
+impl fmt::Debug for Decoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Decoder").finish_non_exhaustive()
+    }
+}
+
+impl Decoder {
+    #[cfg(feature = "encoding")]
+    pub(crate) fn new(reader: impl Read + Send + 'static) -> Self {
+        Self(Box::new(reader))
+    }
+}
+
+impl Read for Decoder {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+pub(crate) trait Encode: Write + Send {
+    /// Writes the end of the compressed stream and flushes the underlying writer.
+    ///
+    /// The encoder must not be written to or flushed afterwards.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+impl Encode for Box<dyn CodecEncoder> {
+    fn finish(&mut self) -> io::Result<()> {
+        (**self).finish()
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<W: Write + Send> Encode for flate2::write::GzEncoder<W> {
+    fn finish(&mut self) -> io::Result<()> {
+        self.try_finish()?;
+        self.get_mut().flush()
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<W: Write + Send> Encode for zstd::stream::write::Encoder<'static, W> {
+    fn finish(&mut self) -> io::Result<()> {
+        self.do_finish()?;
+        self.get_mut().flush()
+    }
+}
+
+/// Options for compressing an output, set by [`OutputBuilder`](crate::OutputBuilder).
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct EncoderOptions {
+    /// The compression level, or `None` for the default level of the format.
+    pub(crate) level: Option<i32>,
+    /// The number of threads to compress with, or `None` for compressing on the writing thread.
+    pub(crate) threads: Option<NonZeroUsize>,
+    /// The uncompressed size of the frames of seekable Zstandard outputs, or `None` for a single frame.
+    pub(crate) frame_size: Option<usize>,
+}
+
+/// The writer that an encoder writes compressed data to, which discards the data once the encoder is abandoned.
+///
+/// Some encoders finish the compressed stream when dropped, which must not happen for abandoned encoders.
+struct Sink<W> {
+    writer: W,
+    abandoned: Arc<AtomicBool>,
+}
+
+impl<W: Write> Write for Sink<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.abandoned.load(Ordering::Relaxed) {
+            return Ok(buf.len());
+        }
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.abandoned.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.writer.flush()
+    }
+}
+
+/// A writer that compresses the data written to it into an underlying writer.
+pub(crate) struct Encoder {
+    inner: Box<dyn Encode>,
+    written: bool,
+    finished: bool,
+    abandoned: Arc<AtomicBool>,
+}
+
+impl Encoder {
+    fn new(inner: impl Encode + 'static, abandoned: Arc<AtomicBool>) -> Self {
+        Self {
+            inner: Box::new(inner),
+            written: false,
+            finished: false,
+            abandoned,
+        }
+    }
+
+    /// Returns `true` if any data has been written to this encoder.
+    pub(crate) fn is_written(&self) -> bool {
+        self.written
+    }
+
+    /// Stops writing to the underlying writer, leaving the compressed stream unfinished.
+    ///
+    /// This makes the truncation detectable by decompressors, unlike a stream that is finished after partial data.
+    pub(crate) fn abandon(&mut self) {
+        self.abandoned.store(true, Ordering::Relaxed);
+        self.finished = true;
+    }
+
+    /// Writes the end of the compressed stream and flushes the underlying writer.
+    ///
+    /// Calling this method again does nothing, and writing to the encoder afterwards fails.
+    pub(crate) fn finish(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.inner.finish()?;
+        self.finished = true;
+        Ok(())
+    }
+
+    fn check_finished(&self) -> io::Result<()> {
+        if self.finished {
+            return Err(error::new(
+                ErrorKind::Other,
+                "cannot write to a compressed output that has already been closed",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Encoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encoder")
+            .field("finished", &self.finished)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_finished()?;
+        self.written |= !buf.is_empty();
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.inner.flush()
+    }
+}
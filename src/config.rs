@@ -0,0 +1,176 @@
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{logging::debug, Buffering, ClosedStdio};
+
+static GLOBAL: RwLock<Option<ClapFileConfig>> = RwLock::new(None);
+
+fn read() -> RwLockReadGuard<'static, Option<ClapFileConfig>> {
+    GLOBAL.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write() -> RwLockWriteGuard<'static, Option<ClapFileConfig>> {
+    GLOBAL.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Process-wide defaults for the value parsers of [`Input`](crate::Input) and [`Output`](crate::Output).
+///
+/// Applications with many file arguments can set their defaults once with [`ClapFileConfig::install`]
+/// instead of repeating the same options on every [`InputValueParser`](crate::InputValueParser)
+/// and [`OutputValueParser`](crate::OutputValueParser).
+/// Options set on a value parser take precedence over the installed defaults.
+///
+/// The configuration is read when values are parsed, so install it before parsing the command line.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::{Buffering, ClapFileConfig, ClosedStdio, Input, Output};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     input: Input,
+///     #[arg(long, default_value = "-")]
+///     output: Output,
+/// }
+///
+/// ClapFileConfig::new()
+///     .input_capacity(1024 * 1024)
+///     .output_buffering(Buffering::Line)
+///     .closed_stdio(ClosedStdio::Null)
+///     .install();
+///
+/// let args = Args::parse_from(["prog", "Cargo.toml"]);
+/// # ClapFileConfig::reset();
+/// ```
+///
+/// Outputs can be written to new directories and replaced atomically:
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::{ClapFileConfig, Output};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     output: Output,
+/// }
+///
+/// ClapFileConfig::new().create_dirs(true).atomic(true).install();
+///
+/// let dir = std::env::temp_dir().join(format!("clap-file-doc-config-{}", std::process::id()));
+/// let path = dir.join("out/report.txt");
+/// let args = Args::parse_from(["prog".as_ref(), path.as_os_str()]);
+/// // The file appears only when the output is closed
+/// assert!(dir.join("out").is_dir() && !path.exists());
+/// args.output.close().unwrap();
+/// assert!(path.exists());
+/// # ClapFileConfig::reset();
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClapFileConfig {
+    pub(crate) input_capacity: Option<usize>,
+    pub(crate) output_capacity: Option<usize>,
+    pub(crate) output_buffering: Buffering,
+    pub(crate) closed_stdio: ClosedStdio,
+    pub(crate) sync_on_close: bool,
+    pub(crate) expand_tilde: bool,
+    pub(crate) create_dirs: bool,
+    pub(crate) atomic: bool,
+    pub(crate) detect_compression: bool,
+}
+
+impl ClapFileConfig {
+    /// Creates a new [`ClapFileConfig`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the installed configuration, or the default configuration if none is installed.
+    pub fn current() -> Self {
+        read().clone().unwrap_or_default()
+    }
+
+    /// Installs this configuration as the process-wide defaults, replacing the previous one.
+    pub fn install(&mut self) {
+        debug!("installing configuration {self:?}");
+        *write() = Some(self.clone());
+    }
+
+    /// Removes the installed configuration, restoring the built-in defaults.
+    pub fn reset() {
+        *write() = None;
+    }
+
+    /// Sets the capacity of the read buffer of opened input files.
+    ///
+    /// See [`InputValueParser::capacity`](crate::InputValueParser::capacity).
+    pub fn input_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.input_capacity = Some(capacity);
+        self
+    }
+
+    /// Sets the capacity of the write buffer of created output files.
+    ///
+    /// See [`OutputValueParser::capacity`](crate::OutputValueParser::capacity).
+    pub fn output_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.output_capacity = Some(capacity);
+        self
+    }
+
+    /// Sets when the write buffer of created output files is flushed.
+    ///
+    /// Defaults to [`Buffering::Auto`]. See [`OutputBuilder::buffering`](crate::OutputBuilder::buffering).
+    pub fn output_buffering(&mut self, buffering: Buffering) -> &mut Self {
+        self.output_buffering = buffering;
+        self
+    }
+
+    /// Sets what to do when `-` is given but the standard stream is closed.
+    ///
+    /// Defaults to [`ClosedStdio::Error`].
+    pub fn closed_stdio(&mut self, closed_stdio: ClosedStdio) -> &mut Self {
+        self.closed_stdio = closed_stdio;
+        self
+    }
+
+    /// Sets whether closing created output files synchronizes them to the storage device.
+    ///
+    /// Defaults to `false`. See [`OutputBuilder::sync_on_close`](crate::OutputBuilder::sync_on_close).
+    pub fn sync_on_close(&mut self, sync: bool) -> &mut Self {
+        self.sync_on_close = sync;
+        self
+    }
+
+    /// Sets whether to expand a leading `~` or `~user` in input and output paths to the home directory.
+    ///
+    /// Defaults to `false`. See [`InputValueParser::expand_tilde`](crate::InputValueParser::expand_tilde).
+    pub fn expand_tilde(&mut self, expand: bool) -> &mut Self {
+        self.expand_tilde = expand;
+        self
+    }
+
+    /// Sets whether to create the missing parent directories of created output files.
+    ///
+    /// Defaults to `false`. See [`OutputBuilder::create_dirs`](crate::OutputBuilder::create_dirs).
+    pub fn create_dirs(&mut self, create_dirs: bool) -> &mut Self {
+        self.create_dirs = create_dirs;
+        self
+    }
+
+    /// Sets whether created output files are written to a temporary file and renamed over the target when closed.
+    ///
+    /// Defaults to `false`. See [`OutputBuilder::atomic`](crate::OutputBuilder::atomic).
+    pub fn atomic(&mut self, atomic: bool) -> &mut Self {
+        self.atomic = atomic;
+        self
+    }
+
+    /// Sets whether to detect the compression of opened input files from their contents, regardless of their extension.
+    ///
+    /// Defaults to `false`. See [`InputBuilder::detect_compression`](crate::InputBuilder::detect_compression).
+    pub fn detect_compression(&mut self, detect: bool) -> &mut Self {
+        self.detect_compression = detect;
+        self
+    }
+}
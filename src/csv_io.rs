@@ -0,0 +1,212 @@
+use std::{fmt, io, path::Path, str::FromStr};
+
+use crate::{error, Compression, ErrorKind};
+
+/// A field delimiter of CSV data, which can be parsed from a command-line argument.
+///
+/// A single ASCII character is used as is. The names `comma`, `tab`, `semicolon`, `pipe`, and `space`,
+/// and the escape `\t` are also accepted, since some delimiters are awkward to pass through a shell.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap_file::CsvDelimiter;
+///
+/// assert_eq!("tab".parse::<CsvDelimiter>().unwrap().as_byte(), b'\t');
+/// assert_eq!(r"\t".parse::<CsvDelimiter>().unwrap().as_byte(), b'\t');
+/// assert_eq!(";".parse::<CsvDelimiter>().unwrap().as_byte(), b';');
+/// assert!("::".parse::<CsvDelimiter>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CsvDelimiter(u8);
+
+impl CsvDelimiter {
+    /// The comma (`,`), used by CSV files.
+    pub const COMMA: Self = Self(b',');
+    /// The tab character, used by TSV files.
+    pub const TAB: Self = Self(b'\t');
+
+    /// Creates a new [`CsvDelimiter`] from an ASCII byte.
+    ///
+    /// Returns `None` if `byte` is not ASCII, as the `csv` crate only supports single-byte delimiters.
+    pub const fn new(byte: u8) -> Option<Self> {
+        if byte.is_ascii() {
+            Some(Self(byte))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the delimiter as a byte.
+    pub const fn as_byte(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the delimiter conventionally used by files with the extension of `path`: tab for `.tsv` files, and comma otherwise.
+    ///
+    /// The extension of a compression format is skipped, so `.tsv.gz` files are also tab-separated.
+    pub fn from_path(path: &Path) -> Self {
+        let path = match Compression::from_path(path) {
+            Some(_) => Path::new(path.file_stem().unwrap_or_default()),
+            None => path,
+        };
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("tsv") => Self::TAB,
+            _ => Self::COMMA,
+        }
+    }
+}
+
+impl FromStr for CsvDelimiter {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let byte = match s.to_ascii_lowercase().as_str() {
+            "comma" => b',',
+            "tab" | "\\t" => b'\t',
+            "semicolon" => b';',
+            "pipe" => b'|',
+            "space" => b' ',
+            _ => match s.as_bytes() {
+                &[byte] if byte.is_ascii() => byte,
+                _ => {
+                    return Err(error::new(
+                        ErrorKind::InvalidInput,
+                        format!("invalid CSV delimiter `{s}`; expected a single ASCII character or one of comma, tab, semicolon, pipe, space"),
+                    ))
+                }
+            },
+        };
+        Ok(Self(byte))
+    }
+}
+
+impl fmt::Display for CsvDelimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            b'\t' => f.write_str("tab"),
+            b' ' => f.write_str("space"),
+            byte => write!(f, "{}", char::from(byte)),
+        }
+    }
+}
+
+/// Options for the CSV readers and writers created by [`Input::csv_reader_with`](crate::Input::csv_reader_with)
+/// and [`Output::csv_writer_with`](crate::Output::csv_writer_with).
+///
+/// The options are plain values, so they can be filled in from command-line arguments.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io;
+///
+/// use clap::Parser as _;
+/// use clap_file::{CsvDelimiter, CsvOptions, Input, Output};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Field delimiter. Defaults to tab for `.tsv` files and comma otherwise.
+///     #[arg(short, long)]
+///     delimiter: Option<CsvDelimiter>,
+///     /// Treats the first row as a data record rather than a header.
+///     #[arg(long)]
+///     no_headers: bool,
+///     /// Input file. If `-` is specified, reads from standard input.
+///     input: Input,
+///     /// Output file. If `-` is specified, writes to standard output.
+///     output: Output,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     let mut options = CsvOptions::new();
+///     options.has_headers(!args.no_headers);
+///     if let Some(delimiter) = args.delimiter {
+///         options.delimiter(delimiter);
+///     }
+///     let mut reader = args.input.csv_reader_with(&options);
+///     let mut writer = args.output.csv_writer_with(&options);
+///     if !args.no_headers {
+///         writer.write_record(reader.headers()?)?;
+///     }
+///     for record in reader.records() {
+///         let record = record?;
+///         writer.write_record(record.iter().rev())?;
+///     }
+///     writer.flush()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    delimiter: Option<CsvDelimiter>,
+    has_headers: bool,
+    flexible: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsvOptions {
+    /// Creates a new [`CsvOptions`] with the default options.
+    pub fn new() -> Self {
+        Self {
+            delimiter: None,
+            has_headers: true,
+            flexible: false,
+        }
+    }
+
+    /// Sets the field delimiter.
+    ///
+    /// If not set, the delimiter is chosen from the extension of the path with [`CsvDelimiter::from_path`],
+    /// and is a comma for standard input and output.
+    pub fn delimiter(&mut self, delimiter: CsvDelimiter) -> &mut Self {
+        self.delimiter = Some(delimiter);
+        self
+    }
+
+    /// Sets whether the first row is a header row. The default is `true`.
+    pub fn has_headers(&mut self, has_headers: bool) -> &mut Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Sets whether records may have different numbers of fields. The default is `false`.
+    pub fn flexible(&mut self, flexible: bool) -> &mut Self {
+        self.flexible = flexible;
+        self
+    }
+
+    fn delimiter_for(&self, path: Option<&Path>) -> u8 {
+        self.delimiter
+            .unwrap_or_else(|| path.map_or(CsvDelimiter::COMMA, CsvDelimiter::from_path))
+            .as_byte()
+    }
+
+    pub(crate) fn reader<R>(&self, path: Option<&Path>, reader: R) -> csv::Reader<R>
+    where
+        R: io::Read,
+    {
+        csv::ReaderBuilder::new()
+            .delimiter(self.delimiter_for(path))
+            .has_headers(self.has_headers)
+            .flexible(self.flexible)
+            .from_reader(reader)
+    }
+
+    pub(crate) fn writer<W>(&self, path: Option<&Path>, writer: W) -> csv::Writer<W>
+    where
+        W: io::Write,
+    {
+        csv::WriterBuilder::new()
+            .delimiter(self.delimiter_for(path))
+            .has_headers(self.has_headers)
+            .flexible(self.flexible)
+            .from_writer(writer)
+    }
+}
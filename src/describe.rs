@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+/// The kind of an input source or output sink.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum IoKind {
+    /// Standard input.
+    Stdin,
+    /// Standard output.
+    Stdout,
+    /// The controlling terminal.
+    Tty,
+    /// A file specified by a path.
+    File,
+}
+
+/// A machine-readable description of an input source or output sink.
+///
+/// This is returned by [`Input::describe`](crate::Input::describe) and [`Output::describe`](crate::Output::describe).
+/// With the `serde` feature enabled, this type implements `serde::Serialize`,
+/// so tools can report which IO they are doing in their `--json` diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct IoDescription {
+    /// The kind of the source or sink.
+    pub kind: IoKind,
+    /// The path of the file, if the source or sink is a file.
+    pub path: Option<PathBuf>,
+    /// The size of the file in bytes, if known.
+    pub size: Option<u64>,
+    /// Whether the source or sink is a terminal.
+    pub is_tty: bool,
+    /// The name of the compression format, if the data is compressed.
+    pub compression: Option<&'static str>,
+    /// The name of the character encoding, if known.
+    pub encoding: Option<&'static str>,
+}
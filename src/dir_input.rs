@@ -0,0 +1,237 @@
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{error, ErrorKind, Input, InputFilter, InputOrder, SkipReason};
+
+/// A directory whose regular files are read as inputs, recursively.
+///
+/// When parsed from a string, the path must refer to a directory.
+/// [`DirInput::inputs`] walks the directory in alphabetical order and opens each regular file that passes the filters.
+/// By default, all depths are visited, hidden files and directories (whose names start with `.`) are skipped,
+/// files with any extension are included, and the entries of each directory are visited in lexicographic order.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io;
+///
+/// use clap::Parser as _;
+/// use clap_file::DirInput;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Directory to process.
+///     dir: DirInput,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let mut args = Args::parse_from(["prog", "src"]);
+///     args.dir.max_depth(1).extensions(["rs"]);
+///     for input in args.dir.inputs() {
+///         let input = input?;
+///         println!("{}", input.path().unwrap().display());
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DirInput {
+    path: PathBuf,
+    max_depth: Option<usize>,
+    include_hidden: bool,
+    filter: InputFilter,
+    order: InputOrder,
+}
+
+impl DirInput {
+    /// Creates a new [`DirInput`] for the directory at the given path.
+    ///
+    /// Returns an error if the path does not refer to a directory.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        if !fs::metadata(&path)?.is_dir() {
+            return Err(error::new(
+                ErrorKind::InvalidInput,
+                format!("input path `{}` is not a directory", path.display()),
+            ));
+        }
+        Ok(Self {
+            path,
+            max_depth: None,
+            include_hidden: false,
+            filter: InputFilter::default(),
+            order: InputOrder::default(),
+        })
+    }
+
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Sets the maximum depth to descend; files directly under the directory have depth 1.
+    ///
+    /// If not set, all depths are visited.
+    pub fn max_depth(&mut self, depth: usize) -> &mut Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Sets whether to include hidden files and directories, whose names start with `.`.
+    ///
+    /// Defaults to `false`.
+    pub fn include_hidden(&mut self, include: bool) -> &mut Self {
+        self.include_hidden = include;
+        self
+    }
+
+    /// Restricts the inputs to files with one of the given extensions (without the leading `.`).
+    ///
+    /// If not set, files with any extension are included. See [`InputFilter::extensions`].
+    pub fn extensions<I, S>(&mut self, extensions: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.filter.extensions(extensions);
+        self
+    }
+
+    /// Sets the filters applied to files, replacing the previously set ones (including [`extensions`](Self::extensions)).
+    pub fn filter(&mut self, filter: InputFilter) -> &mut Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Sets the order in which the entries of each directory are visited.
+    ///
+    /// Defaults to [`InputOrder::Lexicographic`].
+    pub fn order(&mut self, order: InputOrder) -> &mut Self {
+        self.order = order;
+        self
+    }
+
+    /// Returns an iterator that opens each regular file under the directory that passes the filters.
+    pub fn inputs(&self) -> DirInputs<'_> {
+        DirInputs {
+            dir: self,
+            stack: vec![(self.path.clone(), 0)],
+            skipped: vec![],
+        }
+    }
+
+    fn is_visible(&self, path: &Path) -> bool {
+        self.include_hidden
+            || !path
+                .file_name()
+                .is_some_and(|name| name.as_encoded_bytes().starts_with(b"."))
+    }
+}
+
+impl FromStr for DirInput {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::open(PathBuf::from(s))
+    }
+}
+
+/// An iterator over the files under a [`DirInput`], created by [`DirInput::inputs`].
+#[derive(Debug)]
+pub struct DirInputs<'a> {
+    dir: &'a DirInput,
+    // Paths to visit, with their depths. The last element is visited next.
+    stack: Vec<(PathBuf, usize)>,
+    skipped: Vec<(PathBuf, SkipReason)>,
+}
+
+impl DirInputs<'_> {
+    /// Returns the paths skipped so far, with the reasons they were skipped.
+    ///
+    /// Errors are returned from the iterator instead of being reported here.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::DirInput;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let mut dir = DirInput::open("src".into())?;
+    ///     dir.extensions(["md"]);
+    ///     let mut inputs = dir.inputs();
+    ///     for input in &mut inputs {
+    ///         input?;
+    ///     }
+    ///     for (path, reason) in inputs.skipped() {
+    ///         println!("skipped {}: {reason}", path.display());
+    ///     }
+    ///     assert!(!inputs.skipped().is_empty());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn skipped(&self) -> &[(PathBuf, SkipReason)] {
+        &self.skipped
+    }
+
+    /// Converts this iterator into the paths skipped so far.
+    pub fn into_skipped(self) -> Vec<(PathBuf, SkipReason)> {
+        self.skipped
+    }
+
+    fn push_children(&mut self, path: &Path, depth: usize) -> io::Result<()> {
+        let (mut children, hidden): (Vec<_>, Vec<_>) = fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?
+            .into_iter()
+            .partition(|child| self.dir.is_visible(child));
+        self.skipped
+            .extend(hidden.into_iter().map(|child| (child, SkipReason::Hidden)));
+        self.dir.order.sort_by_path(&mut children, |child| child);
+        self.stack
+            .extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+        Ok(())
+    }
+}
+
+impl Iterator for DirInputs<'_> {
+    type Item = io::Result<Input>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((path, depth)) = self.stack.pop() {
+            // Symbolic links to directories are not followed, to avoid cycles.
+            let file_type = match fs::symlink_metadata(&path) {
+                Ok(metadata) => metadata.file_type(),
+                Err(e) => return Some(Err(e)),
+            };
+            if file_type.is_dir() {
+                if self.dir.max_depth.map_or(true, |max| depth < max) {
+                    if let Err(e) = self.push_children(&path, depth) {
+                        return Some(Err(e));
+                    }
+                } else {
+                    self.skipped.push((path, SkipReason::MaxDepth));
+                }
+                continue;
+            }
+            if depth == 0 {
+                continue;
+            }
+            match fs::metadata(&path) {
+                Ok(metadata) if !metadata.is_file() => {
+                    self.skipped.push((path, SkipReason::NotFile));
+                }
+                Ok(metadata) if !self.dir.filter.matches(&path, &metadata) => {
+                    self.skipped.push((path, SkipReason::Filtered));
+                }
+                Ok(_) => return Some(Input::open(path)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
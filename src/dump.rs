@@ -0,0 +1,131 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, Read, Write},
+};
+
+/// A reader or writer adapter that hexdumps the first bytes flowing through it to standard error.
+///
+/// This struct is created by [`Input::debug_dump`](crate::Input::debug_dump) and [`Output::debug_dump`](crate::Output::debug_dump).
+/// It helps troubleshoot encoding or compression issues in the field.
+#[derive(Debug)]
+pub struct DebugDump<T> {
+    inner: T,
+    label: String,
+    offset: usize,
+    remaining: usize,
+}
+
+impl<T> DebugDump<T> {
+    pub(crate) fn new(inner: T, label: String, limit: usize) -> Self {
+        Self {
+            inner,
+            label,
+            offset: 0,
+            remaining: limit,
+        }
+    }
+
+    /// Returns a reference to the underlying reader or writer.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader or writer.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes this adapter and returns the underlying reader or writer.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+fn dump(label: &str, offset: &mut usize, remaining: &mut usize, bytes: &[u8]) {
+    let bytes = &bytes[..bytes.len().min(*remaining)];
+    if bytes.is_empty() {
+        return;
+    }
+    let mut out = String::new();
+    for (i, row) in bytes.chunks(16).enumerate() {
+        let _ = write!(out, "{label}: {:08x} ", *offset + i * 16);
+        for byte in row {
+            let _ = write!(out, " {byte:02x}");
+        }
+        let padding = (16 - row.len()) * 3;
+        let _ = write!(out, "{:padding$}  |", "");
+        for &byte in row {
+            let c = if byte.is_ascii_graphic() || byte == b' ' {
+                char::from(byte)
+            } else {
+                '.'
+            };
+            out.push(c);
+        }
+        out.push_str("|\n");
+    }
+    *offset += bytes.len();
+    *remaining -= bytes.len();
+    let _ = io::stderr().lock().write_all(out.as_bytes());
+}
+
+impl<R> Read for DebugDump<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        dump(
+            &self.label,
+            &mut self.offset,
+            &mut self.remaining,
+            &buf[..n],
+        );
+        Ok(n)
+    }
+}
+
+impl<R> BufRead for DebugDump<R>
+where
+    R: BufRead,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.remaining > 0 {
+            // The buffer has already been filled, so this does not perform any IO.
+            if let Ok(buf) = self.inner.fill_buf() {
+                let amt = amt.min(buf.len());
+                dump(
+                    &self.label,
+                    &mut self.offset,
+                    &mut self.remaining,
+                    &buf[..amt],
+                );
+            }
+        }
+        self.inner.consume(amt);
+    }
+}
+
+impl<W> Write for DebugDump<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        dump(
+            &self.label,
+            &mut self.offset,
+            &mut self.remaining,
+            &buf[..n],
+        );
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
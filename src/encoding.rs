@@ -0,0 +1,153 @@
+use std::{
+    fmt,
+    io::{self, Read},
+    str::FromStr,
+};
+
+use crate::{error, ErrorKind, Input};
+
+/// A character encoding that inputs can be transcoded from, used with [`Input::transcode`].
+///
+/// Encodings are identified by the labels defined in the [WHATWG Encoding Standard](https://encoding.spec.whatwg.org/#names-and-labels),
+/// such as `utf-16le`, `shift_jis`, or `latin1`. Note that the standard maps `latin1` and `iso-8859-1` to `windows-1252`, a superset of Latin-1.
+///
+/// [`Encoding`] implements [`FromStr`], so it can be used as the type of an argument like `--input-encoding`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, BufRead as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::{Encoding, Input};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Character encoding of the input, such as `utf-16` or `shift_jis`.
+///     #[arg(long)]
+///     input_encoding: Option<Encoding>,
+///     input: Input,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     let mut input = args.input;
+///     if let Some(encoding) = args.input_encoding {
+///         input = input.transcode(encoding)?;
+///     }
+///     for line in input.lock().lines() {
+///         println!("{}", line?);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Encoding(&'static encoding_rs::Encoding);
+
+impl Encoding {
+    /// UTF-8.
+    pub const UTF_8: Self = Self(encoding_rs::UTF_8);
+    /// UTF-16 in little-endian byte order.
+    pub const UTF_16LE: Self = Self(encoding_rs::UTF_16LE);
+    /// UTF-16 in big-endian byte order.
+    pub const UTF_16BE: Self = Self(encoding_rs::UTF_16BE);
+    /// Shift_JIS.
+    pub const SHIFT_JIS: Self = Self(encoding_rs::SHIFT_JIS);
+    /// windows-1252, which is also used for the `latin1` and `iso-8859-1` labels.
+    pub const WINDOWS_1252: Self = Self(encoding_rs::WINDOWS_1252);
+
+    /// Returns the encoding for `label`, ignoring case and surrounding whitespace.
+    ///
+    /// Returns `None` if `label` is not a known encoding label.
+    pub fn for_label(label: &str) -> Option<Self> {
+        encoding_rs::Encoding::for_label(label.as_bytes()).map(Self)
+    }
+
+    /// Returns the canonical name of this encoding, e.g. `UTF-16LE`.
+    pub fn name(self) -> &'static str {
+        self.0.name()
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::for_label(s).ok_or_else(|| {
+            error::new(
+                ErrorKind::InvalidInput,
+                format!("unknown character encoding `{s}`"),
+            )
+        })
+    }
+}
+
+/// A reader that transcodes the data read from an [`Input`] into UTF-8.
+///
+/// A byte order mark at the start of the data takes precedence over the specified encoding, and is removed.
+/// Malformed sequences are replaced with U+FFFD REPLACEMENT CHARACTER.
+pub(crate) struct Transcoder {
+    source: Input,
+    decoder: encoding_rs::Decoder,
+    input: Box<[u8]>,
+    input_range: (usize, usize),
+    output: Box<[u8]>,
+    output_range: (usize, usize),
+    eof: bool,
+    finished: bool,
+}
+
+impl Transcoder {
+    const BUF_SIZE: usize = 8 * 1024;
+
+    pub(crate) fn new(source: Input, encoding: Encoding) -> Self {
+        Self {
+            source,
+            decoder: encoding.0.new_decoder(),
+            input: vec![0; Self::BUF_SIZE].into_boxed_slice(),
+            input_range: (0, 0),
+            output: vec![0; Self::BUF_SIZE].into_boxed_slice(),
+            output_range: (0, 0),
+            eof: false,
+            finished: false,
+        }
+    }
+}
+
+impl Read for Transcoder {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let (start, end) = self.output_range;
+            if start < end {
+                let n = buf.len().min(end - start);
+                buf[..n].copy_from_slice(&self.output[start..start + n]);
+                self.output_range.0 += n;
+                return Ok(n);
+            }
+            if self.finished || buf.is_empty() {
+                return Ok(0);
+            }
+            let (start, end) = self.input_range;
+            if start == end && !self.eof {
+                let n = self.source.read(&mut self.input)?;
+                self.eof = n == 0;
+                self.input_range = (0, n);
+            }
+            let (start, end) = self.input_range;
+            let (result, read, written, _) =
+                self.decoder
+                    .decode_to_utf8(&self.input[start..end], &mut self.output, self.eof);
+            self.input_range.0 += read;
+            self.output_range = (0, written);
+            if self.eof && result == encoding_rs::CoderResult::InputEmpty {
+                self.finished = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::{self, Write};
+
+use crate::LineEnding;
+
+/// A writer adapter that converts line endings to a single [`LineEnding`].
+///
+/// This struct is created by [`Output::normalize_eol`](crate::Output::normalize_eol).
+/// `\n`, `\r\n`, and lone `\r` are all treated as line terminators, as in [`UniversalLines`](crate::UniversalLines),
+/// including `\r\n` split across two writes.
+#[derive(Debug)]
+pub struct EolWriter<W> {
+    inner: W,
+    ending: LineEnding,
+    // Set when the last byte written was `\r`, so that a following `\n` belongs to the same terminator.
+    after_cr: bool,
+}
+
+impl<W> EolWriter<W> {
+    pub(crate) fn new(inner: W, ending: LineEnding) -> Self {
+        Self {
+            inner,
+            ending,
+            after_cr: false,
+        }
+    }
+
+    /// Returns the line ending that line terminators are converted to.
+    pub fn line_ending(&self) -> LineEnding {
+        self.ending
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Consumes this adapter and returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> Write for EolWriter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        if self.after_cr && !rest.is_empty() {
+            self.after_cr = false;
+            if rest[0] == b'\n' {
+                rest = &rest[1..];
+            }
+        }
+        let ending = self.ending.as_str().as_bytes();
+        while let Some(i) = rest.iter().position(|&b| b == b'\n' || b == b'\r') {
+            self.inner.write_all(&rest[..i])?;
+            self.inner.write_all(ending)?;
+            rest = match (rest[i], rest.get(i + 1)) {
+                (b'\r', Some(b'\n')) => &rest[i + 2..],
+                (b'\r', None) => {
+                    self.after_cr = true;
+                    &[]
+                }
+                _ => &rest[i + 1..],
+            };
+        }
+        self.inner.write_all(rest)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    error::Error as StdError,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::EscapedPath;
+
+// `io::ErrorKind::ReadOnlyFilesystem` and `io::ErrorKind::IsADirectory` are not available on the MSRV,
+// and there is no kind for bad file descriptors, so check the raw error codes instead. They have the same values on Linux, macOS and the BSDs.
+#[cfg(unix)]
+const EROFS: i32 = 30;
+#[cfg(unix)]
+const EISDIR: i32 = 21;
+#[cfg(unix)]
+const EBADF: i32 = 9;
+
+/// A category of errors returned by this crate.
+///
+/// The functions of this crate return [`io::Error`]s. Use [`ErrorKind::of`] to classify them,
+/// so that applications can branch on failure categories without matching on error messages.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap_file::{ErrorKind, Input};
+///
+/// let error = Input::open("no-such-file".into()).unwrap_err();
+/// assert_eq!(ErrorKind::of(&error), ErrorKind::NotFound);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The file was not found.
+    NotFound,
+    /// The operation lacked the necessary privileges.
+    PermissionDenied,
+    /// The file already exists.
+    AlreadyExists,
+    /// The path refers to a directory where a file was expected.
+    IsDirectory,
+    /// The file is on a read-only file system.
+    ReadOnlyFilesystem,
+    /// Standard input (`-`) was specified more than once.
+    StdinConflict,
+    /// Standard input or output is closed.
+    StdioClosed,
+    /// The input ended before the expected amount of data was read.
+    UnexpectedEof,
+    /// The operation is not supported for this input or output.
+    Unsupported,
+    /// The argument or data was invalid.
+    InvalidInput,
+    /// The data read was not in the expected format, e.g. not valid UTF-8.
+    InvalidData,
+    /// The file is locked by another process.
+    WouldBlock,
+    /// Any other error.
+    Other,
+}
+
+impl ErrorKind {
+    /// Classifies an error returned by this crate.
+    ///
+    /// Errors that wrap other errors (e.g. errors annotated with a hint) are classified by their cause.
+    pub fn of(error: &io::Error) -> Self {
+        let mut error = error;
+        while let Some(inner) = error.get_ref() {
+            if let Some(classified) = inner.downcast_ref::<Classified>() {
+                return classified.kind;
+            }
+            match inner.source().and_then(|e| e.downcast_ref::<io::Error>()) {
+                Some(source) => error = source,
+                None => break,
+            }
+        }
+
+        #[cfg(unix)]
+        match error.raw_os_error() {
+            Some(EROFS) => return Self::ReadOnlyFilesystem,
+            Some(EISDIR) => return Self::IsDirectory,
+            Some(EBADF) => return Self::StdioClosed,
+            _ => {}
+        }
+
+        match error.kind() {
+            io::ErrorKind::NotFound => Self::NotFound,
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            io::ErrorKind::AlreadyExists => Self::AlreadyExists,
+            io::ErrorKind::UnexpectedEof => Self::UnexpectedEof,
+            io::ErrorKind::Unsupported => Self::Unsupported,
+            io::ErrorKind::InvalidInput => Self::InvalidInput,
+            io::ErrorKind::InvalidData => Self::InvalidData,
+            io::ErrorKind::WouldBlock => Self::WouldBlock,
+            _ => Self::Other,
+        }
+    }
+
+    fn io_kind(self) -> io::ErrorKind {
+        match self {
+            Self::NotFound => io::ErrorKind::NotFound,
+            Self::PermissionDenied | Self::ReadOnlyFilesystem => io::ErrorKind::PermissionDenied,
+            Self::AlreadyExists => io::ErrorKind::AlreadyExists,
+            Self::IsDirectory | Self::StdinConflict | Self::InvalidInput => {
+                io::ErrorKind::InvalidInput
+            }
+            Self::StdioClosed => io::ErrorKind::BrokenPipe,
+            Self::UnexpectedEof => io::ErrorKind::UnexpectedEof,
+            Self::InvalidData => io::ErrorKind::InvalidData,
+            Self::WouldBlock => io::ErrorKind::WouldBlock,
+            Self::Unsupported => io::ErrorKind::Unsupported,
+            Self::Other => io::ErrorKind::Other,
+        }
+    }
+}
+
+/// An operation on a file that can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    /// Opening a file for reading.
+    Open,
+    /// Creating a file for writing.
+    Create,
+    /// Parsing the contents of a file.
+    Parse,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Create => write!(f, "create"),
+            Self::Parse => write!(f, "parse"),
+        }
+    }
+}
+
+/// An error that occurred while operating on a file, with the path and the operation.
+///
+/// The value parsers of this crate report failures with this type, so that error messages
+/// tell which file could not be opened or created, e.g. ``failed to open `input.txt`: No such file or directory``.
+/// It converts into an [`io::Error`] that [`ErrorKind::of`] classifies by the underlying error.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+///
+/// use clap_file::{Error, ErrorKind, Operation};
+///
+/// let io_error = std::fs::File::open("no-such-file").unwrap_err();
+/// let error = Error::new(Operation::Open, "no-such-file", io_error);
+/// assert_eq!(error.path(), Path::new("no-such-file"));
+/// assert_eq!(error.kind(), ErrorKind::NotFound);
+/// assert!(error.to_string().starts_with("failed to open `no-such-file`: "));
+///
+/// let io_error = std::io::Error::from(error);
+/// assert_eq!(ErrorKind::of(&io_error), ErrorKind::NotFound);
+/// ```
+#[derive(Debug)]
+pub struct Error {
+    operation: Operation,
+    path: PathBuf,
+    source: io::Error,
+}
+
+impl Error {
+    /// Creates a new [`Error`] for `operation` on the file at `path`, caused by `source`.
+    pub fn new(operation: Operation, path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Self {
+            operation,
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Returns the operation that failed.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Returns the path of the file the operation failed on.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the category of the underlying error.
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::of(&self.source)
+    }
+
+    /// Returns the underlying error, which may carry an OS error code.
+    pub fn io_error(&self) -> &io::Error {
+        &self.source
+    }
+
+    /// Converts this error into the underlying error.
+    pub fn into_io_error(self) -> io::Error {
+        self.source
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to {} `{}`: {}",
+            self.operation,
+            EscapedPath::new(&self.path),
+            self.source
+        )
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(value: Error) -> Self {
+        io::Error::new(value.source.kind(), value)
+    }
+}
+
+/// An error raised by this crate, tagged with its [`ErrorKind`].
+#[derive(Debug)]
+struct Classified {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl fmt::Display for Classified {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl StdError for Classified {}
+
+/// Creates an [`io::Error`] that [`ErrorKind::of`] classifies as `kind`.
+pub(crate) fn new(kind: ErrorKind, message: impl Into<String>) -> io::Error {
+    io::Error::new(
+        kind.io_kind(),
+        Classified {
+            kind,
+            message: message.into(),
+        },
+    )
+}
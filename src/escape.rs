@@ -0,0 +1,121 @@
+use std::{fmt, path::Path};
+
+/// A wrapper for displaying a path with control characters and invalid UTF-8 escaped.
+///
+/// [`Path::display`] prints file names as they are, so a file name containing a newline breaks a line-oriented
+/// message in two, and invalid UTF-8 is replaced with U+FFFD, which makes different file names look the same.
+/// This wrapper escapes control characters as in Rust string literals (`\n`, `\t`, `\u{1b}`, ...)
+/// and invalid UTF-8 bytes as `\xNN`, so that every file name is printed on one line and can be told apart.
+///
+/// The error messages of this crate display paths with this wrapper.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+///
+/// use clap_file::EscapedPath;
+///
+/// let path = Path::new("report\n2024.txt");
+/// assert_eq!(EscapedPath::new(path).to_string(), "report\\n2024.txt");
+/// assert_eq!(EscapedPath::new(Path::new("dir/file.txt")).to_string(), "dir/file.txt");
+/// ```
+///
+/// Invalid UTF-8 bytes are escaped on Unix:
+///
+/// ```rust
+/// # #[cfg(unix)] {
+/// use std::{ffi::OsStr, os::unix::ffi::OsStrExt as _, path::Path};
+///
+/// use clap_file::EscapedPath;
+///
+/// let path = Path::new(OsStr::from_bytes(b"caf\xe9.txt"));
+/// assert_eq!(EscapedPath::new(path).to_string(), "caf\\xe9.txt");
+/// # }
+/// ```
+///
+/// File names that start with `-` are parsed as files, not as standard input, when given after `--`
+/// or with a leading `./`. Names with newlines round-trip through parsing and [`Input::path`](crate::Input::path):
+///
+/// ```rust
+/// use std::{fs, path::Path};
+///
+/// use clap::Parser as _;
+/// use clap_file::{EscapedPath, Input};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     inputs: Vec<Input>,
+/// }
+///
+/// let dir = std::env::temp_dir().join(format!("clap-file-escape-{}", std::process::id()));
+/// fs::create_dir_all(&dir).unwrap();
+/// let dash = dir.join("-n");
+/// let newline = dir.join("a\nb");
+/// fs::write(&dash, "dash").unwrap();
+/// fs::write(&newline, "newline").unwrap();
+///
+/// let args = Args::try_parse_from([
+///     "prog".as_ref(),
+///     "--".as_ref(),
+///     dash.as_os_str(),
+///     newline.as_os_str(),
+/// ])
+/// .unwrap();
+/// assert_eq!(args.inputs[0].path(), Some(dash.as_path()));
+/// assert_eq!(args.inputs[1].path(), Some(newline.as_path()));
+///
+/// // error messages stay on one line
+/// let missing = dir.join("no\nsuch");
+/// let error = Args::try_parse_from(["prog".as_ref(), missing.as_os_str()]).unwrap_err();
+/// let message = error.to_string();
+/// assert!(message.contains(&EscapedPath::new(&missing).to_string()));
+/// assert_eq!(message.trim_end().lines().count(), 1);
+/// # fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct EscapedPath<'a>(&'a Path);
+
+impl<'a> EscapedPath<'a> {
+    /// Creates a new [`EscapedPath`] for displaying `path`.
+    pub fn new(path: &'a Path) -> Self {
+        Self(path)
+    }
+
+    /// Returns the wrapped path.
+    pub fn path(&self) -> &'a Path {
+        self.0
+    }
+}
+
+impl fmt::Display for EscapedPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = self.0.as_os_str().as_encoded_bytes();
+        while !bytes.is_empty() {
+            let (valid, invalid) = match std::str::from_utf8(bytes) {
+                Ok(valid) => (valid, &[][..]),
+                Err(e) => {
+                    let (valid, rest) = bytes.split_at(e.valid_up_to());
+                    let invalid_len = e.error_len().unwrap_or(rest.len());
+                    let valid = std::str::from_utf8(valid).expect("checked to be valid UTF-8");
+                    bytes = &rest[invalid_len..];
+                    (valid, &rest[..invalid_len])
+                }
+            };
+            for c in valid.chars() {
+                if c.is_control() {
+                    write!(f, "{}", c.escape_default())?;
+                } else {
+                    write!(f, "{c}")?;
+                }
+            }
+            for b in invalid {
+                write!(f, "\\x{b:02x}")?;
+            }
+            if invalid.is_empty() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
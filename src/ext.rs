@@ -0,0 +1,82 @@
+use std::io::{self, Read as _, Write as _};
+
+use crate::{Input, Output};
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for crate::Input {}
+    impl Sealed for crate::Output {}
+}
+
+/// Convenience methods for [`Input`].
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+/// It is exported from [`prelude`](crate::prelude).
+pub trait InputExt: private::Sealed {
+    /// Reads all remaining bytes from this input.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::prelude::*;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::open("Cargo.toml".into())?;
+    ///     let bytes = input.read_bytes()?;
+    ///     assert!(!bytes.is_empty());
+    ///     Ok(())
+    /// }
+    /// ```
+    fn read_bytes(&self) -> io::Result<Vec<u8>>;
+
+    /// Reads all remaining data from this input as a UTF-8 string.
+    fn read_string(&self) -> io::Result<String>;
+}
+
+impl InputExt for Input {
+    fn read_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.lock().read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn read_string(&self) -> io::Result<String> {
+        let mut buf = String::new();
+        self.lock().read_to_string(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Convenience methods for [`Output`].
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+/// It is exported from [`prelude`](crate::prelude).
+pub trait OutputExt: private::Sealed {
+    /// Writes all of `data` to this output and flushes it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap_file::prelude::*;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let output = Output::stdout();
+    ///     output.write_and_flush("Hello, world!\n")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    fn write_and_flush(&self, data: impl AsRef<[u8]>) -> io::Result<()>;
+}
+
+impl OutputExt for Output {
+    fn write_and_flush(&self, data: impl AsRef<[u8]>) -> io::Result<()> {
+        let mut writer = self.lock();
+        writer.write_all(data.as_ref())?;
+        writer.flush()
+    }
+}
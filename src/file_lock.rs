@@ -0,0 +1,50 @@
+use std::{fs::File, io, path::Path};
+
+/// Opens the file at `path` for reading, preventing other processes from modifying it while it is open.
+pub(crate) fn open_exclusive(path: &Path) -> io::Result<File> {
+    #[cfg(windows)]
+    {
+        use std::{fs::OpenOptions, os::windows::fs::OpenOptionsExt as _};
+
+        const FILE_SHARE_READ: u32 = 0x0000_0001;
+        OpenOptions::new()
+            .read(true)
+            .share_mode(FILE_SHARE_READ)
+            .open(path)
+    }
+    #[cfg(unix)]
+    {
+        let file = File::open(path)?;
+        lock_shared(&file, path)?;
+        Ok(file)
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        File::open(path)
+    }
+}
+
+/// Takes a shared advisory lock on `file`, which is released when the file is closed.
+#[cfg(unix)]
+pub(crate) fn lock_shared(file: &File, path: &Path) -> io::Result<()> {
+    use std::os::fd::AsRawFd as _;
+
+    use crate::{error, ErrorKind, EscapedPath};
+
+    // SAFETY: The file descriptor is valid while `file` is borrowed.
+    let ret = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_SH | libc::LOCK_NB) };
+    if ret == 0 {
+        return Ok(());
+    }
+    let os_error = io::Error::last_os_error();
+    if os_error.kind() == io::ErrorKind::WouldBlock {
+        return Err(error::new(
+            ErrorKind::WouldBlock,
+            format!(
+                "input file `{}` is locked by another process",
+                EscapedPath::new(path)
+            ),
+        ));
+    }
+    Err(os_error)
+}
@@ -0,0 +1,95 @@
+use std::{ffi::OsString, fs::Metadata, path::Path, time::SystemTime};
+
+/// Filters applied to files while expanding directory or glob inputs.
+///
+/// Files that do not pass the filters are skipped without being opened.
+/// By default, every file passes.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::{
+///     io,
+///     time::{Duration, SystemTime},
+/// };
+///
+/// use clap_file::{DirInput, InputFilter};
+///
+/// fn main() -> io::Result<()> {
+///     let mut filter = InputFilter::new();
+///     filter
+///         .extensions(["rs"])
+///         .max_size(1024 * 1024)
+///         .modified_since(SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+///     let mut dir = DirInput::open("src".into())?;
+///     dir.filter(filter);
+///     for input in dir.inputs() {
+///         assert_eq!(input?.path().unwrap().extension().unwrap(), "rs");
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InputFilter {
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified_since: Option<SystemTime>,
+    extensions: Vec<OsString>,
+}
+
+impl InputFilter {
+    /// Creates a new [`InputFilter`] that every file passes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skips files smaller than `size` bytes.
+    pub fn min_size(&mut self, size: u64) -> &mut Self {
+        self.min_size = Some(size);
+        self
+    }
+
+    /// Skips files larger than `size` bytes.
+    pub fn max_size(&mut self, size: u64) -> &mut Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Skips files last modified before `time`.
+    pub fn modified_since(&mut self, time: SystemTime) -> &mut Self {
+        self.modified_since = Some(time);
+        self
+    }
+
+    /// Skips files without one of the given extensions (without the leading `.`).
+    pub fn extensions<I, S>(&mut self, extensions: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.extensions = extensions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns `true` if the file at `path` with `metadata` passes the filters.
+    pub(crate) fn matches(&self, path: &Path, metadata: &Metadata) -> bool {
+        let size = metadata.len();
+        if self.min_size.is_some_and(|min| size < min)
+            || self.max_size.is_some_and(|max| size > max)
+        {
+            return false;
+        }
+        if let Some(since) = self.modified_since {
+            if metadata
+                .modified()
+                .map_or(true, |modified| modified < since)
+            {
+                return false;
+            }
+        }
+        self.extensions.is_empty()
+            || path
+                .extension()
+                .is_some_and(|ext| self.extensions.iter().any(|e| e == ext))
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    fs, io,
+    ops::Deref,
+    path::{Path, PathBuf},
+    slice,
+    str::FromStr,
+    sync::Arc,
+    vec,
+};
+
+use crate::{error, ErrorKind, Input, InputFilter, InputOrder, Inputs, SkipReason};
+
+/// One or more input sources expanded from a glob pattern.
+///
+/// When parsed from a string containing glob metacharacters (`*`, `?`, `[`), the pattern is expanded
+/// at parse time and every matching regular file is opened, in alphabetical order.
+/// This is useful on Windows, where the shell does not expand globs.
+/// Patterns that match no files are reported as errors.
+///
+/// Strings without metacharacters, and paths of existing files even if they contain metacharacters
+/// (e.g. `report[1].txt`), are parsed as a single [`Input`], so `-` reads from standard input.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::{GlobInput, Inputs};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Input files or glob patterns.
+///     inputs: Vec<GlobInput>,
+/// }
+///
+/// let args = Args::parse_from(["prog", "src/*.rs", "Cargo.toml"]);
+/// let inputs: Inputs = args.inputs.into_iter().collect();
+/// assert!(inputs.len() > 2);
+///
+/// assert!(Args::try_parse_from(["prog", "no-such-dir/*.rs"]).is_err());
+///
+/// let dir = std::env::temp_dir().join(format!("clap-file-doc-glob-{}", std::process::id()));
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let path = dir.join("report[1].txt");
+/// std::fs::write(&path, "report").unwrap();
+/// let args = Args::parse_from(["prog".as_ref(), path.as_os_str()]);
+/// assert_eq!(args.inputs[0][0].path(), Some(path.as_path()));
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct GlobInput {
+    pattern: String,
+    inputs: Vec<Input>,
+    skipped: Arc<[(PathBuf, SkipReason)]>,
+}
+
+fn is_pattern(s: &str) -> bool {
+    s.contains(['*', '?', '['])
+}
+
+impl GlobInput {
+    /// Expands the given glob pattern and opens all matching regular files.
+    ///
+    /// Returns an error if the pattern is invalid or matches no files.
+    pub fn expand(pattern: &str) -> io::Result<Self> {
+        Self::expand_with(pattern, &InputFilter::default())
+    }
+
+    /// Expands the given glob pattern and opens all matching regular files that pass `filter`.
+    ///
+    /// Matched paths that cannot be inspected, such as broken symbolic links or unreadable directories,
+    /// are skipped with [`SkipReason::Failed`].
+    /// Returns an error if the pattern is invalid or no files pass the filter.
+    pub fn expand_with(pattern: &str, filter: &InputFilter) -> io::Result<Self> {
+        let paths = glob::glob(pattern).map_err(|e| {
+            error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid glob pattern `{pattern}`: {e}"),
+            )
+        })?;
+        let mut inputs = vec![];
+        let mut skipped = vec![];
+        for path in paths {
+            let path = match path {
+                Ok(path) => path,
+                Err(e) => {
+                    let path = e.path().to_owned();
+                    skipped.push((path, SkipReason::Failed(e.into())));
+                    continue;
+                }
+            };
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    skipped.push((path, SkipReason::Failed(e)));
+                    continue;
+                }
+            };
+            if !metadata.is_file() {
+                skipped.push((path, SkipReason::NotFile));
+            } else if !filter.matches(&path, &metadata) {
+                skipped.push((path, SkipReason::Filtered));
+            } else {
+                inputs.push(Input::open(path)?);
+            }
+        }
+        if inputs.is_empty() {
+            return Err(error::new(
+                ErrorKind::NotFound,
+                format!("glob pattern `{pattern}` matched no files"),
+            ));
+        }
+        Ok(Self {
+            pattern: pattern.to_owned(),
+            inputs,
+            skipped: skipped.into(),
+        })
+    }
+
+    /// Sorts the matched inputs in the given order.
+    ///
+    /// The inputs are initially in lexicographic order.
+    pub fn sort(&mut self, order: InputOrder) {
+        order.sort_by_path(&mut self.inputs, |input| {
+            input.path().unwrap_or(Path::new(""))
+        });
+    }
+
+    /// Returns the paths that matched the pattern but were skipped, with the reasons they were skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use clap_file::{GlobInput, InputFilter, SkipReason};
+    ///
+    /// let mut filter = InputFilter::new();
+    /// filter.extensions(["toml"]);
+    /// let input = GlobInput::expand_with("*", &filter).unwrap();
+    /// assert!(input.skipped().iter().any(|(path, reason)| {
+    ///     path.ends_with("src") && matches!(reason, SkipReason::NotFile)
+    /// }));
+    /// ```
+    pub fn skipped(&self) -> &[(PathBuf, SkipReason)] {
+        &self.skipped
+    }
+
+    /// Returns the pattern (or path) this [`GlobInput`] was created from.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Converts this [`GlobInput`] into a `Vec<Input>`.
+    pub fn into_vec(self) -> Vec<Input> {
+        self.inputs
+    }
+}
+
+impl Deref for GlobInput {
+    type Target = [Input];
+
+    fn deref(&self) -> &Self::Target {
+        &self.inputs
+    }
+}
+
+impl FromStr for GlobInput {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // An existing file is opened as is, even if its name contains metacharacters.
+        if is_pattern(s) && !Path::new(s).exists() {
+            return Self::expand(s);
+        }
+        Ok(Self {
+            pattern: s.to_owned(),
+            inputs: vec![s.parse()?],
+            skipped: Arc::new([]),
+        })
+    }
+}
+
+impl From<GlobInput> for Inputs {
+    fn from(value: GlobInput) -> Self {
+        Self::new(value.inputs)
+    }
+}
+
+impl FromIterator<GlobInput> for Inputs {
+    fn from_iter<I: IntoIterator<Item = GlobInput>>(iter: I) -> Self {
+        iter.into_iter().flatten().collect()
+    }
+}
+
+impl IntoIterator for GlobInput {
+    type Item = Input;
+    type IntoIter = vec::IntoIter<Input>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inputs.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a GlobInput {
+    type Item = &'a Input;
+    type IntoIter = slice::Iter<'a, Input>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inputs.iter()
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::{self, Write as _};
+
+use crate::{Input, LockedInput, LockedOutput, Output};
+
+/// Locks both `input` and `output`, runs `f` with them, and flushes the output.
+///
+/// Returns the value returned by `f`, or the first error that occurred while running `f` or flushing the output.
+///
+/// If `output` is standard output and writing to it fails with [`io::ErrorKind::BrokenPipe`] (e.g. `mytool | head`),
+/// this function returns `Ok(None)` instead of an error, so that the program can exit quietly.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, BufRead as _, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::{Input, Output};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Input file. If not provided, reads from standard input.
+///     input: Input,
+///     /// output file. If not provided, reads from standard output.
+///     output: Output,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     clap_file::with_io(&args.input, &args.output, |input, output| {
+///         for line in input.lines() {
+///             let line = line?;
+///             writeln!(output, "{line}")?;
+///         }
+///         Ok(())
+///     })?;
+///     Ok(())
+/// }
+/// ```
+pub fn with_io<T, F>(input: &Input, output: &Output, f: F) -> io::Result<Option<T>>
+where
+    F: FnOnce(&mut LockedInput<'_>, &mut LockedOutput<'_>) -> io::Result<T>,
+{
+    let mut reader = input.lock();
+    let mut writer = output.lock();
+    let res = f(&mut reader, &mut writer).and_then(|value| {
+        writer.flush()?;
+        Ok(value)
+    });
+    match res {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe && output.is_stdout() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,46 @@
+use std::{error::Error, fmt, io, path::Path};
+
+use crate::ErrorKind;
+
+/// An error annotated with an actionable hint for the user.
+#[derive(Debug)]
+struct Hinted {
+    error: io::Error,
+    hint: String,
+}
+
+impl fmt::Display for Hinted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (hint: {})", self.error, self.hint)
+    }
+}
+
+impl Error for Hinted {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Adds a hint to an error that occurred while creating the output file at `path`.
+///
+/// Errors that have no useful hint are returned as is.
+pub(crate) fn output_error(error: io::Error, path: &Path) -> io::Error {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let hint = if ErrorKind::of(&error) == ErrorKind::ReadOnlyFilesystem {
+        format!(
+            "`{}` is on a read-only file system; specify `-` to write to standard output instead",
+            dir.display()
+        )
+    } else if error.kind() == io::ErrorKind::PermissionDenied {
+        format!(
+            "check the permissions of `{}`, or specify `-` to write to standard output instead",
+            dir.display()
+        )
+    } else {
+        return error;
+    };
+    io::Error::new(error.kind(), Hinted { error, hint })
+}
@@ -0,0 +1,167 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{logging::debug, AtomicOutput, Input, Output};
+
+/// A pair of an input file and an output that replaces it, for editing files in place like `sed -i`.
+///
+/// The output is written to a temporary file next to the input file, which replaces the input file
+/// when [`InPlace::commit`] is called. If the [`InPlace`] is dropped without being committed, the
+/// input file is left untouched. Optionally, the original file is kept as a backup.
+///
+/// When parsed from `-`, the input reads from standard input and the output writes to standard output,
+/// so the same program can also be used as a filter.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, BufRead as _, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::InPlace;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Files to edit in place.
+///     files: Vec<InPlace>,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     for file in args.files {
+///         let mut output = file.output().lock();
+///         for line in file.input().lock().lines() {
+///             writeln!(output, "{}", line?.to_uppercase())?;
+///         }
+///         drop(output);
+///         file.commit()?;
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InPlace {
+    input: Input,
+    output: AtomicOutput,
+    backup_suffix: Option<OsString>,
+}
+
+impl InPlace {
+    /// Opens the file at the given path for in-place editing.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        Self::builder().open(path)
+    }
+
+    /// Creates a new [`InPlaceBuilder`] to configure in-place editing.
+    pub fn builder() -> InPlaceBuilder {
+        InPlaceBuilder::new()
+    }
+
+    /// Returns the input that reads the original contents.
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Returns the output that writes the new contents.
+    pub fn output(&self) -> &Output {
+        &self.output
+    }
+
+    /// Returns the path of the edited file, or `None` if this [`InPlace`] reads from standard input.
+    pub fn path(&self) -> Option<&Path> {
+        self.input.path()
+    }
+
+    /// Replaces the original file with the written contents.
+    ///
+    /// If a backup suffix is configured, the original file is renamed to the path with the suffix appended first.
+    /// The permissions of the original file are preserved.
+    /// If this [`InPlace`] reads from standard input, this only flushes standard output.
+    pub fn commit(self) -> io::Result<()> {
+        if let (Some(path), Some(temp_path)) = (self.input.path(), self.output.path()) {
+            let permissions = fs::metadata(path)?.permissions();
+            fs::set_permissions(temp_path, permissions)?;
+            if let Some(suffix) = &self.backup_suffix {
+                let backup_path = with_suffix(path, suffix);
+                debug!(
+                    "backing up `{}` to `{}`",
+                    path.display(),
+                    backup_path.display()
+                );
+                fs::rename(path, backup_path)?;
+            }
+        }
+        self.output.commit()
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &OsStr) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+impl FromStr for InPlace {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            return Ok(Self {
+                input: Input::claimed_stdin()?,
+                output: AtomicOutput::stdout(),
+                backup_suffix: None,
+            });
+        }
+        Self::open(PathBuf::from(s))
+    }
+}
+
+/// A builder for creating an [`InPlace`] with custom options.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io;
+///
+/// use clap_file::InPlace;
+///
+/// fn main() -> io::Result<()> {
+///     // keeps the original file as `config.txt.bak`
+///     let file = InPlace::builder().backup_suffix(".bak").open("config.txt".into())?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InPlaceBuilder {
+    backup_suffix: Option<OsString>,
+}
+
+impl InPlaceBuilder {
+    /// Creates a new [`InPlaceBuilder`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the suffix appended to the path of the backup of the original file.
+    ///
+    /// If not set, no backup is kept.
+    pub fn backup_suffix(&mut self, suffix: impl Into<OsString>) -> &mut Self {
+        self.backup_suffix = Some(suffix.into());
+        self
+    }
+
+    /// Opens the file at the given path for in-place editing with the options specified by this builder.
+    pub fn open(&self, path: PathBuf) -> io::Result<InPlace> {
+        let input = Input::open(path.clone())?;
+        let output = AtomicOutput::create(path)?;
+        Ok(InPlace {
+            input,
+            output,
+            backup_suffix: self.backup_suffix.clone(),
+        })
+    }
+}
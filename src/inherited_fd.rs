@@ -0,0 +1,130 @@
+use std::{ffi::OsStr, fs::File, io};
+
+/// The prefix of arguments that refer to inherited file descriptors, e.g. `fd:3` or `fd:listen`.
+const PREFIX: &str = "fd:";
+
+/// Returns the part after `fd:` if `value` refers to an inherited file descriptor.
+pub(crate) fn strip_prefix(value: &OsStr) -> Option<&str> {
+    value.to_str()?.strip_prefix(PREFIX)
+}
+
+/// Duplicates the inherited file descriptor specified by `spec`, the part of an argument after `fd:`.
+///
+/// `spec` is either a descriptor number, `listen` for the first descriptor passed by socket activation,
+/// or a name listed in `LISTEN_FDNAMES`. The original descriptor is left open, since this process
+/// may still own it elsewhere.
+pub(crate) fn open(spec: &str) -> io::Result<File> {
+    #[cfg(unix)]
+    {
+        imp::take(imp::resolve(spec)?)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = spec;
+        Err(crate::error::new(
+            crate::ErrorKind::Unsupported,
+            "inherited file descriptors (`fd:`) are only supported on Unix",
+        ))
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::{
+        env,
+        fs::File,
+        io,
+        os::fd::{BorrowedFd, RawFd},
+        sync::Mutex,
+    };
+
+    use crate::{error, logging::debug, ErrorKind};
+
+    /// The first file descriptor passed by socket activation (`SD_LISTEN_FDS_START` of systemd).
+    const LISTEN_FDS_START: RawFd = 3;
+
+    // Each inherited descriptor is taken by at most one `Input` or `Output`, so that they do not share a file offset.
+    static TAKEN: Mutex<Vec<RawFd>> = Mutex::new(Vec::new());
+
+    pub(super) fn resolve(spec: &str) -> io::Result<RawFd> {
+        if let Ok(fd) = spec.parse::<RawFd>() {
+            return Ok(fd);
+        }
+        let fds = listen_fds()?;
+        let found = if spec == "listen" {
+            fds.first()
+        } else {
+            fds.iter().find(|(_, name)| name == spec)
+        };
+        found.map(|(fd, _)| *fd).ok_or_else(|| {
+            error::new(
+                ErrorKind::NotFound,
+                format!("no file descriptor named `{spec}` was passed by socket activation"),
+            )
+        })
+    }
+
+    /// Returns the descriptors passed by systemd-style socket activation, with their names.
+    fn listen_fds() -> io::Result<Vec<(RawFd, String)>> {
+        let not_activated = || {
+            error::new(
+                ErrorKind::NotFound,
+                "no file descriptors were passed by socket activation (`LISTEN_FDS` is not set for this process)",
+            )
+        };
+        // `LISTEN_PID` guards against the variables being inherited by child processes
+        let pid = env::var("LISTEN_PID").ok().and_then(|pid| pid.parse().ok());
+        if pid != Some(std::process::id()) {
+            return Err(not_activated());
+        }
+        let count = env::var("LISTEN_FDS")
+            .ok()
+            .and_then(|count| count.parse::<RawFd>().ok())
+            .ok_or_else(not_activated)?;
+        let names = env::var("LISTEN_FDNAMES").unwrap_or_default();
+        let mut names = names.split(':').filter(|name| !name.is_empty());
+        Ok((0..count)
+            .map(|i| {
+                // systemd names descriptors `unknown` if no names are passed
+                let name = names.next().unwrap_or("unknown");
+                (LISTEN_FDS_START + i, name.to_owned())
+            })
+            .collect())
+    }
+
+    pub(super) fn take(fd: RawFd) -> io::Result<File> {
+        if (0..=2).contains(&fd) {
+            return Err(error::new(
+                ErrorKind::InvalidInput,
+                "use `-` for standard input and output instead of `fd:0`, `fd:1`, or `fd:2`",
+            ));
+        }
+        if fd < 0 {
+            return Err(error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid file descriptor {fd}"),
+            ));
+        }
+        let mut taken = TAKEN.lock().unwrap_or_else(|e| e.into_inner());
+        if taken.contains(&fd) {
+            return Err(error::new(
+                ErrorKind::InvalidInput,
+                format!("file descriptor {fd} can be specified at most once"),
+            ));
+        }
+        // SAFETY: The descriptor is only borrowed to duplicate it, which fails with `EBADF` if it is not open.
+        // Duplicating the descriptor is the only portable way to check its validity without `libc`.
+        // The original descriptor is never closed, since it may be owned by another handle of this process.
+        let duplicate = unsafe { BorrowedFd::borrow_raw(fd) }
+            .try_clone_to_owned()
+            .map_err(|e| {
+                error::new(
+                    ErrorKind::NotFound,
+                    format!("file descriptor {fd} is not open: {e}"),
+                )
+            })?;
+        taken.push(fd);
+        debug!("took inherited file descriptor {fd}");
+        Ok(File::from(duplicate))
+    }
+}
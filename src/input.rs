@@ -1,17 +1,1870 @@
 use std::{
+    ffi::OsStr,
     fs::File,
-    io::{self, BufRead, BufReader, Read},
+    io::{self, BufRead, BufReader, IsTerminal as _, Read, Seek, SeekFrom},
+    ops::Deref,
     path::{Path, PathBuf},
     str::FromStr,
     sync::{Arc, Mutex, MutexGuard},
 };
 
+use crate::{
+    buf_size,
+    compression::Decoder,
+    error, inherited_fd,
+    logging::debug,
+    stdin_claim::{self, StdinClaim},
+    warning::SizeCheck,
+    Bom, Compression, DebugDump, ErrorKind, InputRange, InputSnapshot, IoDescription, IoKind,
+    LineBatches, LockedOutput, LossyLines, Output, OutputPathRule, ProgressReader, Records, Tee,
+    UniversalLines,
+};
+
 #[track_caller]
-fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
     mutex.lock().unwrap_or_else(|e| e.into_inner())
 }
 
-/// Represents an input source, which can be either standard input or a file.
+fn stdin_not_seekable() -> io::Error {
+    error::new(ErrorKind::Unsupported, "cannot seek standard input")
+}
+
+/// How the data of a decoded [`Input`] is converted from its source.
+#[derive(Debug, Clone, Copy)]
+enum Decoding {
+    Decompress(Compression),
+    #[cfg(feature = "encoding")]
+    Transcode(crate::Encoding),
+}
+
+impl Decoding {
+    fn not_seekable(self) -> io::Error {
+        match self {
+            Self::Decompress(_) => {
+                error::new(ErrorKind::Unsupported, "cannot seek compressed input")
+            }
+            #[cfg(feature = "encoding")]
+            Self::Transcode(_) => {
+                error::new(ErrorKind::Unsupported, "cannot seek transcoded input")
+            }
+        }
+    }
+}
+
+/// Represents an input source, which can be either standard input or a file.
+///
+/// When parsed from `-`, the input reads from standard input.
+/// Since standard input can be consumed only once, `-` is rejected while another [`Input`] parsed from `-` is alive.
+/// The claim is released when that [`Input`] and its clones are dropped, so arguments can be parsed again,
+/// e.g. after [`Parser::try_parse`](clap::Parser::try_parse) fails.
+///
+/// [`Read`] is implemented for both `Input` and `&Input`, so an input can be read from closures that only capture it by shared reference.
+/// [`Seek`] is implemented as well, but fails for standard input; use [`Input::is_seekable`] to check in advance.
+///
+/// When the cargo feature of a [`Compression`] format is enabled, files with its extension (e.g. `.gz`) are decompressed transparently.
+/// Use [`Input::decompress`] or [`Input::decompress_auto`] to decompress other inputs, such as standard input.
+///
+/// A UTF-8 or UTF-16 byte order mark at the start of the input can be stripped with [`Input::strip_bom`] or [`InputBuilder::strip_bom`].
+///
+/// clap parses this type with [`InputValueParser`](crate::InputValueParser). clap only infers value hints for `PathBuf` arguments,
+/// so add `#[arg(value_hint = clap::ValueHint::FilePath)]` or pass the command through [`value_hints`](crate::value_hints)
+/// to enable filename completion in generated shell completions.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, BufRead as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::Input;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Input file. If not provided, reads from standard input.
+///     input: Input,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     let input = args.input.lock();
+///     for line in input.lines() {
+///         let line = line?;
+///         println!("{line}");
+///     }
+///     Ok(())
+/// }
+/// ```
+
+// This struct should not implement `Clone`, but clap-derive requires Clone [1].
+// So, I added `Clone` to the struct and wrap `File` with `Arc` and `Mutex`.
+// This is not the best way to handle this, but it works for now.
+//
+// [1]: https://github.com/clap-rs/clap/issues/4286
+//
+// The second field is the BOM stripped from the start of the input, if any.
+// The third field holds the claim of standard input if this was parsed from `-`.
+#[derive(Debug, Clone)]
+pub struct Input(InputInner, Option<Bom>, Option<Arc<StdinClaim>>);
+
+#[derive(Debug, Clone)]
+enum InputInner {
+    Stdin,
+    #[cfg(feature = "test-util")]
+    InjectedStdin {
+        reader: Arc<Mutex<io::Cursor<Vec<u8>>>>,
+    },
+    File {
+        path: Arc<PathBuf>,
+        reader: Arc<Mutex<BufReader<File>>>,
+        size_check: Arc<SizeCheck>,
+    },
+    Decoded {
+        source: Box<Input>,
+        decoding: Decoding,
+        reader: Arc<Mutex<BufReader<Decoder>>>,
+    },
+}
+
+impl Input {
+    /// Creates a new [`Input`] instance that reads from standard input.
+    ///
+    /// When the `test-util` feature is enabled, this reads from the data injected by
+    /// `testing::with_stdio` instead, if any.
+    pub fn stdin() -> Self {
+        #[cfg(feature = "test-util")]
+        if let Some(reader) = crate::testing::injected_stdin() {
+            return Self(InputInner::InjectedStdin { reader }, None, None);
+        }
+        Self(InputInner::Stdin, None, None)
+    }
+
+    /// Claims standard input and creates a new [`Input`] instance that reads from it, for parsing `-`.
+    ///
+    /// The claim is held until this [`Input`] and its clones are dropped.
+    pub(crate) fn claimed_stdin() -> io::Result<Self> {
+        let claim = stdin_claim::claim()?;
+        let mut input = Self::stdin();
+        input.2 = Some(Arc::new(claim));
+        Ok(input)
+    }
+
+    /// Creates a new [`StdinOnly`] instance that reads from standard input.
+    pub fn stdin_only() -> StdinOnly {
+        StdinOnly::new()
+    }
+
+    /// Opens a file at the given path and creates a new [`Input`] instance that reads from it.
+    ///
+    /// The buffer capacity is chosen automatically based on the type of the file.
+    /// Use [`Input::builder`] to override it.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        Self::builder().open(path)
+    }
+
+    /// Takes a file descriptor inherited from the parent process and creates a new [`Input`] instance that reads from it.
+    ///
+    /// See [`InputBuilder::open_inherited`] for the accepted values of `spec`.
+    pub fn inherited(spec: &str) -> io::Result<Self> {
+        Self::builder().open_inherited(spec)
+    }
+
+    /// Opens a file at the given path and creates a new [`Input`] instance that reads from it with a buffer of `capacity` bytes.
+    ///
+    /// Large buffers speed up sequential reads of bulk data.
+    pub fn open_with_capacity(path: PathBuf, capacity: usize) -> io::Result<Self> {
+        Self::builder().capacity(capacity).open(path)
+    }
+
+    /// Opens the first available candidate and creates a new [`Input`] instance that reads from it.
+    ///
+    /// Candidates are tried in order; those that do not exist are skipped. `-` stands for standard input,
+    /// which is always available, so it is typically used as the last candidate.
+    /// Errors other than a missing file are returned immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     // use `./config` if present, else fall back to standard input
+    ///     let input = Input::first_of(["./config", "-"])?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn first_of<I, P>(candidates: I) -> io::Result<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut tried = vec![];
+        for candidate in candidates {
+            let candidate = candidate.as_ref();
+            if candidate.as_os_str() == "-" {
+                return Self::claimed_stdin();
+            }
+            match Self::open(candidate.to_owned()) {
+                Ok(input) => return Ok(input),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    tried.push(format!("`{}`", candidate.display()));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(error::new(
+            ErrorKind::NotFound,
+            format!("none of the inputs exist: {}", tried.join(", ")),
+        ))
+    }
+
+    /// Opens a file at the given path relative to `dir` and creates a new [`Input`] instance that reads from it.
+    ///
+    /// The path cannot escape `dir`. See [`InputBuilder::open_at`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use cap_std::{ambient_authority, fs::Dir};
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let dir = Dir::open_ambient_dir(".", ambient_authority())?;
+    ///     let input = Input::open_at(&dir, "Cargo.toml".into())?;
+    ///     assert!(Input::open_at(&dir, "../etc/passwd".into()).is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "cap-std")]
+    pub fn open_at(dir: &cap_std::fs::Dir, path: PathBuf) -> io::Result<Self> {
+        Self::builder().open_at(dir, path)
+    }
+
+    /// Creates a new [`InputBuilder`] to configure how an input file is opened.
+    pub fn builder() -> InputBuilder {
+        InputBuilder::new()
+    }
+
+    /// Returns `true` if this [`Input`] reads from standard input.
+    pub fn is_stdin(&self) -> bool {
+        match &self.0 {
+            InputInner::Stdin => true,
+            #[cfg(feature = "test-util")]
+            InputInner::InjectedStdin { .. } => true,
+            InputInner::File { .. } => false,
+            InputInner::Decoded { source, .. } => source.is_stdin(),
+        }
+    }
+
+    /// Returns `true` if this [`Input`] reads from a file.
+    pub fn is_file(&self) -> bool {
+        match &self.0 {
+            InputInner::File { .. } => true,
+            InputInner::Decoded { source, .. } => source.is_file(),
+            _ => false,
+        }
+    }
+
+    /// Returns the path of the file this [`Input`] reads from.
+    ///
+    /// Returns `None` if this [`Input`] reads from standard input.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.0 {
+            InputInner::Stdin => None,
+            #[cfg(feature = "test-util")]
+            InputInner::InjectedStdin { .. } => None,
+            InputInner::File { path, .. } => Some(path),
+            InputInner::Decoded { source, .. } => source.path(),
+        }
+    }
+
+    /// Returns the compression format this [`Input`] is decompressed from, if any.
+    pub fn compression(&self) -> Option<Compression> {
+        match &self.0 {
+            InputInner::Decoded {
+                decoding: Decoding::Decompress(compression),
+                ..
+            } => Some(*compression),
+            #[cfg(feature = "encoding")]
+            InputInner::Decoded { source, .. } => source.compression(),
+            _ => None,
+        }
+    }
+
+    /// Converts this [`Input`] into one that decompresses the data read from it.
+    ///
+    /// Files are decompressed automatically based on their extension when opened (see [`InputBuilder::decompress`]),
+    /// so this is mostly useful for standard input. Data that has already been read into the buffer is decompressed as well.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::Unsupported`] if the cargo feature for `compression` is not enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, BufRead as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::{Compression, Input};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Decompress standard input with gzip.
+    ///     #[arg(long)]
+    ///     gunzip: bool,
+    ///     input: Input,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let mut input = args.input;
+    ///     if args.gunzip && input.is_stdin() {
+    ///         input = input.decompress(Compression::Gzip)?;
+    ///     }
+    ///     for line in input.lock().lines() {
+    ///         println!("{}", line?);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn decompress(self, compression: Compression) -> io::Result<Self> {
+        let source = Box::new(self.clone());
+        let bom = self.1;
+        let capacity = match &self.0 {
+            InputInner::File { reader, .. } => lock(reader).capacity(),
+            _ => buf_size::DEFAULT,
+        };
+        let decoder = compression.decoder(self)?;
+        debug!(
+            "decompressing {} as {compression}",
+            source.path().map_or_else(
+                || "standard input".to_owned(),
+                |path| format!("`{}`", path.display())
+            )
+        );
+        let reader = Arc::new(Mutex::new(BufReader::with_capacity(capacity, decoder)));
+        Ok(Self(
+            InputInner::Decoded {
+                source,
+                decoding: Decoding::Decompress(compression),
+                reader,
+            },
+            bom,
+            None,
+        ))
+    }
+
+    /// Returns the character encoding this [`Input`] is transcoded from, if any.
+    #[cfg(feature = "encoding")]
+    pub fn encoding(&self) -> Option<crate::Encoding> {
+        match &self.0 {
+            InputInner::Decoded {
+                decoding: Decoding::Transcode(encoding),
+                ..
+            } => Some(*encoding),
+            InputInner::Decoded { source, .. } => source.encoding(),
+            _ => None,
+        }
+    }
+
+    /// Converts this [`Input`] into one that transcodes the data read from it from `encoding` into UTF-8.
+    ///
+    /// A byte order mark at the start of the data, or one already stripped with [`Input::strip_bom`], takes precedence over `encoding`.
+    /// The byte order mark is removed.
+    /// Malformed sequences are replaced with U+FFFD REPLACEMENT CHARACTER, so reading never fails due to the encoding.
+    /// Data that has already been read into the buffer is transcoded as well.
+    ///
+    /// See [`Encoding`](crate::Encoding) for selecting the encoding with a command-line argument.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Read as _};
+    ///
+    /// use clap_file::{Encoding, Input};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-doc-{}.txt", std::process::id()));
+    ///     std::fs::write(&path, b"caf\xe9")?;
+    ///     let input = Input::open(path.clone())?.transcode(Encoding::WINDOWS_1252)?;
+    ///     let mut text = String::new();
+    ///     input.lock().read_to_string(&mut text)?;
+    ///     assert_eq!(text, "caf\u{e9}");
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "encoding")]
+    pub fn transcode(self, encoding: crate::Encoding) -> io::Result<Self> {
+        let source = Box::new(self.clone());
+        let bom = self.1;
+        let encoding = bom.map_or(encoding, crate::Encoding::from);
+        let capacity = match &self.0 {
+            InputInner::File { reader, .. } => lock(reader).capacity(),
+            _ => buf_size::DEFAULT,
+        };
+        debug!(
+            "transcoding {} from {encoding}",
+            source.path().map_or_else(
+                || "standard input".to_owned(),
+                |path| format!("`{}`", path.display())
+            )
+        );
+        let decoder = Decoder::new(crate::encoding::Transcoder::new(self, encoding));
+        let reader = Arc::new(Mutex::new(BufReader::with_capacity(capacity, decoder)));
+        Ok(Self(
+            InputInner::Decoded {
+                source,
+                decoding: Decoding::Transcode(encoding),
+                reader,
+            },
+            bom,
+            None,
+        ))
+    }
+
+    /// Strips the UTF-8 or UTF-16 byte order mark (BOM) at the start of this [`Input`], if any.
+    ///
+    /// The BOM is peeked into the read buffer and consumed, so this must be called before anything is read.
+    /// The detected BOM is reported by [`Input::detected_encoding`] and [`LockedInput::detected_encoding`].
+    /// Inputs whose BOM has already been stripped are returned unchanged.
+    ///
+    /// Peeking blocks until data is available, so call this after parsing the command line rather than in a value parser.
+    /// Use [`InputBuilder::strip_bom`] to strip the BOM of files when they are opened.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Read as _};
+    ///
+    /// use clap_file::{Bom, Input};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-doc-{}.csv", std::process::id()));
+    ///     std::fs::write(&path, "\u{feff}name,age\n")?;
+    ///     let input = Input::open(path.clone())?.strip_bom()?;
+    ///     let mut locked = input.lock();
+    ///     assert_eq!(locked.detected_encoding(), Some(Bom::Utf8));
+    ///     let mut text = String::new();
+    ///     locked.read_to_string(&mut text)?;
+    ///     assert_eq!(text, "name,age\n");
+    ///     # drop(locked);
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn strip_bom(mut self) -> io::Result<Self> {
+        if self.1.is_some() {
+            return Ok(self);
+        }
+        let mut reader = self.lock();
+        let bom = Bom::from_bytes(reader.fill_buf()?);
+        if let Some(bom) = bom {
+            reader.consume(bom.as_bytes().len());
+            debug!("stripped {bom} byte order mark");
+        }
+        drop(reader);
+        self.1 = bom;
+        Ok(self)
+    }
+
+    /// Returns the encoding identified by the byte order mark stripped with [`Input::strip_bom`], if any.
+    pub fn detected_encoding(&self) -> Option<Bom> {
+        self.1
+    }
+
+    /// Converts this [`Input`] into one that decompresses the data read from it, if the data starts with the magic bytes of a compression format.
+    ///
+    /// Unlike extension-based detection, this works for inputs without a meaningful name, such as `curl ... | mytool`.
+    /// The first bytes are peeked into the read buffer without consuming them, so the data is read as is if it is not compressed,
+    /// or if the cargo feature for the detected format is not enabled. Inputs that are already decompressed are returned unchanged.
+    ///
+    /// Peeking blocks until data is available, so call this after parsing the command line rather than in a value parser.
+    /// Use [`InputBuilder::detect_compression`] to detect the format of files when they are opened.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, BufRead as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Input;
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Input file, which may be compressed. If `-` is specified, reads from standard input.
+    ///     input: Input,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let input = args.input.decompress_auto()?;
+    ///     for line in input.lock().lines() {
+    ///         println!("{}", line?);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn decompress_auto(self) -> io::Result<Self> {
+        if self.compression().is_some() {
+            return Ok(self);
+        }
+        let compression = Compression::from_magic(self.lock().fill_buf()?);
+        match compression {
+            Some(compression) if compression.is_supported() => self.decompress(compression),
+            Some(compression) => {
+                debug!("detected {compression} data, but the `{compression}` feature is disabled");
+                Ok(self)
+            }
+            None => Ok(self),
+        }
+    }
+
+    /// Derives a default output path from the path of this [`Input`] by applying `rule`.
+    ///
+    /// Returns `None` if this [`Input`] reads from standard input.
+    pub fn derive_output_path(&self, rule: &OutputPathRule) -> Option<PathBuf> {
+        self.path().map(|path| rule.apply(path))
+    }
+
+    /// Skips the next `n` bytes of this [`Input`] and returns the number of bytes actually skipped.
+    ///
+    /// Regular files are skipped by seeking; other sources such as standard input and pipes are read and discarded.
+    /// The returned value is less than `n` if the end of the input is reached.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::open("Cargo.toml".into())?;
+    ///     assert_eq!(input.skip(9)?, 9);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn skip(&self, n: u64) -> io::Result<u64> {
+        if let InputInner::File { path, reader, .. } = &self.0 {
+            let mut reader = lock(reader);
+            let metadata = reader.get_ref().metadata()?;
+            if metadata.is_file() {
+                let pos = reader.stream_position()?;
+                let skipped = n.min(metadata.len().saturating_sub(pos));
+                reader.seek(SeekFrom::Start(pos + skipped))?;
+                debug!("skipped {skipped} bytes of `{}` by seeking", path.display());
+                return Ok(skipped);
+            }
+        }
+        io::copy(&mut self.lock().take(n), &mut io::sink())
+    }
+
+    /// Copies the contents of this [`Input`] to a temporary file and returns an [`InputSnapshot`] that reads from the copy.
+    ///
+    /// This guarantees a stable view of files that change while they are read, such as live logs,
+    /// without locking them. The file is cloned (reflinked) instead of copied on file systems that support it.
+    /// Standard input is read to the end and copied as well.
+    ///
+    /// The whole file is copied, regardless of how much of it has been read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-snapshot-{}.log", std::process::id()));
+    ///     std::fs::write(&path, "first line\n")?;
+    ///     let input = Input::open(path.clone())?;
+    ///     let snapshot = input.snapshot()?;
+    ///     assert_eq!(snapshot.source_path(), Some(path.as_path()));
+    ///     // Later changes to the file do not affect the snapshot
+    ///     std::fs::write(&path, "rewritten\n")?;
+    ///     let text = io::read_to_string(snapshot)?;
+    ///     assert_eq!(text, "first line\n");
+    ///     # drop(input);
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn snapshot(&self) -> io::Result<InputSnapshot> {
+        InputSnapshot::new(self)
+    }
+
+    /// Splits the file this [`Input`] reads from into `n` readers over disjoint byte ranges.
+    ///
+    /// The ranges cover the whole file regardless of how much of it has been read, and their sizes differ by at most one byte.
+    /// Each [`InputRange`] has its own file descriptor, so the ranges can be read in parallel from different threads.
+    /// Since the file is split at byte boundaries, records that cross a boundary must be handled by the caller.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::Unsupported`] if this [`Input`] reads from standard input,
+    /// a compressed file, or a file that is not a regular file (e.g. a pipe).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::{io, io::Read as _, thread};
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::open("Cargo.toml".into())?;
+    ///     let ranges = input.split_ranges(4)?;
+    ///     let counts = thread::scope(|s| {
+    ///         let handles = ranges
+    ///             .into_iter()
+    ///             .map(|mut range| {
+    ///                 s.spawn(move || -> io::Result<usize> {
+    ///                     let mut bytes = vec![];
+    ///                     range.read_to_end(&mut bytes)?;
+    ///                     Ok(bytes.iter().filter(|&&b| b == b'\n').count())
+    ///                 })
+    ///             })
+    ///             .collect::<Vec<_>>();
+    ///         handles.into_iter().map(|h| h.join().unwrap()).sum::<io::Result<usize>>()
+    ///     })?;
+    ///     let text = std::fs::read_to_string("Cargo.toml")?;
+    ///     assert_eq!(counts, text.lines().count());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn split_ranges(&self, n: usize) -> io::Result<Vec<InputRange>> {
+        match &self.0 {
+            InputInner::File { path, reader, .. } => {
+                let reader = lock(reader);
+                let file = reader.get_ref();
+                if !file.metadata()?.is_file() {
+                    return Err(error::new(
+                        ErrorKind::Unsupported,
+                        format!(
+                            "cannot split `{}` into ranges: not a regular file",
+                            path.display()
+                        ),
+                    ));
+                }
+                let ranges = InputRange::split(path, file, n)?;
+                debug!("split `{}` into {n} ranges", path.display());
+                Ok(ranges)
+            }
+            InputInner::Decoded { .. } => Err(error::new(
+                ErrorKind::Unsupported,
+                "cannot split compressed or transcoded input into ranges",
+            )),
+            _ => Err(error::new(
+                ErrorKind::Unsupported,
+                "cannot split standard input into ranges",
+            )),
+        }
+    }
+
+    /// Returns `true` if this [`Input`] supports [`Seek`].
+    ///
+    /// Files are seekable unless they are pipes, sockets, or terminals. Standard input and compressed inputs are never seekable.
+    pub fn is_seekable(&self) -> bool {
+        match &self.0 {
+            InputInner::File { reader, .. } => {
+                let reader = lock(reader);
+                let mut file = reader.get_ref();
+                file.stream_position().is_ok()
+            }
+            _ => false,
+        }
+    }
+
+    /// Seeks to `pos` if this [`Input`] is seekable, returning the new position from the start of the input.
+    ///
+    /// Returns `Ok(None)` without reading anything if this [`Input`] is not seekable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Read as _, SeekFrom};
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-try-seek-{}", std::process::id()));
+    ///     std::fs::write(&path, "header\nbody\n")?;
+    ///     let mut input = Input::open(path.clone())?;
+    ///     let mut header = [0; 6];
+    ///     input.read_exact(&mut header)?;
+    ///     assert_eq!(&header, b"header");
+    ///     assert_eq!(input.try_seek(SeekFrom::Start(1))?, Some(1));
+    ///
+    ///     assert_eq!(Input::stdin().try_seek(SeekFrom::Start(0))?, None);
+    ///     # drop(input);
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn try_seek(&self, pos: SeekFrom) -> io::Result<Option<u64>> {
+        if !self.is_seekable() {
+            return Ok(None);
+        }
+        let mut this = self;
+        this.seek(pos).map(Some)
+    }
+
+    /// Returns the contents of this [`Input`] as bytes, memory-mapping regular files.
+    ///
+    /// This gives zero-copy access to large files for parsers and searchers.
+    /// The whole file is mapped, regardless of how much of it has been read.
+    /// Other sources, such as standard input and pipes, are read to the end into memory instead,
+    /// or into a temporary file if they exceed the budget set with [`set_memory_budget`](crate::set_memory_budget).
+    ///
+    /// # Safety
+    ///
+    /// The mapped contents change if the file is modified or truncated by this or another process while
+    /// the returned [`InputBytes`](crate::InputBytes) is alive, which is undefined behavior.
+    /// The caller must ensure that the file is not modified until the returned value is dropped,
+    /// e.g. because it is owned by the program or locked against writers.
+    /// To read files that may be modified concurrently, read them into memory instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-mmap-{}", std::process::id()));
+    ///     std::fs::write(&path, "GIF89a")?;
+    ///     let input = Input::open(path.clone())?;
+    ///     // SAFETY: The file is created by this example and is not modified while it is mapped.
+    ///     let bytes = unsafe { input.mmap()? };
+    ///     assert!(bytes.is_mapped());
+    ///     assert!(bytes.starts_with(b"GIF"));
+    ///     # drop(bytes);
+    ///     # drop(input);
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "mmap")]
+    pub unsafe fn mmap(&self) -> io::Result<crate::InputBytes> {
+        if let InputInner::File { path, reader, .. } = &self.0 {
+            let reader = lock(reader);
+            let file = reader.get_ref();
+            if file.metadata()?.is_file() {
+                debug!("memory-mapping `{}`", path.display());
+                // SAFETY: The caller guarantees that the file is not modified while it is mapped.
+                return unsafe { crate::InputBytes::map(file) };
+            }
+        }
+        crate::InputBytes::read(self.lock())
+    }
+
+    /// Calls `f` with a freshly opened [`Input`] now and whenever the file this [`Input`] reads from changes.
+    ///
+    /// This is useful for implementing `--watch` modes. Watching continues until `f` returns
+    /// [`ControlFlow::Break`](std::ops::ControlFlow::Break) or an error. Returns an error of kind [`io::ErrorKind::Unsupported`]
+    /// if this [`Input`] reads from standard input.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::{io, ops::ControlFlow};
+    ///
+    /// use clap_file::{Input, InputExt as _};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::open("input.txt".into())?;
+    ///     input.watch(|input| {
+    ///         let text = input.read_string()?;
+    ///         println!("{} lines", text.lines().count());
+    ///         Ok(ControlFlow::Continue(()))
+    ///     })
+    /// }
+    /// ```
+    #[cfg(feature = "notify")]
+    pub fn watch<F>(&self, f: F) -> io::Result<()>
+    where
+        F: FnMut(Input) -> io::Result<std::ops::ControlFlow<()>>,
+    {
+        let Some(path) = self.path() else {
+            return Err(error::new(
+                ErrorKind::Unsupported,
+                "cannot watch standard input",
+            ));
+        };
+        crate::watch::watch(path, f)
+    }
+
+    /// Returns a machine-readable description of this [`Input`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::{Input, IoKind};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::open("Cargo.toml".into())?;
+    ///     let description = input.describe();
+    ///     assert_eq!(description.kind, IoKind::File);
+    ///     assert!(description.size.is_some());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn describe(&self) -> IoDescription {
+        let description = match &self.0 {
+            InputInner::Stdin => IoDescription {
+                kind: IoKind::Stdin,
+                path: None,
+                size: None,
+                is_tty: io::stdin().is_terminal(),
+                compression: None,
+                encoding: None,
+            },
+            #[cfg(feature = "test-util")]
+            InputInner::InjectedStdin { reader } => IoDescription {
+                kind: IoKind::Stdin,
+                path: None,
+                size: Some(lock(reader).get_ref().len() as u64),
+                is_tty: false,
+                compression: None,
+                encoding: None,
+            },
+            InputInner::File { path, reader, .. } => {
+                let reader = lock(reader);
+                let file = reader.get_ref();
+                IoDescription {
+                    kind: IoKind::File,
+                    path: Some(PathBuf::clone(path)),
+                    size: file.metadata().ok().map(|m| m.len()),
+                    is_tty: file.is_terminal(),
+                    compression: None,
+                    encoding: None,
+                }
+            }
+            InputInner::Decoded {
+                source,
+                decoding: Decoding::Decompress(compression),
+                ..
+            } => IoDescription {
+                compression: Some(compression.name()),
+                ..source.describe()
+            },
+            #[cfg(feature = "encoding")]
+            InputInner::Decoded {
+                source,
+                decoding: Decoding::Transcode(encoding),
+                ..
+            } => IoDescription {
+                encoding: Some(encoding.name()),
+                ..source.describe()
+            },
+        };
+        IoDescription {
+            encoding: description.encoding.or(self.1.map(Bom::name)),
+            ..description
+        }
+    }
+
+    /// Locks the input source and returns a reader that hexdumps the first `limit` bytes read to standard error.
+    ///
+    /// A `limit` of 0 disables dumping, so the limit can be derived from a debug flag.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Read as _};
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let debug = std::env::var_os("MYTOOL_DEBUG").is_some();
+    ///     let input = Input::stdin();
+    ///     let mut reader = input.debug_dump(if debug { 256 } else { 0 });
+    ///     let mut buf = Vec::new();
+    ///     reader.read_to_end(&mut buf)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn debug_dump(&self, limit: usize) -> DebugDump<LockedInput<'_>> {
+        let label = match self.path() {
+            Some(path) => format!("input `{}`", path.display()),
+            None => "stdin".to_owned(),
+        };
+        DebugDump::new(self.lock(), label, limit)
+    }
+
+    /// Returns a reader that copies everything read from this [`Input`] to `output`.
+    ///
+    /// This allows tools to archive the raw input (e.g. standard input) while processing it.
+    /// Call [`Tee::finish`] to flush `output` and check for errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, BufRead as _};
+    ///
+    /// use clap_file::{Input, Output};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::stdin();
+    ///     let mut reader = input.tee_to(Output::create("archive.txt".into())?);
+    ///     let mut line = String::new();
+    ///     while reader.read_line(&mut line)? > 0 {
+    ///         print!("{line}");
+    ///         line.clear();
+    ///     }
+    ///     reader.finish()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn tee_to(&self, output: Output) -> Tee<LockedInput<'_>> {
+        Tee::new(self.lock(), output)
+    }
+
+    /// Copies the entire contents of this [`Input`] to `output`, and flushes the output.
+    ///
+    /// Returns the number of bytes copied. As with [`with_io`](crate::with_io), this returns `Ok(None)`
+    /// if `output` is standard output and it has been closed by the reader (e.g. `mytool | head`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::{Input, Output};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Input files. If `-` is specified, reads from standard input.
+    ///     #[arg(default_value = "-")]
+    ///     inputs: Vec<Input>,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let output = Output::stdout();
+    ///     for input in &args.inputs {
+    ///         if input.copy_to(&output)?.is_none() {
+    ///             break;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn copy_to(&self, output: &Output) -> io::Result<Option<u64>> {
+        crate::with_io(self, output, |input, output| io::copy(input, output))
+    }
+
+    /// Calls `f` with each line of this [`Input`] and the locked `output`, and flushes the output.
+    ///
+    /// Lines are split in the same way as [`BufRead::lines`], and do not include the line terminator.
+    /// Returns the number of lines processed. As with [`with_io`](crate::with_io), this returns `Ok(None)`
+    /// if `output` is standard output and it has been closed by the reader (e.g. `mytool | head`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::{Input, Output};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Input file. If `-` is specified, reads from standard input.
+    ///     input: Input,
+    ///     /// Output file. If `-` is specified, writes to standard output.
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     args.input.process_lines(&args.output, |line, output| {
+    ///         writeln!(output, "{}", line.to_uppercase())
+    ///     })?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn process_lines<F>(&self, output: &Output, mut f: F) -> io::Result<Option<u64>>
+    where
+        F: FnMut(&str, &mut LockedOutput<'_>) -> io::Result<()>,
+    {
+        crate::with_io(self, output, |input, output| {
+            let mut count = 0;
+            for line in input.lines() {
+                f(&line?, output)?;
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+
+    /// Locks this [`Input`] and returns an iterator over its lines, as with [`BufRead::lines`].
+    ///
+    /// A line that is not valid UTF-8 is reported as an error of kind [`io::ErrorKind::InvalidData`],
+    /// and iteration continues with the next line.
+    /// Use [`Input::lines_lossy`] to replace invalid sequences instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::open("Cargo.toml".into())?;
+    ///     for line in input.lines() {
+    ///         let line = line?;
+    ///         if line.starts_with("name") {
+    ///             println!("{line}");
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Invalid lines can be skipped:
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-doc-lines-{}.txt", std::process::id()));
+    ///     std::fs::write(&path, b"first\ncaf\xe9\nlast\n")?;
+    ///     let input = Input::open(path.clone())?;
+    ///     let lines = input.lines().collect::<Vec<_>>();
+    ///     assert_eq!(lines.len(), 3);
+    ///     assert_eq!(lines[1].as_ref().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    ///     assert_eq!(lines[2].as_ref().unwrap(), "last");
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn lines(&self) -> io::Lines<LockedInput<'_>> {
+        self.lock().lines()
+    }
+
+    /// Locks this [`Input`] and returns an iterator over its lines that replaces invalid UTF-8 sequences with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Input;
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     pattern: String,
+    ///     /// Input file. If `-` is specified, reads from standard input.
+    ///     input: Input,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     for line in args.input.lines_lossy() {
+    ///         let line = line?;
+    ///         if line.contains(&args.pattern) {
+    ///             println!("{line}");
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn lines_lossy(&self) -> LossyLines<LockedInput<'_>> {
+        self.lock().lines_lossy()
+    }
+
+    /// Locks this [`Input`] and returns an iterator over its records separated by `delim`.
+    ///
+    /// See [`LockedInput::split_records`].
+    pub fn records(&self, delim: u8) -> Records<LockedInput<'_>> {
+        self.lock().split_records(delim)
+    }
+
+    /// Locks this [`Input`] and returns an iterator that deserializes each line as a JSON value of type `T`, as in JSON Lines (NDJSON).
+    ///
+    /// Blank lines are skipped. Errors are reported with the line number of the offending line.
+    /// See [`JsonLines`](crate::JsonLines).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Input;
+    ///
+    /// #[derive(Debug, serde::Deserialize)]
+    /// struct Event {
+    ///     user: String,
+    ///     count: u64,
+    /// }
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// NDJSON input file. If `-` is specified, reads from standard input.
+    ///     input: Input,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let mut total = 0;
+    ///     for event in args.input.json_lines::<Event>() {
+    ///         let event = event?;
+    ///         println!("{}: {}", event.user, event.count);
+    ///         total += event.count;
+    ///     }
+    ///     println!("total: {total}");
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn json_lines<T>(&self) -> crate::JsonLines<T, LockedInput<'_>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.lock().json_lines()
+    }
+
+    /// Locks this [`Input`] and returns a CSV reader over it with the default [`CsvOptions`](crate::CsvOptions).
+    ///
+    /// Fields are separated by tabs if the path has the `.tsv` extension, and by commas otherwise. The first row is read as the header.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Input;
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// CSV file. If `-` is specified, reads from standard input.
+    ///     input: Input,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let mut reader = args.input.csv_reader();
+    ///     println!("columns: {}", reader.headers()?.len());
+    ///     println!("rows: {}", reader.records().count());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "csv")]
+    pub fn csv_reader(&self) -> csv::Reader<LockedInput<'_>> {
+        self.csv_reader_with(&crate::CsvOptions::new())
+    }
+
+    /// Locks this [`Input`] and returns a CSV reader over it configured with `options`.
+    ///
+    /// See [`CsvOptions`](crate::CsvOptions) for an example.
+    #[cfg(feature = "csv")]
+    pub fn csv_reader_with(&self, options: &crate::CsvOptions) -> csv::Reader<LockedInput<'_>> {
+        options.reader(self.path(), self.lock())
+    }
+
+    /// Reads the rest of this [`Input`] and deserializes it from JSON into a value of type `T`.
+    ///
+    /// Errors in the data are reported with the path of the input, e.g. ``failed to parse `config.json`: ...``,
+    /// and [`ErrorKind::of`] classifies them as [`ErrorKind::InvalidInput`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Input;
+    ///
+    /// #[derive(Debug, serde::Deserialize)]
+    /// struct Config {
+    ///     name: String,
+    ///     retries: u32,
+    /// }
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Configuration file. If `-` is specified, reads from standard input.
+    ///     #[arg(long)]
+    ///     config: Input,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let config = args.config.read_json::<Config>()?;
+    ///     println!("{}: {} retries", config.name, config.retries);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn read_json<T>(&self) -> io::Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.deserialize(|text| serde_json::from_str(text))
+    }
+
+    /// Reads the rest of this [`Input`] and deserializes it from YAML into a value of type `T`.
+    ///
+    /// Errors are reported in the same way as [`Input::read_json`].
+    #[cfg(feature = "yaml")]
+    pub fn read_yaml<T>(&self) -> io::Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.deserialize(|text| serde_yaml::from_str(text))
+    }
+
+    /// Reads the rest of this [`Input`] and deserializes it from TOML into a value of type `T`.
+    ///
+    /// Errors are reported in the same way as [`Input::read_json`].
+    #[cfg(feature = "toml")]
+    pub fn read_toml<T>(&self) -> io::Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.deserialize(|text| toml::from_str(text))
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize<T, E>(&self, f: impl FnOnce(&str) -> Result<T, E>) -> io::Result<T>
+    where
+        E: std::fmt::Display,
+    {
+        let text = io::read_to_string(self.lock())?;
+        f(&text).map_err(|e| match self.path() {
+            Some(path) => crate::Error::new(
+                crate::Operation::Parse,
+                path,
+                error::new(ErrorKind::InvalidInput, e.to_string()),
+            )
+            .into(),
+            None => error::new(
+                ErrorKind::InvalidInput,
+                format!("failed to parse standard input: {e}"),
+            ),
+        })
+    }
+
+    /// Returns a reader that tracks how much of this [`Input`] has been read.
+    ///
+    /// The total size is known only if this [`Input`] reads from a regular file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, BufRead as _};
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::open("input.txt".into())?;
+    ///     let reader = input.track_progress();
+    ///     let progress = reader.progress();
+    ///     for line in reader.lines() {
+    ///         let _line = line?;
+    ///         match progress.fraction() {
+    ///             Some(fraction) => eprint!("\r{:.1}%", fraction * 100.0),
+    ///             None => eprint!("\r{} bytes ({:.0} B/s)", progress.bytes(), progress.throughput()),
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn track_progress(&self) -> ProgressReader<LockedInput<'_>> {
+        let total = match &self.0 {
+            InputInner::File { reader, .. } => {
+                let reader = lock(reader);
+                let file = reader.get_ref();
+                file.metadata()
+                    .ok()
+                    .filter(|m| m.is_file())
+                    .map(|m| m.len())
+            }
+            _ => None,
+        };
+        ProgressReader::new(self.lock(), total)
+    }
+
+    /// Locks the input source and returns a [`LockedInput`] instance.
+    ///
+    /// This lock is released when the returned [`LockedInput`] instance is dropped.
+    /// The returned `LockedInput` instance implements [`Read`] and [`BufRead`] traits.
+    pub fn lock(&self) -> LockedInput<'_> {
+        let inner = match &self.0 {
+            InputInner::Stdin => {
+                let reader = io::stdin().lock();
+                debug!("locked standard input");
+                LockedInputInner::Stdin { reader }
+            }
+            #[cfg(feature = "test-util")]
+            InputInner::InjectedStdin { reader } => {
+                let reader = lock(reader);
+                debug!("locked injected standard input");
+                LockedInputInner::InjectedStdin { reader }
+            }
+            InputInner::File {
+                path,
+                reader: file,
+                size_check,
+            } => {
+                let reader = lock(file);
+                debug!("locked input file `{}`", path.display());
+                LockedInputInner::File {
+                    path: Arc::clone(path),
+                    reader,
+                    size_check: Arc::clone(size_check),
+                }
+            }
+            InputInner::Decoded {
+                source,
+                decoding,
+                reader,
+            } => {
+                let reader = lock(reader);
+                debug!("locked decoded input");
+                LockedInputInner::Decoded {
+                    source,
+                    decoding: *decoding,
+                    reader,
+                }
+            }
+        };
+        LockedInput(inner, self.1)
+    }
+
+    /// Converts this [`Input`] into a buffered reader of a boxed trait object, a type accepted by many libraries.
+    ///
+    /// If this is the only handle to a file or a decoded input, its buffer is moved into the returned reader
+    /// along with the data already read into it, so the data is not buffered twice.
+    /// Otherwise, the returned reader reads through this [`Input`] with a buffer of the same capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, BufRead as _, Read as _};
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-buf-reader-{}", std::process::id()));
+    ///     std::fs::write(&path, "header\nbody\n")?;
+    ///     let input = Input::open(path.clone())?;
+    ///     let mut header = String::new();
+    ///     input.lock().read_line(&mut header)?;
+    ///
+    ///     let mut reader = input.into_buf_reader();
+    ///     let mut body = String::new();
+    ///     reader.read_to_string(&mut body)?;
+    ///     assert_eq!(body, "body\n");
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_buf_reader(mut self) -> BufReader<Box<dyn Read + Send>> {
+        // `Input` implements `Drop` with the `log` feature, which forbids moving out of it, so the inner value is swapped out.
+        match std::mem::replace(&mut self.0, InputInner::Stdin) {
+            InputInner::File {
+                path,
+                reader,
+                size_check,
+            } => match Arc::try_unwrap(reader) {
+                Ok(reader) => {
+                    debug!("moving the buffer of input file `{}`", path.display());
+                    return take_buffer(reader, |file| CheckedFile {
+                        path,
+                        file,
+                        size_check,
+                    });
+                }
+                Err(reader) => {
+                    self.0 = InputInner::File {
+                        path,
+                        reader,
+                        size_check,
+                    };
+                }
+            },
+            InputInner::Decoded {
+                source,
+                decoding,
+                reader,
+            } => match Arc::try_unwrap(reader) {
+                Ok(reader) => {
+                    debug!("moving the buffer of decoded input");
+                    return take_buffer(reader, |decoder| decoder);
+                }
+                Err(reader) => {
+                    self.0 = InputInner::Decoded {
+                        source,
+                        decoding,
+                        reader,
+                    };
+                }
+            },
+            inner => self.0 = inner,
+        }
+        let capacity = match &self.0 {
+            InputInner::File { reader, .. } => lock(reader).capacity(),
+            InputInner::Decoded { reader, .. } => lock(reader).capacity(),
+            _ => buf_size::DEFAULT,
+        };
+        BufReader::with_capacity(capacity, Box::new(self))
+    }
+}
+
+/// Moves the buffer of `reader` into a new buffered reader of `f(inner)`, keeping the data already buffered.
+fn take_buffer<R, S>(
+    reader: Mutex<BufReader<R>>,
+    f: impl FnOnce(R) -> S,
+) -> BufReader<Box<dyn Read + Send>>
+where
+    S: Read + Send + 'static,
+{
+    let reader = reader.into_inner().unwrap_or_else(|e| e.into_inner());
+    let capacity = reader.capacity();
+    let buffered = io::Cursor::new(reader.buffer().to_vec());
+    BufReader::with_capacity(capacity, Box::new(buffered.chain(f(reader.into_inner()))))
+}
+
+/// A file moved out of an [`Input`], which still reports a warning if its size changed while reading.
+struct CheckedFile {
+    path: Arc<PathBuf>,
+    file: File,
+    size_check: Arc<SizeCheck>,
+}
+
+impl Read for CheckedFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.file.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.size_check.at_eof(&self.path, &self.file);
+        }
+        Ok(n)
+    }
+}
+
+/// A builder for opening an [`Input`] with custom options.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io;
+///
+/// use clap_file::Input;
+///
+/// fn main() -> io::Result<()> {
+///     let input = Input::builder().capacity(1024 * 1024).open("input.txt".into())?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InputBuilder {
+    capacity: Option<usize>,
+    decompress: bool,
+    detect_compression: bool,
+    strip_bom: bool,
+    #[cfg(feature = "encoding")]
+    encoding: Option<crate::Encoding>,
+    #[cfg(feature = "lock")]
+    exclusive: bool,
+}
+
+impl Default for InputBuilder {
+    fn default() -> Self {
+        Self {
+            capacity: None,
+            decompress: true,
+            detect_compression: false,
+            strip_bom: false,
+            #[cfg(feature = "encoding")]
+            encoding: None,
+            #[cfg(feature = "lock")]
+            exclusive: false,
+        }
+    }
+}
+
+impl InputBuilder {
+    /// Creates a new [`InputBuilder`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether to decompress files based on their extension (e.g. `.gz`).
+    ///
+    /// Defaults to `true`. Only the formats whose cargo feature is enabled are decompressed; see [`Compression`].
+    pub fn decompress(&mut self, decompress: bool) -> &mut Self {
+        self.decompress = decompress;
+        self
+    }
+
+    /// Sets whether to detect the compression format of files from their first bytes instead of their extension.
+    ///
+    /// Defaults to `false`. When enabled, files are decompressed as with [`Input::decompress_auto`] regardless of their name.
+    /// This has no effect if [`decompress`](Self::decompress) is disabled.
+    pub fn detect_compression(&mut self, detect: bool) -> &mut Self {
+        self.detect_compression = detect;
+        self
+    }
+
+    /// Sets whether to strip the UTF-8 or UTF-16 byte order mark (BOM) at the start of files, as with [`Input::strip_bom`].
+    ///
+    /// Defaults to `false`. Files are checked after they are decompressed.
+    pub fn strip_bom(&mut self, strip: bool) -> &mut Self {
+        self.strip_bom = strip;
+        self
+    }
+
+    /// Sets the character encoding to transcode files from into UTF-8, as with [`Input::transcode`].
+    ///
+    /// Files are transcoded after they are decompressed. If not set, files are read as is.
+    #[cfg(feature = "encoding")]
+    pub fn encoding(&mut self, encoding: crate::Encoding) -> &mut Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Sets the capacity of the read buffer.
+    ///
+    /// If not set, the capacity is chosen based on the type of the opened file:
+    /// a large buffer for regular files, a pipe-sized buffer for FIFOs, and a small buffer for terminals.
+    pub fn capacity(&mut self, capacity: usize) -> &mut Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Sets whether to prevent the file from being modified while it is open.
+    ///
+    /// On Windows, the file is opened without write and delete sharing, so other processes cannot open it for writing
+    /// (and opening fails if another process already has it open for writing).
+    /// On Unix, a shared advisory lock ([`flock(2)`](https://man7.org/linux/man-pages/man2/flock.2.html)) is taken,
+    /// which only excludes processes that take an exclusive lock before writing.
+    /// Opening fails with an error of kind [`ErrorKind::WouldBlock`](crate::ErrorKind::WouldBlock) (and [`io::ErrorKind::WouldBlock`])
+    /// if the file is exclusively locked by another process.
+    ///
+    /// Defaults to `false`. With `open_at` (`cap-std` feature), only the advisory lock on Unix is taken.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::builder().exclusive(true).open("Cargo.toml".into())?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "lock")]
+    pub fn exclusive(&mut self, exclusive: bool) -> &mut Self {
+        self.exclusive = exclusive;
+        self
+    }
+
+    /// Takes a file descriptor inherited from the parent process and reads from it with the options specified by this builder.
+    ///
+    /// This allows service-style programs to read from sockets managed by systemd-style socket activation,
+    /// or from descriptors redirected by the shell (e.g. `mytool fd:3 3<input.txt`). `spec` is one of the following:
+    ///
+    /// * A descriptor number, e.g. `3`.
+    /// * `listen`, for the first descriptor passed by socket activation (`LISTEN_FDS`).
+    /// * A descriptor name passed by socket activation (`LISTEN_FDNAMES`), set by `FileDescriptorName=` in systemd.
+    ///
+    /// Arguments of the form `fd:<spec>` (e.g. `fd:3` or `fd:listen`) take an inherited descriptor
+    /// only if enabled with [`InputValueParser::inherited_fds`](crate::InputValueParser::inherited_fds).
+    /// The descriptor is duplicated and the original is left open, so only pass descriptors that this process inherited;
+    /// a descriptor of a file opened by this process would share its file offset. Each descriptor can be taken only once.
+    /// Listening sockets cannot be read from directly; use `Accept=yes` in the socket unit to receive connected sockets.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::Unsupported`] on platforms other than Unix.
+    pub fn open_inherited(&self, spec: &str) -> io::Result<Input> {
+        let file = inherited_fd::open(spec)?;
+        self.build(PathBuf::from(format!("fd:{spec}")), file)
+    }
+
+    /// Opens a file at the given path with the options specified by this builder.
+    pub fn open(&self, path: PathBuf) -> io::Result<Input> {
+        #[cfg(feature = "lock")]
+        if self.exclusive {
+            let file = crate::file_lock::open_exclusive(&path)?;
+            return self.build(path, file);
+        }
+        let file = File::open(&path)?;
+        self.build(path, file)
+    }
+
+    /// Opens a file at the given path relative to `dir` with the options specified by this builder.
+    ///
+    /// The path is resolved with [`cap_std`], so it cannot escape `dir` (e.g. by `..` or absolute paths).
+    /// This allows capability-based sandboxed programs to open files specified by command-line arguments safely.
+    #[cfg(feature = "cap-std")]
+    pub fn open_at(&self, dir: &cap_std::fs::Dir, path: PathBuf) -> io::Result<Input> {
+        let file = dir.open(&path)?.into_std();
+        #[cfg(all(feature = "lock", unix))]
+        if self.exclusive {
+            crate::file_lock::lock_shared(&file, &path)?;
+        }
+        self.build(path, file)
+    }
+
+    fn build(&self, path: PathBuf, file: File) -> io::Result<Input> {
+        let compression = Compression::from_path(&path).filter(|compression| {
+            self.decompress && !self.detect_compression && compression.is_supported()
+        });
+        let path = Arc::new(path);
+        let capacity = self.capacity.unwrap_or_else(|| buf_size::for_file(&file));
+        debug!(
+            "opened input file `{}` (buffer capacity: {capacity})",
+            path.display()
+        );
+        let size_check = Arc::new(SizeCheck::new(&file));
+        let reader = Arc::new(Mutex::new(BufReader::with_capacity(capacity, file)));
+        let input = Input(
+            InputInner::File {
+                path,
+                reader,
+                size_check,
+            },
+            None,
+            None,
+        );
+        let input = match compression {
+            Some(compression) => input.decompress(compression)?,
+            None if self.decompress && self.detect_compression => input.decompress_auto()?,
+            None => input,
+        };
+        let input = if self.strip_bom {
+            input.strip_bom()?
+        } else {
+            input
+        };
+        #[cfg(feature = "encoding")]
+        if let Some(encoding) = self.encoding {
+            return input.transcode(encoding);
+        }
+        Ok(input)
+    }
+}
+
+impl Input {
+    /// Reports a warning if the size of the file changed since it was opened.
+    fn at_eof(&self) {
+        if let InputInner::File {
+            path,
+            reader,
+            size_check,
+        } = &self.0
+        {
+            size_check.at_eof(path, lock(reader).get_ref());
+        }
+    }
+}
+
+// Only used to log when the file is closed.
+#[cfg(feature = "log")]
+impl Drop for Input {
+    fn drop(&mut self) {
+        if let InputInner::File { path, reader, .. } = &self.0 {
+            if Arc::strong_count(reader) == 1 {
+                debug!("closing input file `{}`", path.display());
+            }
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(PathBuf::from(s))
+    }
+}
+
+/// Opens an [`Input`] with the same rules as parsing it from a command-line argument.
+///
+/// `-` reads from standard input, and any other path opens a file. Use `./-` to open a file named `-`.
+/// Inherited file descriptors (`fd:<spec>`) are not taken; use [`Input::inherited`] for them.
+/// This allows alternative argument parsers and programmatic construction to share the behavior of the clap value parser.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::{io, path::PathBuf};
+///
+/// use clap_file::Input;
+///
+/// fn main() -> io::Result<()> {
+///     let input = Input::try_from(PathBuf::from("Cargo.toml"))?;
+///     assert!(input.is_file());
+///     Ok(())
+/// }
+/// ```
+impl TryFrom<PathBuf> for Input {
+    type Error = io::Error;
+
+    fn try_from(value: PathBuf) -> Result<Self, Self::Error> {
+        if value.as_os_str() == "-" {
+            return Self::claimed_stdin();
+        }
+        Self::open(value)
+    }
+}
+
+/// Opens an [`Input`] with the same rules as parsing it from a command-line argument.
+impl TryFrom<&Path> for Input {
+    type Error = io::Error;
+
+    fn try_from(value: &Path) -> Result<Self, Self::Error> {
+        Self::try_from(value.to_owned())
+    }
+}
+
+/// Opens an [`Input`] with the same rules as parsing it from a command-line argument.
+impl TryFrom<&OsStr> for Input {
+    type Error = io::Error;
+
+    fn try_from(value: &OsStr) -> Result<Self, Self::Error> {
+        Self::try_from(PathBuf::from(value))
+    }
+}
+
+macro_rules! with_reader {
+    ($inner:expr, $var:ident => $e:expr) => {
+        match $inner {
+            InputInner::Stdin => {
+                let mut $var = io::stdin();
+                $e
+            }
+            #[cfg(feature = "test-util")]
+            InputInner::InjectedStdin { reader } => {
+                let mut $var = lock(reader);
+                $e
+            }
+            InputInner::File { reader, .. } => {
+                let mut $var = lock(reader);
+                $e
+            }
+            InputInner::Decoded { reader, .. } => {
+                let mut $var = lock(reader);
+                $e
+            }
+        }
+    };
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        (&*self).read_vectored(bufs)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        (&*self).read_to_end(buf)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        (&*self).read_to_string(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        (&*self).read_exact(buf)
+    }
+}
+
+impl Seek for Input {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        (&*self).seek(pos)
+    }
+}
+
+impl Seek for &Input {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match &self.0 {
+            InputInner::File { reader, .. } => lock(reader).seek(pos),
+            InputInner::Decoded { decoding, .. } => Err(decoding.not_seekable()),
+            _ => Err(stdin_not_seekable()),
+        }
+    }
+}
+
+// Like `&File` and `&Stdin`, reading through a shared reference is safe because the underlying
+// source is protected by a mutex.
+impl Read for &Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = with_reader!(&self.0, r => r.read(buf))?;
+        if n == 0 && !buf.is_empty() {
+            self.at_eof();
+        }
+        Ok(n)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        let n = with_reader!(&self.0, r => r.read_vectored(bufs))?;
+        if n == 0 && bufs.iter().any(|buf| !buf.is_empty()) {
+            self.at_eof();
+        }
+        Ok(n)
+    }
+
+    // this method is not yet stable
+    // fn is_read_vectored(&self) -> bool {
+    //     with_reader!(&self.0, r => r.is_read_vectored())
+    // }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let n = with_reader!(&self.0, r => r.read_to_end(buf))?;
+        self.at_eof();
+        Ok(n)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        let n = with_reader!(&self.0, r => r.read_to_string(buf))?;
+        self.at_eof();
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let result = with_reader!(&self.0, r => r.read_exact(buf));
+        if matches!(&result, Err(e) if e.kind() == io::ErrorKind::UnexpectedEof) {
+            self.at_eof();
+        }
+        result
+    }
+
+    // this method is not yet stable
+    // fn read_buf(&mut self, buf: io::BorrowedCursor<'_>) -> io::Result<()> {
+    //     with_reader!(&self.0, r => r.read_buf(buf))
+    // }
+
+    // this method is not yet stable
+    // fn read_buf_exact(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
+    //     with_reader!(&self.0, r => r.read_buf_exact(cursor))
+    // }
+}
+
+/// An input source that only accepts standard input.
+///
+/// Parsing this type from a string only succeeds for `-`; any file path is rejected.
+/// Like [`Input`], standard input can be specified at most once among the parsed arguments that are alive at the same time,
+/// and clap applies the defaults installed with [`ClapFileConfig`](crate::ClapFileConfig), such as [`ClosedStdio`](crate::ClosedStdio).
+/// This is useful for arguments that currently only support standard input but may support files in the future.
+/// Since [`StdinOnly`] dereferences to [`Input`], migrating the argument to [`Input`] later requires no code changes other than the type.
 ///
 /// # Examples
 ///
@@ -19,176 +1872,232 @@ fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
 /// use std::io::{self, BufRead as _};
 ///
 /// use clap::Parser as _;
-/// use clap_file::Input;
+/// use clap_file::StdinOnly;
 ///
 /// #[derive(Debug, clap::Parser)]
 /// struct Args {
-///     /// Input file. If not provided, reads from standard input.
-///     input: Input,
+///     /// Input. Only `-` (standard input) is supported for now.
+///     #[arg(default_value = "-")]
+///     input: StdinOnly,
 /// }
 ///
 /// fn main() -> io::Result<()> {
 ///     let args = Args::parse();
-///     let input = args.input.lock();
-///     for line in input.lines() {
+///     for line in args.input.lock().lines() {
 ///         let line = line?;
 ///         println!("{line}");
 ///     }
 ///     Ok(())
 /// }
 /// ```
-
-// This struct should not implement `Clone`, but clap-derive requires Clone [1].
-// So, I added `Clone` to the struct and wrap `File` with `Arc` and `Mutex`.
-// This is not the best way to handle this, but it works for now.
-//
-// [1]: https://github.com/clap-rs/clap/issues/4286
 #[derive(Debug, Clone)]
-pub struct Input(InputInner);
+pub struct StdinOnly(pub(crate) Input);
 
-#[derive(Debug, Clone)]
-enum InputInner {
-    Stdin,
-    File {
-        path: Arc<PathBuf>,
-        reader: Arc<Mutex<BufReader<File>>>,
-    },
+impl StdinOnly {
+    /// Creates a new [`StdinOnly`] instance that reads from standard input.
+    pub fn new() -> Self {
+        Self(Input::stdin())
+    }
+
+    /// Converts this [`StdinOnly`] into an [`Input`].
+    pub fn into_input(self) -> Input {
+        self.0
+    }
 }
 
-impl Input {
-    /// Creates a new [`Input`] instance that reads from standard input.
-    pub fn stdin() -> Self {
-        Self(InputInner::Stdin)
+impl Default for StdinOnly {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    /// Opens a file at the given path and creates a new [`Input`] instance that reads from it.
-    pub fn open(path: PathBuf) -> io::Result<Self> {
-        let path = Arc::new(path);
-        let file = File::open(&*path)?;
-        let reader = Arc::new(Mutex::new(BufReader::new(file)));
-        Ok(Self(InputInner::File { path, reader }))
+impl Deref for StdinOnly {
+    type Target = Input;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
+}
 
-    /// Returns `true` if this [`Input`] reads from standard input.
-    pub fn is_stdin(&self) -> bool {
-        matches!(self.0, InputInner::Stdin)
+impl From<StdinOnly> for Input {
+    fn from(value: StdinOnly) -> Self {
+        value.into_input()
     }
+}
 
-    /// Returns `true` if this [`Input`] reads from a file.
+impl FromStr for StdinOnly {
+    type Err = io::Error;
 
-    pub fn is_file(&self) -> bool {
-        matches!(self.0, InputInner::File { .. })
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            return Input::claimed_stdin().map(Self);
+        }
+        Err(error::new(
+            ErrorKind::InvalidInput,
+            "only standard input (`-`) is supported",
+        ))
     }
+}
 
-    /// Returns the path of the file this [`Input`] reads from.
-    ///
-    /// Returns `None` if this [`Input`] reads from standard input.
-    pub fn path(&self) -> Option<&Path> {
-        match &self.0 {
-            InputInner::Stdin => None,
-            InputInner::File { path, .. } => Some(path),
-        }
+impl Read for StdinOnly {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
     }
 
-    /// Locks the input source and returns a [`LockedInput`] instance.
-    ///
-    /// This lock is released when the returned [`LockedInput`] instance is dropped.
-    /// The returned `LockedInput` instance implements [`Read`] and [`BufRead`] traits.
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.0.read_vectored(bufs)
+    }
 
-    pub fn lock(&self) -> LockedInput<'_> {
-        let inner = match &self.0 {
-            InputInner::Stdin => {
-                let reader = io::stdin().lock();
-                LockedInputInner::Stdin { reader }
-            }
-            InputInner::File { path, reader: file } => {
-                let reader = lock(file);
-                LockedInputInner::File {
-                    path: Arc::clone(path),
-                    reader,
-                }
-            }
-        };
-        LockedInput(inner)
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.0.read_to_end(buf)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.0.read_to_string(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.0.read_exact(buf)
     }
 }
 
-impl FromStr for Input {
+/// An input source that only accepts files.
+///
+/// Parsing this type from a string fails for `-`, so clap reports an error if standard input is specified.
+/// Otherwise, clap parses the value like an [`Input`], applying the defaults installed with [`ClapFileConfig`](crate::ClapFileConfig)
+/// and accepting non-UTF-8 paths.
+/// This is useful for commands where streaming input makes no sense, e.g. tools that need to seek.
+/// [`FileInput`] dereferences to [`Input`].
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::FileInput;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Input file. Standard input is not supported.
+///     input: FileInput,
+/// }
+///
+/// let args = Args::try_parse_from(["prog", "Cargo.toml"]).unwrap();
+/// assert_eq!(args.input.path(), std::path::Path::new("Cargo.toml"));
+/// assert!(Args::try_parse_from(["prog", "-"]).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileInput(pub(crate) Input);
+
+impl FileInput {
+    /// Opens a file at the given path and creates a new [`FileInput`] instance that reads from it.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        Input::open(path).map(Self)
+    }
+
+    /// Returns the path of the file this [`FileInput`] reads from.
+    pub fn path(&self) -> &Path {
+        self.0.path().expect("file input must have a path")
+    }
+
+    /// Converts this [`FileInput`] into an [`Input`].
+    pub fn into_input(self) -> Input {
+        self.0
+    }
+}
+
+impl Deref for FileInput {
+    type Target = Input;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<FileInput> for Input {
+    fn from(value: FileInput) -> Self {
+        value.into_input()
+    }
+}
+
+impl FromStr for FileInput {
     type Err = io::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s == "-" {
-            return Ok(Self::stdin());
+            return Err(error::new(
+                ErrorKind::Unsupported,
+                "standard input (`-`) is not supported; specify a file",
+            ));
         }
         Self::open(PathBuf::from(s))
     }
 }
 
-macro_rules! with_reader {
-    ($inner:expr, $var:ident => $e:expr) => {
-        match $inner {
-            InputInner::Stdin => {
-                let mut $var = io::stdin();
-                $e
-            }
-            InputInner::File { reader, .. } => {
-                let mut $var = lock(reader);
-                $e
-            }
-        }
-    };
-}
-
-impl Read for Input {
+impl Read for FileInput {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        with_reader!(&self.0, r => r.read(buf))
+        self.0.read(buf)
     }
 
     fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
-        with_reader!(&self.0, r => r.read_vectored(bufs))
+        self.0.read_vectored(bufs)
     }
 
-    // this method is not yet stable
-    // fn is_read_vectored(&self) -> bool {
-    //     with_reader!(&self.0, r => r.is_read_vectored())
-    // }
-
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
-        with_reader!(&self.0, r => r.read_to_end(buf))
+        self.0.read_to_end(buf)
     }
 
     fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
-        with_reader!(&self.0, r => r.read_to_string(buf))
+        self.0.read_to_string(buf)
     }
 
     fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
-        with_reader!(&self.0, r => r.read_exact(buf))
+        self.0.read_exact(buf)
     }
-
-    // this method is not yet stable
-    // fn read_buf(&mut self, buf: io::BorrowedCursor<'_>) -> io::Result<()> {
-    //     with_reader!(&self.0, r => r.read_buf(buf))
-    // }
-
-    // this method is not yet stable
-    // fn read_buf_exact(&mut self, cursor: io::BorrowedCursor<'_>) -> io::Result<()> {
-    //     with_reader!(&self.0, r => r.read_buf_exact(cursor))
-    // }
 }
 
 /// A locked input source that implements [`Read`] and [`BufRead`] traits.
 #[derive(Debug)]
-pub struct LockedInput<'a>(LockedInputInner<'a>);
+pub struct LockedInput<'a>(LockedInputInner<'a>, Option<Bom>);
 
 impl LockedInput<'_> {
+    /// Returns the encoding identified by the byte order mark stripped with [`Input::strip_bom`], if any.
+    ///
+    /// This is useful for reporting the encoding of inputs whose BOM would otherwise be invisible to users.
+    pub fn detected_encoding(&self) -> Option<Bom> {
+        self.1
+    }
+
+    /// Returns the number of bytes that have been read from the source into the buffer but not yet consumed.
+    ///
+    /// Returns `None` if this [`LockedInput`] reads from standard input, whose buffer is not accessible.
+    pub fn buffered(&self) -> Option<usize> {
+        match &self.0 {
+            LockedInputInner::Stdin { .. } => None,
+            #[cfg(feature = "test-util")]
+            LockedInputInner::InjectedStdin { .. } => None,
+            LockedInputInner::File { reader, .. } => Some(reader.buffer().len()),
+            LockedInputInner::Decoded { reader, .. } => Some(reader.buffer().len()),
+        }
+    }
+
     /// Returns `true` if this [`LockedInput`] reads from standard input.
     pub fn is_stdin(&self) -> bool {
-        matches!(self.0, LockedInputInner::Stdin { .. })
+        match &self.0 {
+            LockedInputInner::Stdin { .. } => true,
+            #[cfg(feature = "test-util")]
+            LockedInputInner::InjectedStdin { .. } => true,
+            LockedInputInner::File { .. } => false,
+            LockedInputInner::Decoded { source, .. } => source.is_stdin(),
+        }
     }
 
     /// Returns `true` if this [`LockedInput`] reads from a file.
     pub fn is_file(&self) -> bool {
-        matches!(self.0, LockedInputInner::File { .. })
+        match &self.0 {
+            LockedInputInner::File { .. } => true,
+            LockedInputInner::Decoded { source, .. } => source.is_file(),
+            _ => false,
+        }
     }
 
     /// Returns the path of the file this [`LockedInput`] reads from.
@@ -197,9 +2106,143 @@ impl LockedInput<'_> {
     pub fn path(&self) -> Option<&Path> {
         match &self.0 {
             LockedInputInner::Stdin { .. } => None,
+            #[cfg(feature = "test-util")]
+            LockedInputInner::InjectedStdin { .. } => None,
             LockedInputInner::File { path, .. } => Some(path),
+            LockedInputInner::Decoded { source, .. } => source.path(),
         }
     }
+
+    /// Returns an iterator over batches of up to `size` lines of this [`LockedInput`].
+    ///
+    /// Each batch contains exactly `size` lines, except for the last one, which may contain fewer.
+    /// Lines are split in the same way as [`BufRead::lines`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::stdin();
+    ///     for batch in input.lock().line_batches(100) {
+    ///         let batch = batch?;
+    ///         println!("submitting {} lines", batch.len());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn line_batches(self, size: usize) -> LineBatches<Self> {
+        LineBatches::new(self, size)
+    }
+
+    /// Returns an iterator over the lines of this [`LockedInput`] that accepts `\n`, `\r\n`, and lone `\r` as line terminators.
+    ///
+    /// Unlike [`BufRead::lines`], which only splits on `\n` and leaves a trailing `\r` in the line,
+    /// this behaves the same on every platform, and reports the terminator of each line.
+    /// A line that is not valid UTF-8 is reported as an error of kind [`ErrorKind::InvalidData`](crate::ErrorKind::InvalidData).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap_file::{Input, LineEnding, Output};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::open("Cargo.toml".into())?;
+    ///     let mut output = Output::stdout();
+    ///     for line in input.lock().universal_newlines() {
+    ///         let (line, ending) = line?;
+    ///         let ending = ending.map_or("", LineEnding::as_str);
+    ///         write!(output, "{}{ending}", line.to_uppercase())?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Invalid UTF-8 is classified with [`ErrorKind::of`](crate::ErrorKind::of):
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::{ErrorKind, Input};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-doc-invalid-{}.txt", std::process::id()));
+    ///     std::fs::write(&path, b"caf\xe9\n")?;
+    ///     let input = Input::open(path.clone())?;
+    ///     let error = input.lock().universal_newlines().next().unwrap().unwrap_err();
+    ///     assert_eq!(ErrorKind::of(&error), ErrorKind::InvalidData);
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn universal_newlines(self) -> UniversalLines<Self> {
+        UniversalLines::new(self)
+    }
+
+    /// Returns an iterator over the lines of this [`LockedInput`] that replaces invalid UTF-8 sequences with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// Lines are split in the same way as [`BufRead::lines`]. See [`Input::lines_lossy`].
+    pub fn lines_lossy(self) -> LossyLines<Self> {
+        LossyLines::new(self)
+    }
+
+    /// Returns an iterator over the records of this [`LockedInput`] separated by `delim`, without the delimiter.
+    ///
+    /// Records are returned as bytes, since they are often file names that may not be valid UTF-8.
+    /// With `b'\0'` as the delimiter, this reads the output of `find -print0` or `git ls-files -z`,
+    /// so tools can offer a `-0` option as `xargs` does.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Input;
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Input items are terminated by a null character instead of a newline.
+    ///     #[arg(short = '0', long)]
+    ///     null: bool,
+    ///     /// Input file. If `-` is specified, reads from standard input.
+    ///     #[arg(default_value = "-")]
+    ///     input: Input,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let delim = if args.null { b'\0' } else { b'\n' };
+    ///     for record in args.input.lock().split_records(delim) {
+    ///         let record = record?;
+    ///         println!("{}", String::from_utf8_lossy(&record));
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn split_records(self, delim: u8) -> Records<Self> {
+        Records::new(self, delim)
+    }
+
+    /// Returns an iterator that deserializes each line of this [`LockedInput`] as a JSON value of type `T`.
+    ///
+    /// See [`Input::json_lines`].
+    #[cfg(feature = "serde")]
+    pub fn json_lines<T>(self) -> crate::JsonLines<T, Self>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        crate::JsonLines::new(self)
+    }
 }
 
 #[derive(Debug)]
@@ -207,9 +2250,19 @@ enum LockedInputInner<'a> {
     Stdin {
         reader: io::StdinLock<'a>,
     },
+    #[cfg(feature = "test-util")]
+    InjectedStdin {
+        reader: MutexGuard<'a, io::Cursor<Vec<u8>>>,
+    },
     File {
         path: Arc<PathBuf>,
         reader: MutexGuard<'a, BufReader<File>>,
+        size_check: Arc<SizeCheck>,
+    },
+    Decoded {
+        source: &'a Input,
+        decoding: Decoding,
+        reader: MutexGuard<'a, BufReader<Decoder>>,
     },
 }
 
@@ -220,21 +2273,52 @@ macro_rules! with_locked_reader {
                 let $var = reader;
                 $e
             }
+            #[cfg(feature = "test-util")]
+            LockedInputInner::InjectedStdin { reader } => {
+                let $var = reader;
+                $e
+            }
             LockedInputInner::File { reader, .. } => {
                 let $var = reader;
                 $e
             }
+            LockedInputInner::Decoded { reader, .. } => {
+                let $var = reader;
+                $e
+            }
         }
     };
 }
 
+impl LockedInput<'_> {
+    /// Reports a warning if the size of the file changed since it was opened.
+    fn at_eof(&self) {
+        if let LockedInputInner::File {
+            path,
+            reader,
+            size_check,
+        } = &self.0
+        {
+            size_check.at_eof(path, reader.get_ref());
+        }
+    }
+}
+
 impl Read for LockedInput<'_> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        with_locked_reader!(&mut self.0, r => r.read(buf))
+        let n = with_locked_reader!(&mut self.0, r => r.read(buf))?;
+        if n == 0 && !buf.is_empty() {
+            self.at_eof();
+        }
+        Ok(n)
     }
 
     fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
-        with_locked_reader!(&mut self.0, r => r.read_vectored(bufs))
+        let n = with_locked_reader!(&mut self.0, r => r.read_vectored(bufs))?;
+        if n == 0 && bufs.iter().any(|buf| !buf.is_empty()) {
+            self.at_eof();
+        }
+        Ok(n)
     }
 
     // this method is not yet stable
@@ -243,15 +2327,23 @@ impl Read for LockedInput<'_> {
     // }
 
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
-        with_locked_reader!(&mut self.0, r => r.read_to_end(buf))
+        let n = with_locked_reader!(&mut self.0, r => r.read_to_end(buf))?;
+        self.at_eof();
+        Ok(n)
     }
 
     fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
-        with_locked_reader!(&mut self.0, r => r.read_to_string(buf))
+        let n = with_locked_reader!(&mut self.0, r => r.read_to_string(buf))?;
+        self.at_eof();
+        Ok(n)
     }
 
     fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
-        with_locked_reader!(&mut self.0, r => r.read_exact(buf))
+        let result = with_locked_reader!(&mut self.0, r => r.read_exact(buf));
+        if matches!(&result, Err(e) if e.kind() == io::ErrorKind::UnexpectedEof) {
+            self.at_eof();
+        }
+        result
     }
 
     // this method is not yet stable
@@ -265,8 +2357,22 @@ impl Read for LockedInput<'_> {
     // }
 }
 
+impl Seek for LockedInput<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match &mut self.0 {
+            LockedInputInner::File { reader, .. } => reader.seek(pos),
+            LockedInputInner::Decoded { decoding, .. } => Err(decoding.not_seekable()),
+            _ => Err(stdin_not_seekable()),
+        }
+    }
+}
+
 impl BufRead for LockedInput<'_> {
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let eof = with_locked_reader!(&mut self.0, r => r.fill_buf())?.is_empty();
+        if eof {
+            self.at_eof();
+        }
         with_locked_reader!(&mut self.0, r => r.fill_buf())
     }
 
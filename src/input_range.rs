@@ -0,0 +1,121 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{error, ErrorKind};
+
+/// A reader over a byte range of a file, created by [`Input::split_ranges`](crate::Input::split_ranges).
+///
+/// Each [`InputRange`] owns its own file descriptor and reads with positional I/O, so the ranges of a file
+/// can be read from different threads without contending for a lock or disturbing each other's position.
+/// Positions used by [`Seek`] are relative to the start of the range.
+#[derive(Debug)]
+pub struct InputRange {
+    path: Arc<PathBuf>,
+    file: File,
+    range: Range<u64>,
+    pos: u64,
+}
+
+impl InputRange {
+    pub(crate) fn split(path: &Arc<PathBuf>, file: &File, n: usize) -> io::Result<Vec<Self>> {
+        if n == 0 {
+            return Err(error::new(
+                ErrorKind::InvalidInput,
+                "the number of ranges must be at least 1",
+            ));
+        }
+        let len = file.metadata()?.len();
+        let n = n as u64;
+        let (size, rest) = (len / n, len % n);
+        let mut start = 0;
+        (0..n)
+            .map(|i| {
+                // The first `rest` ranges take one extra byte, so that the sizes differ by at most one.
+                let end = start + size + u64::from(i < rest);
+                let range = Self {
+                    path: Arc::clone(path),
+                    file: file.try_clone()?,
+                    range: start..end,
+                    pos: 0,
+                };
+                start = end;
+                Ok(range)
+            })
+            .collect()
+    }
+
+    /// Returns the path of the file this [`InputRange`] reads from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the byte range of the file this [`InputRange`] reads.
+    pub fn range(&self) -> Range<u64> {
+        self.range.clone()
+    }
+
+    /// Returns the length of the range in bytes.
+    pub fn len(&self) -> u64 {
+        self.range.end - self.range.start
+    }
+
+    /// Returns `true` if the range is empty.
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::FileExt::read_at(&self.file, buf, offset)
+        }
+        #[cfg(windows)]
+        {
+            std::os::windows::fs::FileExt::seek_read(&self.file, buf, offset)
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            // Without positional I/O, the file position is shared with the other ranges of the same file.
+            let mut file = &self.file;
+            file.seek(SeekFrom::Start(offset))?;
+            file.read(buf)
+        }
+    }
+}
+
+impl Read for InputRange {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len().saturating_sub(self.pos);
+        let max = usize::try_from(remaining)
+            .unwrap_or(usize::MAX)
+            .min(buf.len());
+        if max == 0 {
+            return Ok(0);
+        }
+        let n = self.read_at(&mut buf[..max], self.range.start + self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for InputRange {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        self.pos = pos.ok_or_else(|| {
+            error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,322 @@
+use std::{
+    fmt,
+    io::{self, BufRead as _, Write as _},
+    iter,
+    ops::Deref,
+    path::{Path, PathBuf},
+    slice, vec,
+};
+
+use crate::{Input, LockedInput, SkipReason};
+
+/// A list of input sources, each of which can be either standard input or a file.
+///
+/// Standard input can be consumed only once, so `-` is accepted at most once among the [`Input`]s parsed from command-line arguments.
+/// This makes `Vec<Input>` arguments work well with clap's `num_args` and `value_delimiter`
+/// (e.g. `--inputs a.txt,b.txt,-`).
+///
+/// Note that paths containing the delimiter cannot be specified when `value_delimiter` is used.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::{Input, Inputs};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Comma-separated input files. `-` means standard input.
+///     #[arg(long, value_delimiter = ',')]
+///     inputs: Vec<Input>,
+/// }
+///
+/// let args = Args::try_parse_from(["prog", "--inputs", "Cargo.toml,-"]).unwrap();
+/// let inputs = Inputs::from(args.inputs);
+/// assert_eq!(inputs.len(), 2);
+/// assert!(inputs[0].is_file());
+/// assert!(inputs[1].is_stdin());
+/// drop(inputs);
+///
+/// // `-` cannot be specified twice
+/// assert!(Args::try_parse_from(["prog", "--inputs", "-,-"]).is_err());
+/// // Standard input is released once the inputs parsed from `-` are dropped
+/// assert!(Args::try_parse_from(["prog", "--inputs", "-"]).is_ok());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Inputs(Vec<Input>);
+
+impl Inputs {
+    /// Creates a new [`Inputs`] instance from the given list of [`Input`]s.
+    pub fn new(inputs: Vec<Input>) -> Self {
+        Self(inputs)
+    }
+
+    /// Returns an iterator over the [`Input`]s.
+    pub fn iter(&self) -> slice::Iter<'_, Input> {
+        self.0.iter()
+    }
+
+    /// Converts this [`Inputs`] into a `Vec<Input>`.
+    pub fn into_vec(self) -> Vec<Input> {
+        self.0
+    }
+
+    /// Returns an iterator over the [`Input`]s, each paired with its name for display.
+    pub fn iter_files(&self) -> IterFiles<'_> {
+        IterFiles(self.0.iter().map(InputFile))
+    }
+
+    /// Calls `f` for each line of each input, in order.
+    ///
+    /// `f` receives the input the line belongs to, the 1-based line number within that input, and the line without the trailing newline.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::{Input, Inputs};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let inputs = Inputs::new(vec![Input::open("Cargo.toml".into())?]);
+    ///     // prints lines like `grep -n`
+    ///     inputs.for_each_line(|file, lineno, line| {
+    ///         if line.contains("clap") {
+    ///             println!("{file}:{lineno}:{line}");
+    ///         }
+    ///         Ok(())
+    ///     })?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn for_each_line<F>(&self, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(&InputFile<'_>, u64, &str) -> io::Result<()>,
+    {
+        for file in self.iter_files() {
+            for (line, lineno) in file.lock().lines().zip(1..) {
+                f(&file, lineno, &line?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens the given paths, collecting failures instead of aborting at the first one.
+    ///
+    /// `-` stands for standard input. Returns the inputs that were opened successfully and the failures.
+    /// This is useful for tools like `grep` and `wc`, which report unreadable files and continue with the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::PathBuf;
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Inputs;
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Input files.
+    ///     paths: Vec<PathBuf>,
+    /// }
+    ///
+    /// let args = Args::parse_from(["prog", "Cargo.toml", "no-such-file"]);
+    /// let (inputs, mut errors) = Inputs::open_tolerant(args.paths);
+    /// assert_eq!(inputs.len(), 1);
+    /// errors.append(inputs.for_each_tolerant(|file| {
+    ///     let _text = std::io::read_to_string(file.lock())?;
+    ///     Ok(())
+    /// }));
+    /// assert_eq!(errors.len(), 1);
+    /// errors.report();
+    /// assert_eq!(errors.exit_code(), 1);
+    /// ```
+    pub fn open_tolerant<I, P>(paths: I) -> (Self, InputErrors)
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        let mut inputs = vec![];
+        let mut errors = InputErrors::default();
+        for path in paths {
+            let path = path.into();
+            let result = match path.to_str() {
+                Some(s @ "-") => s.parse(),
+                _ => Input::open(path.clone()),
+            };
+            match result {
+                Ok(input) => inputs.push(input),
+                Err(e) => errors.push(path.display().to_string(), e),
+            }
+        }
+        (Self(inputs), errors)
+    }
+
+    /// Calls `f` for each input, collecting failures instead of aborting at the first one.
+    pub fn for_each_tolerant<F>(&self, mut f: F) -> InputErrors
+    where
+        F: FnMut(&InputFile<'_>) -> io::Result<()>,
+    {
+        let mut errors = InputErrors::default();
+        for file in self.iter_files() {
+            if let Err(e) = f(&file) {
+                errors.push(file.to_string(), e);
+            }
+        }
+        errors
+    }
+}
+
+/// Failures collected from processing multiple inputs, created by [`Inputs::open_tolerant`] and [`Inputs::for_each_tolerant`].
+#[derive(Debug, Default)]
+pub struct InputErrors {
+    errors: Vec<(String, io::Error)>,
+}
+
+impl InputErrors {
+    fn push(&mut self, name: String, error: io::Error) {
+        self.errors.push((name, error));
+    }
+
+    /// Moves all failures of `other` into `self`.
+    pub fn append(&mut self, other: InputErrors) {
+        self.errors.extend(other.errors);
+    }
+
+    /// Returns `true` if no failure has been collected.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the number of collected failures.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Returns an iterator over the name of each failed input (its path, or `<stdin>`) and its error.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &io::Error)> {
+        self.errors.iter().map(|(name, e)| (name.as_str(), e))
+    }
+
+    /// Prints each failure to standard error as `name: error`.
+    pub fn report(&self) {
+        let mut stderr = io::stderr().lock();
+        for (name, error) in &self.errors {
+            let _ = writeln!(stderr, "{name}: {error}");
+        }
+    }
+
+    /// Converts the failures into skipped paths, so that they can be reported together with
+    /// the paths skipped while expanding directories or glob patterns.
+    ///
+    /// Standard input is reported as `<stdin>`.
+    pub fn into_skipped(self) -> Vec<(PathBuf, SkipReason)> {
+        self.errors
+            .into_iter()
+            .map(|(name, error)| (PathBuf::from(name), SkipReason::Failed(error)))
+            .collect()
+    }
+
+    /// Returns the exit code summarizing the failures: `0` if there are none, `1` otherwise.
+    pub fn exit_code(&self) -> i32 {
+        i32::from(!self.is_empty())
+    }
+}
+
+impl fmt::Display for InputErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.len() {
+            1 => write!(f, "1 input failed"),
+            n => write!(f, "{n} inputs failed"),
+        }
+    }
+}
+
+/// An iterator over the [`Input`]s of an [`Inputs`], created by [`Inputs::iter_files`].
+#[derive(Debug, Clone)]
+pub struct IterFiles<'a>(iter::Map<slice::Iter<'a, Input>, fn(&'a Input) -> InputFile<'a>>);
+
+impl<'a> Iterator for IterFiles<'a> {
+    type Item = InputFile<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for IterFiles<'_> {}
+
+/// An [`Input`] paired with its name for display, yielded by [`Inputs::iter_files`].
+///
+/// The name is the path of the file, or `<stdin>` for standard input.
+/// [`fmt::Display`] prints the name, so it can be used to prefix output lines like `grep`.
+#[derive(Debug, Clone, Copy)]
+pub struct InputFile<'a>(&'a Input);
+
+impl<'a> InputFile<'a> {
+    /// Returns the [`Input`].
+    pub fn input(&self) -> &'a Input {
+        self.0
+    }
+
+    /// Returns the path of the file, or `None` for standard input.
+    pub fn path(&self) -> Option<&'a Path> {
+        self.0.path()
+    }
+
+    /// Locks the input source and returns a [`LockedInput`] instance.
+    pub fn lock(&self) -> LockedInput<'a> {
+        self.0.lock()
+    }
+}
+
+impl fmt::Display for InputFile<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path() {
+            Some(path) => path.display().fmt(f),
+            None => f.write_str("<stdin>"),
+        }
+    }
+}
+
+impl Deref for Inputs {
+    type Target = [Input];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<Input>> for Inputs {
+    fn from(inputs: Vec<Input>) -> Self {
+        Self::new(inputs)
+    }
+}
+
+impl FromIterator<Input> for Inputs {
+    fn from_iter<I: IntoIterator<Item = Input>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Inputs {
+    type Item = Input;
+    type IntoIter = vec::IntoIter<Input>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Inputs {
+    type Item = &'a Input;
+    type IntoIter = slice::Iter<'a, Input>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
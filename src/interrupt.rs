@@ -0,0 +1,142 @@
+use std::{
+    io, process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Mutex, MutexGuard,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{cleanup, error, logging::debug, ErrorKind};
+
+/// How long the interrupt handler waits for the registered actions, which may be blocked by a thread holding an output lock.
+const GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+type Action = Box<dyn FnOnce() -> io::Result<()> + Send>;
+
+// Actions to run when interrupted, with the IDs of their registrations.
+static ACTIONS: Mutex<Vec<(usize, Action)>> = Mutex::new(Vec::new());
+static INSTALLED: Mutex<bool> = Mutex::new(false);
+
+#[track_caller]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Installs a `Ctrl-C` (`SIGINT`) handler that finishes outputs before the process terminates.
+///
+/// When interrupted, the handler flushes the outputs registered with [`Output::flush_on_interrupt`](crate::Output::flush_on_interrupt),
+/// commits the atomic outputs registered with [`AtomicOutput::commit_on_interrupt`](crate::AtomicOutput::commit_on_interrupt),
+/// and rolls back all other uncommitted [`AtomicOutput`](crate::AtomicOutput)s by removing their temporary files,
+/// along with the other temporary files created by this crate. The process then terminates as if it had not handled the signal.
+///
+/// Outputs are locked to be flushed, so the handler waits for other threads to release their locks,
+/// for up to 2 seconds in total before terminating anyway. Lock outputs only while writing to them, rather than for the whole run,
+/// so that interrupted runs keep the last buffered lines.
+///
+/// The registration methods install the handler automatically, so this only needs to be called to roll back atomic outputs
+/// without registering any. Calling this function more than once has no effect.
+///
+/// Returns an error if another `Ctrl-C` handler has been installed, e.g. with the `ctrlc` crate.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::AtomicOutput;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     output: AtomicOutput,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     clap_file::install_interrupt_handler()?;
+///     let args = Args::parse();
+///     for line in io::stdin().lines() {
+///         writeln!(&*args.output, "{}", line?.to_uppercase())?;
+///     }
+///     args.output.commit()?;
+///     Ok(())
+/// }
+/// ```
+pub fn install_interrupt_handler() -> io::Result<()> {
+    let mut installed = lock(&INSTALLED);
+    if *installed {
+        return Ok(());
+    }
+    debug!("installing interrupt handler for finishing outputs");
+    ctrlc::set_handler(on_interrupt).map_err(|e| {
+        error::new(
+            ErrorKind::Other,
+            format!("failed to install the interrupt handler: {e}"),
+        )
+    })?;
+    *installed = true;
+    Ok(())
+}
+
+fn on_interrupt() {
+    debug!("interrupted; finishing outputs");
+    let actions = lock(&ACTIONS).drain(..).collect::<Vec<_>>();
+    let (sender, receiver) = mpsc::channel();
+    // Run the actions on another thread, so that an action blocked on a lock does not keep the process alive.
+    thread::spawn(move || {
+        for (_, action) in actions {
+            if let Err(e) = action() {
+                debug!("failed to finish an output on interrupt: {e}");
+            }
+        }
+        let _ = sender.send(());
+    });
+    if receiver.recv_timeout(GRACE_PERIOD).is_err() {
+        debug!("timed out finishing outputs on interrupt");
+    }
+    cleanup::remove_all("interrupt");
+    terminate();
+}
+
+fn terminate() -> ! {
+    #[cfg(unix)]
+    // SAFETY: `signal` and `raise` have no preconditions, and this runs on the thread of the `ctrlc` crate, not in a signal handler.
+    unsafe {
+        libc::signal(libc::SIGINT, libc::SIG_DFL);
+        libc::raise(libc::SIGINT);
+    }
+    // `STATUS_CONTROL_C_EXIT` on Windows, and the exit status shells use for `SIGINT` elsewhere.
+    #[cfg(windows)]
+    process::exit(0xc000_013a_u32 as i32);
+    #[cfg(not(windows))]
+    process::exit(130);
+}
+
+/// A registration of an action to run when the process is interrupted, which is unregistered when dropped.
+///
+/// This struct is created by [`Output::flush_on_interrupt`](crate::Output::flush_on_interrupt)
+/// and [`AtomicOutput::commit_on_interrupt`](crate::AtomicOutput::commit_on_interrupt).
+/// The output is kept open until this is dropped.
+#[derive(Debug)]
+#[must_use = "the action is unregistered when the guard is dropped"]
+pub struct InterruptGuard(usize);
+
+impl InterruptGuard {
+    pub(crate) fn register(
+        action: impl FnOnce() -> io::Result<()> + Send + 'static,
+    ) -> io::Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        install_interrupt_handler()?;
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        lock(&ACTIONS).push((id, Box::new(action)));
+        Ok(Self(id))
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        lock(&ACTIONS).retain(|(id, _)| *id != self.0);
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader, Read, Seek as _, SeekFrom, Write as _},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{error, hint, logging::debug, ErrorKind};
+
+#[track_caller]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The size of the header of each record: the length of the data and the CRC-32 of the length and the data,
+/// both little-endian `u32`s.
+///
+/// The checksum covers the length, so that a zero-filled tail, which file systems may leave after a power loss,
+/// is not mistaken for empty records.
+const HEADER_LEN: usize = 8;
+
+/// An output that appends records to a journal file, so that the file is never corrupted, even by a crash or power loss.
+///
+/// Each record is framed with its length and CRC-32 checksum. A record that was being written when the process crashed
+/// (a torn tail) is detected by [`JournalReader`] and skipped, and it is truncated when the journal is opened again,
+/// so that new records follow the last complete one. Records are durable once [`JournalOutput::sync`] returns.
+///
+/// When parsed from a string, the value is the path of the journal file, which is created if it does not exist.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io;
+///
+/// use clap_file::{JournalOutput, JournalReader};
+///
+/// fn main() -> io::Result<()> {
+///     let path = std::env::temp_dir().join(format!("clap-file-doc-{}.journal", std::process::id()));
+///     let journal = JournalOutput::open(path.clone())?;
+///     journal.append(b"first")?;
+///     journal.append(b"second")?;
+///     journal.sync()?;
+///
+///     let records = JournalReader::open(&path)?.collect::<io::Result<Vec<_>>>()?;
+///     assert_eq!(records, [b"first".to_vec(), b"second".to_vec()]);
+///     # std::fs::remove_file(&path)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JournalOutput {
+    path: Arc<PathBuf>,
+    file: Arc<Mutex<File>>,
+}
+
+impl JournalOutput {
+    /// Opens the journal file at `path` for appending records, creating it if it does not exist.
+    ///
+    /// The existing records are read to find the end of the last complete record, and a torn tail after it is truncated.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        // Not opened in append mode, which does not allow truncating the file on Windows.
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|e| hint::output_error(e, &path))?;
+        let mut records = JournalReader::new(BufReader::new(&file));
+        for record in records.by_ref() {
+            record?;
+        }
+        if records.is_torn() {
+            debug!(
+                "truncating torn tail of journal `{}` at {} bytes",
+                path.display(),
+                records.valid_len()
+            );
+            file.set_len(records.valid_len())?;
+            file.sync_data()?;
+        }
+        file.seek(SeekFrom::Start(records.valid_len()))?;
+        debug!("opened journal `{}`", path.display());
+        Ok(Self {
+            path: Arc::new(path),
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Returns the path of the journal file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `record` to the journal.
+    ///
+    /// The record is written with a single write, but it may be lost by a crash or power loss until [`JournalOutput::sync`] returns.
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if `record` is 4 GiB or larger.
+    pub fn append(&self, record: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(record.len()).map_err(|_| {
+            error::new(
+                ErrorKind::InvalidInput,
+                "journal records must be smaller than 4 GiB",
+            )
+        })?;
+        let mut frame = Vec::with_capacity(HEADER_LEN + record.len());
+        frame.extend_from_slice(&len.to_le_bytes());
+        frame.extend_from_slice(&checksum(len, record).to_le_bytes());
+        frame.extend_from_slice(record);
+        lock(&self.file).write_all(&frame)
+    }
+
+    /// Synchronizes the appended records to the storage device, so that they survive a crash or power loss.
+    pub fn sync(&self) -> io::Result<()> {
+        debug!("synchronizing journal `{}`", self.path.display());
+        lock(&self.file).sync_data()
+    }
+}
+
+impl FromStr for JournalOutput {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::open(PathBuf::from(s))
+    }
+}
+
+/// An iterator over the records of a journal written by [`JournalOutput`].
+///
+/// Iteration stops at the end of the last complete record. If the journal ends with a torn tail,
+/// such as a partially written record or one whose checksum does not match, the tail is skipped and
+/// [`JournalReader::is_torn`] returns `true`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::{fs::OpenOptions, io::{self, Write as _}};
+///
+/// use clap_file::{JournalOutput, JournalReader};
+///
+/// fn main() -> io::Result<()> {
+///     let path = std::env::temp_dir().join(format!("clap-file-doc-torn-{}.journal", std::process::id()));
+///     let journal = JournalOutput::open(path.clone())?;
+///     journal.append(b"record")?;
+///     journal.sync()?;
+///     drop(journal);
+///
+///     // A power loss may leave a zero-filled tail after the last record.
+///     OpenOptions::new().append(true).open(&path)?.write_all(&[0; 64])?;
+///     let mut reader = JournalReader::open(&path)?;
+///     assert_eq!(reader.by_ref().collect::<io::Result<Vec<_>>>()?, [b"record".to_vec()]);
+///     assert!(reader.is_torn());
+///
+///     // The tail is truncated when the journal is opened again.
+///     drop(JournalOutput::open(path.clone())?);
+///     assert_eq!(std::fs::metadata(&path)?.len(), reader.valid_len());
+///     # std::fs::remove_file(&path)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct JournalReader<R> {
+    reader: R,
+    valid_len: u64,
+    torn: bool,
+    done: bool,
+}
+
+impl JournalReader<BufReader<File>> {
+    /// Opens the journal file at `path` for reading.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R> JournalReader<R>
+where
+    R: Read,
+{
+    /// Creates a new [`JournalReader`] that reads a journal from `reader`, e.g. an [`Input`](crate::Input).
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            valid_len: 0,
+            torn: false,
+            done: false,
+        }
+    }
+
+    /// Returns the length in bytes of the complete records read so far.
+    pub fn valid_len(&self) -> u64 {
+        self.valid_len
+    }
+
+    /// Returns `true` if a torn tail was found after the last complete record.
+    pub fn is_torn(&self) -> bool {
+        self.torn
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut header = [0; HEADER_LEN];
+        let n = read_full(&mut self.reader, &mut header)?;
+        if n < HEADER_LEN {
+            self.torn = n > 0;
+            return Ok(None);
+        }
+        let [l0, l1, l2, l3, c0, c1, c2, c3] = header;
+        let len = u32::from_le_bytes([l0, l1, l2, l3]);
+        let checksum = u32::from_le_bytes([c0, c1, c2, c3]);
+        // Read through `take`, so that a corrupted length does not allocate a huge buffer.
+        let mut record = vec![];
+        (&mut self.reader)
+            .take(u64::from(len))
+            .read_to_end(&mut record)?;
+        if record.len() as u64 != u64::from(len) || self::checksum(len, &record) != checksum {
+            self.torn = true;
+            return Ok(None);
+        }
+        self.valid_len += (HEADER_LEN + record.len()) as u64;
+        Ok(Some(record))
+    }
+}
+
+impl<R> Iterator for JournalReader<R>
+where
+    R: Read,
+{
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let record = self.read_record().transpose();
+        self.done = !matches!(record, Some(Ok(_)));
+        record
+    }
+}
+
+/// Reads until `buf` is full or the end of the reader is reached, returning the number of bytes read.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(m) => n += m,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n)
+}
+
+/// Computes the checksum of a record: the CRC-32 of its little-endian length followed by its data.
+fn checksum(len: u32, data: &[u8]) -> u32 {
+    !crc32_update(crc32_update(!0, &len.to_le_bytes()), data)
+}
+
+/// Updates the CRC-32 (IEEE 802.3) register `crc` with `data`, without the initial and final inversions.
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    data.iter().fold(crc, |crc, &b| {
+        TABLE[usize::from((crc as u8) ^ b)] ^ (crc >> 8)
+    })
+}
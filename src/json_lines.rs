@@ -0,0 +1,79 @@
+use std::{
+    io::{self, BufRead},
+    marker::PhantomData,
+};
+
+use serde::de::DeserializeOwned;
+
+use crate::{error, ErrorKind};
+
+/// An iterator that deserializes each line of a [`BufRead`] instance as a JSON value, as in [JSON Lines](https://jsonlines.org/) (NDJSON).
+///
+/// This struct is created by [`Input::json_lines`](crate::Input::json_lines) and [`LockedInput::json_lines`](crate::LockedInput::json_lines).
+/// Lines consisting only of whitespace are skipped. A line that cannot be deserialized is reported as an error
+/// of kind [`io::ErrorKind::InvalidInput`] with its line number, and iteration continues with the next line.
+#[derive(Debug)]
+pub struct JsonLines<T, B> {
+    reader: B,
+    line_number: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, B> JsonLines<T, B>
+where
+    T: DeserializeOwned,
+    B: BufRead,
+{
+    pub(crate) fn new(reader: B) -> Self {
+        Self {
+            reader,
+            line_number: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of the last line read, starting from 1, or 0 if no line has been read yet.
+    pub fn line_number(&self) -> u64 {
+        self.line_number
+    }
+
+    fn read_value(&mut self) -> io::Result<Option<T>> {
+        let mut line = vec![];
+        loop {
+            line.clear();
+            if self.reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+            if !line.iter().all(u8::is_ascii_whitespace) {
+                break;
+            }
+        }
+        serde_json::from_slice(&line).map(Some).map_err(|e| {
+            // Each line is parsed on its own, so drop the location within it that `serde_json` appends.
+            let message = e.to_string();
+            let suffix = format!(" at line {} column {}", e.line(), e.column());
+            let message = message.strip_suffix(&suffix).unwrap_or(&message);
+            let location = match e.column() {
+                0 => format!("line {}", self.line_number),
+                column => format!("line {}, column {column}", self.line_number),
+            };
+            error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid JSON at {location}: {message}"),
+            )
+        })
+    }
+}
+
+impl<T, B> Iterator for JsonLines<T, B>
+where
+    T: DeserializeOwned,
+    B: BufRead,
+{
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_value().transpose()
+    }
+}
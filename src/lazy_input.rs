@@ -0,0 +1,202 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard, OnceLock},
+};
+
+use crate::{error, logging::debug, ErrorKind, Input, InputBuilder, LockedInput};
+
+#[track_caller]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// An input source that defers opening the file until it is first read.
+///
+/// When parsed from a string, the path is validated (it must exist and must not be a directory),
+/// but the file is not opened until [`LazyInput::lock`] or the first read.
+/// This avoids exhausting file descriptors when many inputs are passed at once.
+///
+/// When parsed from `-`, the input reads from standard input.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, BufRead as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::LazyInput;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Input files. Each file is opened only when it is processed.
+///     inputs: Vec<LazyInput>,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     for input in args.inputs {
+///         for line in input.lock()?.lines() {
+///             println!("{}", line?);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyInput(Arc<LazyInputInner>);
+
+#[derive(Debug)]
+struct LazyInputInner {
+    source: Source,
+    input: OnceLock<Input>,
+    init: Mutex<()>,
+}
+
+#[derive(Debug)]
+enum Source {
+    Stdin,
+    File {
+        path: PathBuf,
+        builder: InputBuilder,
+    },
+}
+
+impl LazyInput {
+    fn new(source: Source) -> Self {
+        Self(Arc::new(LazyInputInner {
+            source,
+            input: OnceLock::new(),
+            init: Mutex::new(()),
+        }))
+    }
+
+    /// Creates a new [`LazyInput`] instance that reads from standard input.
+    pub fn stdin() -> Self {
+        Self::new(Source::Stdin)
+    }
+
+    /// Validates the given path and creates a new [`LazyInput`] instance that opens it on the first read.
+    ///
+    /// Returns an error if the path does not exist or refers to a directory.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        Self::open_with(path, InputBuilder::new())
+    }
+
+    /// Validates the given path and creates a new [`LazyInput`] instance that opens it with `builder` on the first read.
+    ///
+    /// Returns an error if the path does not exist or refers to a directory.
+    pub fn open_with(path: PathBuf, builder: InputBuilder) -> io::Result<Self> {
+        if fs::metadata(&path)?.is_dir() {
+            return Err(error::new(
+                ErrorKind::IsDirectory,
+                format!("input path `{}` is a directory", path.display()),
+            ));
+        }
+        Ok(Self::new(Source::File { path, builder }))
+    }
+
+    /// Returns `true` if the underlying [`Input`] has already been opened.
+    pub fn is_opened(&self) -> bool {
+        self.0.input.get().is_some()
+    }
+
+    /// Returns `true` if this [`LazyInput`] reads from standard input.
+    pub fn is_stdin(&self) -> bool {
+        matches!(self.0.source, Source::Stdin)
+    }
+
+    /// Returns the path of the file this [`LazyInput`] reads from.
+    ///
+    /// Returns `None` if this [`LazyInput`] reads from standard input.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.0.source {
+            Source::Stdin => None,
+            Source::File { path, .. } => Some(path),
+        }
+    }
+
+    /// Returns the underlying [`Input`], opening it if it has not been opened yet.
+    pub fn get(&self) -> io::Result<&Input> {
+        if let Some(input) = self.0.input.get() {
+            return Ok(input);
+        }
+        let _guard = lock(&self.0.init);
+        if let Some(input) = self.0.input.get() {
+            return Ok(input);
+        }
+        let input = match &self.0.source {
+            Source::Stdin => Input::stdin(),
+            Source::File { path, builder } => {
+                debug!("opening deferred input file `{}`", path.display());
+                builder.open(path.clone())?
+            }
+        };
+        Ok(self.0.input.get_or_init(|| input))
+    }
+
+    /// Opens the underlying [`Input`] if needed, locks it, and returns a [`LockedInput`] instance.
+    pub fn lock(&self) -> io::Result<LockedInput<'_>> {
+        Ok(self.get()?.lock())
+    }
+}
+
+impl FromStr for LazyInput {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            // Standard input is claimed while parsing, so the claim is held from the start
+            let lazy = Self::stdin();
+            let _ = lazy.0.input.set(Input::claimed_stdin()?);
+            return Ok(lazy);
+        }
+        Self::open(PathBuf::from(s))
+    }
+}
+
+impl Read for LazyInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        (&*self).read_vectored(bufs)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        (&*self).read_to_end(buf)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        (&*self).read_to_string(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        (&*self).read_exact(buf)
+    }
+}
+
+impl Read for &LazyInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.get()?.read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.get()?.read_vectored(bufs)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.get()?.read_to_end(buf)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.get()?.read_to_string(buf)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.get()?.read_exact(buf)
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard, OnceLock},
+};
+
+use crate::{logging::debug, LockedOutput, Output, OutputBuilder};
+
+#[track_caller]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// An output sink that defers creating the file until the first write.
+///
+/// With [`Output`], the destination file is created (and truncated) as soon as the argument is parsed,
+/// even if the program fails before producing any output. [`LazyOutput`] only creates the file
+/// when data is first written to it, so that an existing file is not clobbered by a failed run.
+///
+/// When parsed from a string, `-` writes to standard output and `tty:` writes to the terminal.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::LazyOutput;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Output file. If `-` is specified, writes to standard output.
+///     output: LazyOutput,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     // the file is created here
+///     writeln!(args.output.lock()?, "Hello, world!")?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyOutput(Arc<LazyOutputInner>);
+
+#[derive(Debug)]
+struct LazyOutputInner {
+    target: Target,
+    output: OnceLock<Output>,
+    init: Mutex<()>,
+}
+
+#[derive(Debug)]
+enum Target {
+    Stdout,
+    Tty,
+    File {
+        path: PathBuf,
+        builder: OutputBuilder,
+    },
+}
+
+impl LazyOutput {
+    fn new(target: Target) -> Self {
+        Self(Arc::new(LazyOutputInner {
+            target,
+            output: OnceLock::new(),
+            init: Mutex::new(()),
+        }))
+    }
+
+    /// Creates a new [`LazyOutput`] instance that writes to standard output.
+    pub fn stdout() -> Self {
+        Self::new(Target::Stdout)
+    }
+
+    /// Creates a new [`LazyOutput`] instance that opens the controlling terminal on the first write.
+    pub fn tty() -> Self {
+        Self::new(Target::Tty)
+    }
+
+    /// Creates a new [`LazyOutput`] instance that creates a file at the given path on the first write.
+    pub fn create(path: PathBuf) -> Self {
+        Self::create_with(path, OutputBuilder::new())
+    }
+
+    /// Creates a new [`LazyOutput`] instance that opens a file at the given path with `builder` on the first write.
+    pub fn create_with(path: PathBuf, builder: OutputBuilder) -> Self {
+        Self::new(Target::File { path, builder })
+    }
+
+    /// Returns `true` if the underlying [`Output`] has already been opened.
+    pub fn is_opened(&self) -> bool {
+        self.0.output.get().is_some()
+    }
+
+    /// Returns `true` if this [`LazyOutput`] writes to standard output.
+    pub fn is_stdout(&self) -> bool {
+        matches!(self.0.target, Target::Stdout)
+    }
+
+    /// Returns the path of the file this [`LazyOutput`] writes to.
+    ///
+    /// Returns `None` if this [`LazyOutput`] writes to standard output or the terminal.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.0.target {
+            Target::Stdout | Target::Tty => None,
+            Target::File { path, .. } => Some(path),
+        }
+    }
+
+    /// Returns the underlying [`Output`], opening it if it has not been opened yet.
+    pub fn get(&self) -> io::Result<&Output> {
+        if let Some(output) = self.0.output.get() {
+            return Ok(output);
+        }
+        let _guard = lock(&self.0.init);
+        if let Some(output) = self.0.output.get() {
+            return Ok(output);
+        }
+        let output = match &self.0.target {
+            Target::Stdout => Output::stdout(),
+            Target::Tty => Output::tty()?,
+            Target::File { path, builder } => {
+                debug!("opening deferred output file `{}`", path.display());
+                builder.open(path.clone())?
+            }
+        };
+        Ok(self.0.output.get_or_init(|| output))
+    }
+
+    /// Opens the underlying [`Output`] if needed, locks it, and returns a [`LockedOutput`] instance.
+    pub fn lock(&self) -> io::Result<LockedOutput<'_>> {
+        Ok(self.get()?.lock())
+    }
+}
+
+impl FromStr for LazyOutput {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            return Ok(Self::stdout());
+        }
+        if s == "tty:" {
+            return Ok(Self::tty());
+        }
+        Ok(Self::create(PathBuf::from(s)))
+    }
+}
+
+impl Write for LazyOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        (&*self).write_vectored(bufs)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        (&*self).write_all(buf)
+    }
+}
+
+impl Write for &LazyOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.get()?.write(buf)
+    }
+
+    // Flushing an output that has not been written to does not create the file.
+    fn flush(&mut self) -> io::Result<()> {
+        match self.0.output.get() {
+            Some(mut output) => output.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.get()?.write_vectored(bufs)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.get()?.write_all(buf)
+    }
+}
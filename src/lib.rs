@@ -9,6 +9,29 @@
 //! clap-file = "0.2.0"
 //! ```
 //!
+//! # Feature flags
+//!
+//! No features are enabled by default, so the default build only depends on `clap`.
+//! The `full` feature enables all features except `test-util`.
+//!
+//! * `bzip2`: Decompresses input files with the `.bz2` extension transparently, and enables [`Input::decompress`] for [`Compression::Bzip2`].
+//! * `cap-std`: Enables opening files relative to a [`cap_std::fs::Dir`](https://docs.rs/cap-std/latest/cap_std/fs/struct.Dir.html), for capability-based sandboxing.
+//! * `csv`: Enables `Input::csv_reader` and `Output::csv_writer`, which read and write CSV data with the [`csv`](https://docs.rs/csv) crate.
+//! * `encoding`: Enables `Input::transcode` and the `Encoding` type, which transcode input files in other character encodings, such as UTF-16 and Shift_JIS, into UTF-8.
+//! * `glob`: Enables `GlobInput`, which expands glob patterns in input arguments at parse time.
+//! * `gzip`: Decompresses input files and compresses output files with the `.gz` extension transparently, and enables [`Input::decompress`] for [`Compression::Gzip`].
+//! * `lock`: Enables `InputBuilder::exclusive`, which prevents input files from being modified while they are read.
+//! * `log`: Emits debug-level records through the [`log`](https://docs.rs/log) crate when inputs/outputs are opened, locked, flushed, and closed.
+//! * `mmap`: Enables `Input::mmap`, which gives zero-copy access to the contents of input files through memory mapping.
+//! * `notify`: Enables `Input::watch`, which reruns a callback whenever the input file changes.
+//! * `serde`: Implements `serde::Serialize` for [`IoDescription`], enables `Input::json_lines` and `Input::read_json`, which deserialize JSON Lines (NDJSON) and JSON inputs, and enables `Output::write_json`, which serializes values to outputs.
+//! * `signal`: Enables `Output::flush_on_interrupt` and `AtomicOutput::commit_on_interrupt`, which finish outputs when the process is interrupted by `Ctrl-C`.
+//! * `test-util`: Enables the `testing` module, which provides utilities for testing programs that use this crate.
+//! * `toml`: Enables `Input::read_toml`, which deserializes TOML inputs. Implies `serde`.
+//! * `xz`: Decompresses input files with the `.xz` extension transparently, and enables [`Input::decompress`] for [`Compression::Xz`].
+//! * `yaml`: Enables `Input::read_yaml` and `Output::write_yaml`, which deserialize and serialize YAML. Implies `serde`.
+//! * `zstd`: Decompresses input files and compresses output files with the `.zst` extension transparently, and enables [`Compression::Zstd`].
+//!
 //! # Examples
 //!
 //! Example usage of [`Input`] ans [`Output`] types:
@@ -38,11 +61,103 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! The [`examples`](https://github.com/gifnksm/clap-file/tree/main/examples) directory contains complete command-line tools built with this crate,
+//! such as `cat`, `tee`, and `grep-lite`.
 
 #![doc(html_root_url = "https://docs.rs/clap-file/0.2.0")]
 #![warn(missing_docs)]
 
-pub use self::{input::*, output::*};
+#[cfg(feature = "csv")]
+pub use self::csv_io::*;
+#[cfg(feature = "encoding")]
+pub use self::encoding::*;
+#[cfg(feature = "glob")]
+pub use self::glob_input::*;
+#[cfg(feature = "signal")]
+pub use self::interrupt::*;
+#[cfg(feature = "serde")]
+pub use self::json_lines::*;
+#[cfg(feature = "mmap")]
+pub use self::mmap::*;
+pub use self::{
+    atomic_output::*, binary::*, bom::*, cleanup::*, closed_stdio::*, compression::*, config::*,
+    describe::*, dir_input::*, dump::*, eol::*, error::*, escape::*, ext::*, filter::*, helpers::*,
+    in_place::*, input::*, input_range::*, inputs::*, journal::*, lazy_input::*, lazy_output::*,
+    lines::*, memory::*, multi_input::*, order::*, output::*, output_dir::*, output_path::*,
+    path_arg::*, progress::*, recording::*, resume::*, skip::*, snapshot::*, spooled_input::*,
+    tee::*, value_parser::*, warning::*,
+};
+
+pub mod paths;
+pub mod prelude;
+#[cfg(feature = "test-util")]
+pub mod testing;
 
+mod atomic_output;
+mod binary;
+mod bom;
+mod buf_size;
+mod cleanup;
+mod closed_stdio;
+mod compression;
+mod config;
+#[cfg(feature = "csv")]
+mod csv_io;
+mod describe;
+mod dir_input;
+mod dump;
+#[cfg(feature = "encoding")]
+mod encoding;
+mod eol;
+mod error;
+mod escape;
+mod ext;
+#[cfg(feature = "lock")]
+mod file_lock;
+mod filter;
+#[cfg(feature = "glob")]
+mod glob_input;
+mod helpers;
+mod hint;
+mod in_place;
+mod inherited_fd;
 mod input;
+mod input_range;
+mod inputs;
+#[cfg(feature = "signal")]
+mod interrupt;
+mod journal;
+#[cfg(feature = "serde")]
+mod json_lines;
+mod lazy_input;
+mod lazy_output;
+mod lines;
+mod logging;
+mod memory;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod multi_input;
+mod order;
 mod output;
+mod output_dir;
+mod output_path;
+#[cfg(feature = "gzip")]
+mod parallel_gzip;
+mod path_arg;
+mod progress;
+mod recording;
+mod resume;
+#[cfg(feature = "zstd")]
+mod seekable_zstd;
+mod skip;
+mod snapshot;
+mod spooled_input;
+mod stdin_claim;
+mod tee;
+mod temp;
+mod value_parser;
+mod warning;
+#[cfg(feature = "notify")]
+mod watch;
+mod writer;
@@ -0,0 +1,332 @@
+use std::{
+    fmt,
+    io::{self, BufRead},
+    str::FromStr,
+};
+
+use crate::{error, ErrorKind};
+
+/// An iterator over batches of lines of a [`BufRead`] instance.
+///
+/// This struct is created by [`LockedInput::line_batches`](crate::LockedInput::line_batches).
+#[derive(Debug)]
+pub struct LineBatches<B> {
+    lines: io::Lines<B>,
+    size: usize,
+}
+
+impl<B> LineBatches<B>
+where
+    B: BufRead,
+{
+    pub(crate) fn new(reader: B, size: usize) -> Self {
+        assert!(size != 0, "batch size must be non-zero");
+        Self {
+            lines: reader.lines(),
+            size,
+        }
+    }
+}
+
+impl<B> Iterator for LineBatches<B>
+where
+    B: BufRead,
+{
+    type Item = io::Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut batch = Vec::with_capacity(self.size);
+        for line in self.lines.by_ref() {
+            match line {
+                Ok(line) => batch.push(line),
+                Err(e) => return Some(Err(e)),
+            }
+            if batch.len() == self.size {
+                break;
+            }
+        }
+        (!batch.is_empty()).then_some(Ok(batch))
+    }
+}
+
+/// A line terminator.
+///
+/// [`LineEnding`] implements [`FromStr`], parsing `lf`, `crlf`, `cr`, and `native` (case-insensitively),
+/// so it can be used as the type of an argument like `--eol`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LineEnding {
+    /// `\n`, used on Unix-like systems.
+    Lf,
+    /// `\r\n`, used on Windows.
+    CrLf,
+    /// A lone `\r`, used on classic Mac OS.
+    Cr,
+}
+
+impl LineEnding {
+    /// Returns the line terminator of the current platform: [`LineEnding::CrLf`] on Windows, and [`LineEnding::Lf`] elsewhere.
+    pub fn native() -> Self {
+        if cfg!(windows) {
+            Self::CrLf
+        } else {
+            Self::Lf
+        }
+    }
+
+    /// Returns the line terminator as a string.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::Cr => "\r",
+        }
+    }
+
+    /// Returns the name of the line terminator, e.g. `crlf`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Lf => "lf",
+            Self::CrLf => "crlf",
+            Self::Cr => "cr",
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for LineEnding {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "lf" => Ok(Self::Lf),
+            "crlf" => Ok(Self::CrLf),
+            "cr" => Ok(Self::Cr),
+            "native" => Ok(Self::native()),
+            _ => Err(error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid line ending `{s}`; expected `lf`, `crlf`, `cr`, or `native`"),
+            )),
+        }
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`] instance that accepts `\n`, `\r\n`, and lone `\r` as line terminators.
+///
+/// Each line is returned without its terminator, together with the terminator that ended it,
+/// so that tools can write the original line endings back.
+/// The terminator is `None` for a last line that is not terminated.
+///
+/// This struct is created by [`LockedInput::universal_newlines`](crate::LockedInput::universal_newlines).
+#[derive(Debug)]
+pub struct UniversalLines<B> {
+    reader: B,
+}
+
+impl<B> UniversalLines<B>
+where
+    B: BufRead,
+{
+    pub(crate) fn new(reader: B) -> Self {
+        Self { reader }
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<(String, Option<LineEnding>)>> {
+        let mut line = vec![];
+        let ending = loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                if line.is_empty() {
+                    return Ok(None);
+                }
+                break None;
+            }
+            match buf.iter().position(|&b| b == b'\n' || b == b'\r') {
+                Some(i) => {
+                    let is_cr = buf[i] == b'\r';
+                    line.extend_from_slice(&buf[..i]);
+                    self.reader.consume(i + 1);
+                    if !is_cr {
+                        break Some(LineEnding::Lf);
+                    }
+                    if self.reader.fill_buf()?.first() == Some(&b'\n') {
+                        self.reader.consume(1);
+                        break Some(LineEnding::CrLf);
+                    }
+                    break Some(LineEnding::Cr);
+                }
+                None => {
+                    let n = buf.len();
+                    line.extend_from_slice(buf);
+                    self.reader.consume(n);
+                }
+            }
+        };
+        let line = String::from_utf8(line).map_err(|e| {
+            error::new(
+                ErrorKind::InvalidData,
+                format!("line is not valid UTF-8: {}", e.utf8_error()),
+            )
+        })?;
+        Ok(Some((line, ending)))
+    }
+}
+
+impl<B> Iterator for UniversalLines<B>
+where
+    B: BufRead,
+{
+    type Item = io::Result<(String, Option<LineEnding>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_line().transpose()
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`] instance that replaces invalid UTF-8 sequences with U+FFFD REPLACEMENT CHARACTER.
+///
+/// Lines are split in the same way as [`BufRead::lines`], but invalid UTF-8 does not stop the iteration,
+/// which is what grep-like tools want for real-world files.
+///
+/// This struct is created by [`Input::lines_lossy`](crate::Input::lines_lossy) and [`LockedInput::lines_lossy`](crate::LockedInput::lines_lossy).
+/// Use [`LossyLines::flagged`] to tell which lines had invalid sequences.
+#[derive(Debug)]
+pub struct LossyLines<B> {
+    reader: B,
+}
+
+impl<B> LossyLines<B>
+where
+    B: BufRead,
+{
+    pub(crate) fn new(reader: B) -> Self {
+        Self { reader }
+    }
+
+    /// Converts this iterator into one that also returns whether invalid sequences were replaced in each line.
+    ///
+    /// This lets tools that process dirty real-world logs count or report the affected lines.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap_file::Input;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::stdin();
+    ///     for (i, line) in input.lines_lossy().flagged().enumerate() {
+    ///         let (line, replaced) = line?;
+    ///         if replaced {
+    ///             eprintln!("warning: line {} is not valid UTF-8", i + 1);
+    ///         }
+    ///         println!("{line}");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn flagged(self) -> FlaggedLossyLines<B> {
+        FlaggedLossyLines(self)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<(String, bool)>> {
+        let mut line = vec![];
+        if self.reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(match String::from_utf8(line) {
+            Ok(line) => (line, false),
+            Err(e) => (String::from_utf8_lossy(e.as_bytes()).into_owned(), true),
+        }))
+    }
+}
+
+impl<B> Iterator for LossyLines<B>
+where
+    B: BufRead,
+{
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_line()
+            .map(|line| line.map(|(line, _)| line))
+            .transpose()
+    }
+}
+
+/// An iterator over the lines of a [`BufRead`] instance that replaces invalid UTF-8 sequences,
+/// and returns whether any sequence was replaced in each line.
+///
+/// This struct is created by [`LossyLines::flagged`].
+#[derive(Debug)]
+pub struct FlaggedLossyLines<B>(LossyLines<B>);
+
+impl<B> Iterator for FlaggedLossyLines<B>
+where
+    B: BufRead,
+{
+    type Item = io::Result<(String, bool)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.read_line().transpose()
+    }
+}
+
+/// An iterator over the records of a [`BufRead`] instance, separated by a delimiter byte.
+///
+/// Each record is returned without its delimiter. A last record that is not terminated is returned as well,
+/// but no empty record is returned after a trailing delimiter.
+///
+/// This struct is created by [`Input::records`](crate::Input::records) and [`LockedInput::split_records`](crate::LockedInput::split_records).
+#[derive(Debug)]
+pub struct Records<B> {
+    reader: B,
+    delim: u8,
+}
+
+impl<B> Records<B>
+where
+    B: BufRead,
+{
+    pub(crate) fn new(reader: B, delim: u8) -> Self {
+        Self { reader, delim }
+    }
+
+    /// Returns the byte that separates records.
+    pub fn delimiter(&self) -> u8 {
+        self.delim
+    }
+}
+
+impl<B> Iterator for Records<B>
+where
+    B: BufRead,
+{
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = vec![];
+        match self.reader.read_until(self.delim, &mut record) {
+            Ok(0) => None,
+            Ok(_) => {
+                if record.last() == Some(&self.delim) {
+                    record.pop();
+                }
+                Some(Ok(record))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
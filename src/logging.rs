@@ -0,0 +1,16 @@
+// Emits a debug-level record through the `log` crate when the `log` feature is enabled.
+//
+// When the feature is disabled, the arguments are still type-checked (so that variables used only
+// for logging don't trigger `unused` warnings), but they are never evaluated.
+macro_rules! debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::debug!(target: "clap_file", $($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+
+pub(crate) use debug;
@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::logging::debug;
+
+// `usize::MAX` means that no budget is set.
+static BUDGET: AtomicUsize = AtomicUsize::new(usize::MAX);
+static USED: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the process-wide budget for the memory this crate uses to buffer whole inputs, replacing the previous one.
+///
+/// Once the data buffered by all [`SpooledInput`](crate::SpooledInput)s and `Input::mmap` fallbacks would exceed `budget` bytes,
+/// further data is buffered in temporary files instead, so that tools stay within the memory of small machines.
+/// The memory limit of each [`SpooledInput`](crate::SpooledInput) still applies. `None` removes the budget, which is the default.
+///
+/// Data that is already buffered in memory is not moved when the budget is lowered.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Read as _};
+///
+/// use clap_file::{Input, SpooledInput};
+///
+/// fn main() -> io::Result<()> {
+///     clap_file::set_memory_budget(Some(16));
+///     let mut input = SpooledInput::new(Input::stdin());
+///     let mut data = vec![];
+///     input.read_to_end(&mut data)?;
+///     Ok(())
+/// }
+/// ```
+pub fn set_memory_budget(budget: Option<usize>) {
+    debug!("setting memory budget to {budget:?}");
+    BUDGET.store(budget.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// Returns the number of bytes currently buffered in memory under the budget set with [`set_memory_budget`].
+pub fn memory_usage() -> usize {
+    USED.load(Ordering::Relaxed)
+}
+
+/// Memory reserved against the budget set with [`set_memory_budget`], which is released when dropped.
+#[derive(Debug, Default)]
+pub(crate) struct MemoryCharge(AtomicUsize);
+
+impl MemoryCharge {
+    /// Reserves `n` more bytes, returning `false` without reserving anything if that would exceed the budget.
+    pub(crate) fn try_grow(&self, n: usize) -> bool {
+        let budget = BUDGET.load(Ordering::Relaxed);
+        let reserved = USED.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+            used.checked_add(n).filter(|&used| used <= budget)
+        });
+        if reserved.is_ok() {
+            self.0.fetch_add(n, Ordering::Relaxed);
+        }
+        reserved.is_ok()
+    }
+}
+
+impl Drop for MemoryCharge {
+    fn drop(&mut self) {
+        USED.fetch_sub(*self.0.get_mut(), Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, Write as _},
+    ops::Deref,
+};
+
+use memmap2::Mmap;
+
+use crate::{logging::debug, memory::MemoryCharge, temp::TempFile};
+
+/// The contents of an input, returned by [`Input::mmap`](crate::Input::mmap).
+///
+/// Dereferences to the bytes of the input. Regular files are memory-mapped; other sources are read into memory,
+/// or into a memory-mapped temporary file once they exceed the budget set with [`set_memory_budget`](crate::set_memory_budget).
+#[derive(Debug)]
+pub struct InputBytes(InputBytesInner);
+
+#[derive(Debug)]
+enum InputBytesInner {
+    Mapped(Mmap),
+    Read {
+        bytes: Vec<u8>,
+        _charge: MemoryCharge,
+    },
+    // Declared in this order so that the map is dropped before the file is removed.
+    Spilled {
+        map: Mmap,
+        _temp: TempFile,
+    },
+}
+
+impl InputBytes {
+    /// Memory-maps the whole file.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified while the returned value is alive.
+    pub(crate) unsafe fn map(file: &File) -> io::Result<Self> {
+        // Mapping an empty file fails on some platforms.
+        if file.metadata()?.len() == 0 {
+            return Ok(Self(InputBytesInner::Read {
+                bytes: vec![],
+                _charge: MemoryCharge::default(),
+            }));
+        }
+        // SAFETY: The map is read-only, and the caller guarantees that the file is not modified while it is mapped.
+        let map = unsafe { Mmap::map(file)? };
+        Ok(Self(InputBytesInner::Mapped(map)))
+    }
+
+    /// Reads `reader` to the end, spilling to a temporary file if the data exceeds the memory budget.
+    pub(crate) fn read(mut reader: impl BufRead) -> io::Result<Self> {
+        let mut bytes = vec![];
+        let charge = MemoryCharge::default();
+        loop {
+            let buf = reader.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(Self(InputBytesInner::Read {
+                    bytes,
+                    _charge: charge,
+                }));
+            }
+            if !charge.try_grow(buf.len()) {
+                break;
+            }
+            bytes.extend_from_slice(buf);
+            let n = buf.len();
+            reader.consume(n);
+        }
+        let temp = TempFile::new()?;
+        debug!(
+            "spilling input exceeding the memory budget to `{}`",
+            temp.path().display()
+        );
+        let mut file = temp.file();
+        file.write_all(&bytes)?;
+        drop((bytes, charge));
+        io::copy(&mut reader, &mut file)?;
+        // SAFETY: The map is read-only, and the temporary file is not modified while it is mapped.
+        let map = unsafe { Mmap::map(temp.file())? };
+        Ok(Self(InputBytesInner::Spilled { map, _temp: temp }))
+    }
+
+    /// Returns `true` if the contents are memory-mapped rather than read into memory.
+    pub fn is_mapped(&self) -> bool {
+        matches!(
+            self.0,
+            InputBytesInner::Mapped(_) | InputBytesInner::Spilled { .. }
+        )
+    }
+}
+
+impl Deref for InputBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            InputBytesInner::Mapped(map) => map,
+            InputBytesInner::Read { bytes, .. } => bytes,
+            InputBytesInner::Spilled { map, .. } => map,
+        }
+    }
+}
+
+impl AsRef<[u8]> for InputBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
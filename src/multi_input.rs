@@ -0,0 +1,222 @@
+use std::{
+    io::{self, BufRead, Read},
+    path::Path,
+};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Args, Command, FromArgMatches, ValueHint};
+
+use crate::{Input, Inputs, LockedInput};
+
+const ID: &str = "inputs";
+
+/// Multiple input sources that are read one after another, like `cat`.
+///
+/// This type implements [`clap::Args`], so it can be used with `#[command(flatten)]`.
+/// It adds a positional argument that accepts any number of paths and/or `-`;
+/// if no input is specified, standard input is used.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::{self, Read as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::MultiInput;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     #[command(flatten)]
+///     inputs: MultiInput,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse_from(["prog", "Cargo.toml", "README.md"]);
+///     assert_eq!(args.inputs.len(), 2);
+///
+///     // reads all inputs as a single stream
+///     let mut text = String::new();
+///     args.inputs.lock().read_to_string(&mut text)?;
+///
+///     // or, reads each input separately
+///     for (path, reader) in args.inputs.sources() {
+///         println!("{:?}: {} bytes", path, reader.bytes().count());
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiInput {
+    inputs: Vec<Input>,
+}
+
+impl MultiInput {
+    /// Creates a new [`MultiInput`] from the given list of [`Input`]s.
+    ///
+    /// If `inputs` is empty, standard input is used.
+    pub fn new(mut inputs: Vec<Input>) -> Self {
+        if inputs.is_empty() {
+            inputs.push(Input::stdin());
+        }
+        Self { inputs }
+    }
+
+    /// Returns the number of input sources.
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Always returns `false`, since a [`MultiInput`] has at least one input source.
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Returns the input sources.
+    pub fn inputs(&self) -> &[Input] {
+        &self.inputs
+    }
+
+    /// Converts this [`MultiInput`] into a `Vec<Input>`.
+    pub fn into_vec(self) -> Vec<Input> {
+        self.inputs
+    }
+
+    /// Returns an iterator that locks each input source in turn and yields it with its path.
+    ///
+    /// The path is `None` for standard input.
+    pub fn sources(&self) -> impl Iterator<Item = (Option<&Path>, LockedInput<'_>)> {
+        self.inputs.iter().map(|input| (input.path(), input.lock()))
+    }
+
+    /// Locks all input sources and returns a [`LockedMultiInput`] instance that reads them in order.
+    pub fn lock(&self) -> LockedMultiInput<'_> {
+        LockedMultiInput {
+            inputs: self.inputs.iter().map(Input::lock).collect(),
+            current: 0,
+        }
+    }
+}
+
+impl Default for MultiInput {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl From<Vec<Input>> for MultiInput {
+    fn from(inputs: Vec<Input>) -> Self {
+        Self::new(inputs)
+    }
+}
+
+impl From<Inputs> for MultiInput {
+    fn from(inputs: Inputs) -> Self {
+        Self::new(inputs.into_vec())
+    }
+}
+
+impl FromIterator<Input> for MultiInput {
+    fn from_iter<T: IntoIterator<Item = Input>>(iter: T) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl FromArgMatches for MultiInput {
+    fn from_arg_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+        Self::from_arg_matches_mut(&mut matches.clone())
+    }
+
+    fn from_arg_matches_mut(matches: &mut ArgMatches) -> Result<Self, clap::Error> {
+        let inputs = matches
+            .remove_many::<Input>(ID)
+            .map(Iterator::collect)
+            .unwrap_or_default();
+        Ok(Self::new(inputs))
+    }
+
+    fn update_from_arg_matches(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        self.update_from_arg_matches_mut(&mut matches.clone())
+    }
+
+    fn update_from_arg_matches_mut(&mut self, matches: &mut ArgMatches) -> Result<(), clap::Error> {
+        if let Some(inputs) = matches.remove_many::<Input>(ID) {
+            *self = Self::new(inputs.collect());
+        }
+        Ok(())
+    }
+}
+
+impl Args for MultiInput {
+    fn augment_args(cmd: Command) -> Command {
+        cmd.arg(
+            Arg::new(ID)
+                .value_name("INPUT")
+                .num_args(0..)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(Input))
+                .value_hint(ValueHint::FilePath)
+                .help(
+                    "Input files. If not provided or `-` is specified, reads from standard input",
+                ),
+        )
+    }
+
+    fn augment_args_for_update(cmd: Command) -> Command {
+        Self::augment_args(cmd)
+    }
+}
+
+impl Read for MultiInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.lock().read(buf)
+    }
+}
+
+/// A locked [`MultiInput`] that reads all input sources in order.
+#[derive(Debug)]
+pub struct LockedMultiInput<'a> {
+    inputs: Vec<LockedInput<'a>>,
+    current: usize,
+}
+
+impl LockedMultiInput<'_> {
+    /// Returns the path of the input source currently being read.
+    ///
+    /// Returns `None` if the current source is standard input or all sources have been read.
+    pub fn current_path(&self) -> Option<&Path> {
+        self.inputs.get(self.current).and_then(LockedInput::path)
+    }
+}
+
+impl Read for LockedMultiInput<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while let Some(input) = self.inputs.get_mut(self.current) {
+            let n = input.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            self.current += 1;
+        }
+        Ok(0)
+    }
+}
+
+impl BufRead for LockedMultiInput<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while let Some(input) = self.inputs.get_mut(self.current) {
+            if !input.fill_buf()?.is_empty() {
+                break;
+            }
+            self.current += 1;
+        }
+        match self.inputs.get_mut(self.current) {
+            Some(input) => input.fill_buf(),
+            None => Ok(&[]),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Some(input) = self.inputs.get_mut(self.current) {
+            input.consume(amt);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::{fs, path::Path};
+
+/// Specifies the order of inputs expanded from a directory or glob pattern.
+///
+/// Ties are broken by the lexicographic order of the paths, so the resulting order is reproducible
+/// across platforms and file systems (except for [`InputOrder::None`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum InputOrder {
+    /// Sorts by path, in lexicographic order.
+    #[default]
+    Lexicographic,
+    /// Sorts by modification time, oldest first.
+    Modified,
+    /// Sorts by file size, smallest first.
+    Size,
+    /// Keeps the order in which the file system or the pattern expansion returned the paths.
+    None,
+}
+
+impl InputOrder {
+    /// Sorts `items` by the paths returned by `path`.
+    pub(crate) fn sort_by_path<T>(self, items: &mut [T], path: impl Fn(&T) -> &Path) {
+        if self == Self::None {
+            return;
+        }
+        items.sort_by(|a, b| path(a).cmp(path(b)));
+        match self {
+            Self::Lexicographic | Self::None => {}
+            Self::Modified => {
+                items.sort_by_cached_key(|item| {
+                    fs::metadata(path(item)).and_then(|m| m.modified()).ok()
+                });
+            }
+            Self::Size => {
+                items.sort_by_cached_key(|item| fs::metadata(path(item)).ok().map(|m| m.len()))
+            }
+        }
+    }
+}
@@ -1,17 +1,1793 @@
 use std::{
-    fs::File,
-    io::{self, LineWriter, Write},
+    ffi::OsStr,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, IsTerminal as _, Seek as _, Write},
+    num::NonZeroUsize,
+    ops::Deref,
     path::{Path, PathBuf},
     str::FromStr,
     sync::{Arc, Mutex, MutexGuard},
 };
 
+use crate::{
+    atomic_output, buf_size,
+    compression::{Encoder, EncoderOptions},
+    error, hint, inherited_fd,
+    logging::debug,
+    paths, warning,
+    writer::{FileWriter, PendingRename},
+    Compression, CompressionLevel, DebugDump, EolWriter, ErrorKind, Input, IoDescription, IoKind,
+    LineEnding,
+};
+
+#[cfg(windows)]
+const TTY_PATH: &str = "CONOUT$";
+#[cfg(not(windows))]
+const TTY_PATH: &str = "/dev/tty";
+
 #[track_caller]
-fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
     mutex.lock().unwrap_or_else(|e| e.into_inner())
 }
 
-/// Represents an output sink, which can be either standard output or a file.
+fn describe(is_tty: bool, path: Option<&Path>) -> String {
+    match path {
+        Some(path) => format!("output file `{}`", path.display()),
+        None if is_tty => "terminal".to_owned(),
+        None => "standard output".to_owned(),
+    }
+}
+
+/// Represents an output sink, which can be either standard output or a file.
+///
+/// [`Write`] is implemented for both `Output` and `&Output`, so an output can be written to from closures that only capture it by shared reference.
+///
+/// clap parses this type with [`OutputValueParser`](crate::OutputValueParser). clap only infers value hints for `PathBuf` arguments,
+/// so add `#[arg(value_hint = clap::ValueHint::FilePath)]` or pass the command through [`value_hints`](crate::value_hints)
+/// to enable filename completion in generated shell completions.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::Output;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// output file. If not provided, reads from standard output.
+///     output: Output,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     let mut output = args.output.lock();
+///     writeln!(&mut output, "Hello, world!")?;
+///     Ok(())
+/// }
+/// ```
+
+// This struct should not implement `Clone`, but clap-derive requires Clone [1].
+// So, I added `Clone` to the struct and wrap `File` with `Arc` and `Mutex`.
+// This is not the best way to handle this, but it works for now.
+//
+// [1]: https://github.com/clap-rs/clap/issues/4286
+#[derive(Debug, Clone)]
+pub struct Output(OutputInner);
+
+#[derive(Debug, Clone)]
+enum OutputInner {
+    Stdout,
+    #[cfg(feature = "test-util")]
+    InjectedStdout {
+        writer: Arc<Mutex<Vec<u8>>>,
+    },
+    RawStdout {
+        writer: Arc<Mutex<FileWriter>>,
+    },
+    Tty {
+        writer: Arc<Mutex<FileWriter>>,
+    },
+    File {
+        path: Arc<PathBuf>,
+        writer: Arc<Mutex<FileWriter>>,
+    },
+    Encoded {
+        sink: Box<Output>,
+        compression: Compression,
+        writer: Arc<Mutex<Encoder>>,
+    },
+}
+
+impl Output {
+    /// Creates a new [`Output`] instance that writes to standard output.
+    ///
+    /// When the `test-util` feature is enabled, this writes to the buffer injected by
+    /// `testing::with_stdio` instead, if any.
+    pub fn stdout() -> Self {
+        #[cfg(feature = "test-util")]
+        if let Some(writer) = crate::testing::injected_stdout() {
+            return Self(OutputInner::InjectedStdout { writer });
+        }
+        Self(OutputInner::Stdout)
+    }
+
+    /// Creates a new [`StdoutOnly`] instance that writes to standard output.
+    pub fn stdout_only() -> StdoutOnly {
+        StdoutOnly::new()
+    }
+
+    /// Opens the controlling terminal and creates a new [`Output`] instance that writes to it.
+    ///
+    /// This writes to the terminal even when standard output is redirected,
+    /// so that progress or other UI messages can be separated from data output.
+    /// The terminal is `/dev/tty` on Unix and `CONOUT$` on Windows.
+    ///
+    /// When parsed from a string, `tty:` creates an [`Output`] that writes to the terminal.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap_file::Output;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let tty = Output::tty()?;
+    ///     writeln!(tty.lock(), "processing...")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn tty() -> io::Result<Self> {
+        let file = OpenOptions::new().write(true).open(TTY_PATH)?;
+        debug!("opened terminal `{TTY_PATH}`");
+        let writer = Arc::new(Mutex::new(FileWriter::with_capacity(
+            buf_size::DEFAULT,
+            file,
+            true,
+        )));
+        Ok(Self(OutputInner::Tty { writer }))
+    }
+
+    /// Converts this [`Output`] into one that writes directly to the underlying handle, without any buffering.
+    ///
+    /// Binary tools, such as image or video pipelines, write large chunks of data and don't want newline-triggered flushes
+    /// or extra copies into a buffer. Data buffered so far is flushed first.
+    ///
+    /// Standard output is written through a duplicate of its handle, bypassing the line buffer of [`io::Stdout`].
+    /// Files and the terminal stop buffering for all clones of this [`Output`].
+    /// Compressed outputs are returned as is, since the compressor buffers data anyway.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Output;
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Output image. If `-` is specified, writes to standard output.
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let output = args.output.raw()?;
+    ///     output.lock().write_all(&[0x89, b'P', b'N', b'G'])?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn raw(self) -> io::Result<Self> {
+        match &self.0 {
+            OutputInner::Stdout => {
+                io::stdout().flush()?;
+                let file = duplicate_stdout()?;
+                debug!("writing to standard output without buffering");
+                let writer = Arc::new(Mutex::new(FileWriter::with_capacity(0, file, false)));
+                Ok(Self(OutputInner::RawStdout { writer }))
+            }
+            #[cfg(feature = "test-util")]
+            OutputInner::InjectedStdout { .. } => Ok(self),
+            OutputInner::RawStdout { .. } | OutputInner::Encoded { .. } => Ok(self),
+            OutputInner::Tty { writer } | OutputInner::File { writer, .. } => {
+                lock(writer).unbuffer()?;
+                debug!(
+                    "writing to {} without buffering",
+                    describe(self.is_tty(), self.path())
+                );
+                Ok(self)
+            }
+        }
+    }
+
+    /// Creates a file at the given path and creates a new [`Output`] instance that writes to it.
+    ///
+    /// The buffer capacity is chosen automatically based on the type of the file.
+    /// Use [`Output::builder`] to override it.
+    pub fn create(path: PathBuf) -> io::Result<Self> {
+        Self::builder().open(path)
+    }
+
+    /// Takes a file descriptor inherited from the parent process and creates a new [`Output`] instance that writes to it.
+    ///
+    /// See [`OutputBuilder::open_inherited`] for the accepted values of `spec`.
+    pub fn inherited(spec: &str) -> io::Result<Self> {
+        Self::builder().open_inherited(spec)
+    }
+
+    /// Creates a file at the given path and creates a new [`Output`] instance that writes to it with a buffer of `capacity` bytes.
+    ///
+    /// Large buffers speed up sequential writes of bulk data.
+    pub fn create_with_capacity(path: PathBuf, capacity: usize) -> io::Result<Self> {
+        Self::builder().capacity(capacity).open(path)
+    }
+
+    /// Opens a file at the given path in append mode and creates a new [`Output`] instance that writes to it.
+    ///
+    /// The file is created if it does not exist, and all writes go to the end of the file.
+    pub fn append(path: PathBuf) -> io::Result<Self> {
+        Self::builder().append(true).open(path)
+    }
+
+    /// Creates a file at the given path relative to `dir` and creates a new [`Output`] instance that writes to it.
+    ///
+    /// The path cannot escape `dir`. See [`OutputBuilder::open_at`] for details.
+    #[cfg(feature = "cap-std")]
+    pub fn create_at(dir: &cap_std::fs::Dir, path: PathBuf) -> io::Result<Self> {
+        Self::builder().open_at(dir, path)
+    }
+
+    /// Creates a new [`OutputBuilder`] to configure how an output file is created.
+    pub fn builder() -> OutputBuilder {
+        OutputBuilder::new()
+    }
+
+    /// Returns `true` if this [`Output`] writes to standard output.
+    pub fn is_stdout(&self) -> bool {
+        match &self.0 {
+            OutputInner::Stdout | OutputInner::RawStdout { .. } => true,
+            #[cfg(feature = "test-util")]
+            OutputInner::InjectedStdout { .. } => true,
+            OutputInner::Tty { .. } | OutputInner::File { .. } => false,
+            OutputInner::Encoded { sink, .. } => sink.is_stdout(),
+        }
+    }
+
+    /// Returns `true` if this [`Output`] writes to the controlling terminal opened by [`Output::tty`].
+    pub fn is_tty(&self) -> bool {
+        match &self.0 {
+            OutputInner::Tty { .. } => true,
+            OutputInner::Encoded { sink, .. } => sink.is_tty(),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if this [`Output`] writes to a file.
+    pub fn is_file(&self) -> bool {
+        match &self.0 {
+            OutputInner::File { .. } => true,
+            OutputInner::Encoded { sink, .. } => sink.is_file(),
+            _ => false,
+        }
+    }
+
+    /// Returns the path of the file this [`Output`] writes to.
+    ///
+    /// Returns `None` if this [`Output`] writes to standard output or the terminal.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.0 {
+            OutputInner::Stdout | OutputInner::RawStdout { .. } | OutputInner::Tty { .. } => None,
+            #[cfg(feature = "test-util")]
+            OutputInner::InjectedStdout { .. } => None,
+            OutputInner::File { path, .. } => Some(path),
+            OutputInner::Encoded { sink, .. } => sink.path(),
+        }
+    }
+
+    /// Returns the compression format of the data written to this [`Output`], if it is compressed.
+    ///
+    /// Outputs are compressed automatically based on the extension of the file (see [`OutputBuilder::compress`]),
+    /// or explicitly by [`Output::compress`].
+    pub fn compression(&self) -> Option<Compression> {
+        match &self.0 {
+            OutputInner::Encoded { compression, .. } => Some(*compression),
+            _ => None,
+        }
+    }
+
+    /// Converts this [`Output`] into one that compresses the data written to it, with the default compression level.
+    ///
+    /// Files are compressed automatically based on their extension when created (see [`OutputBuilder::compress`]),
+    /// so this is mostly useful for standard output. Use [`OutputBuilder::compression_level`] to choose the level for files.
+    ///
+    /// The end of the compressed stream is written when the output is closed by [`Output::close`],
+    /// or when the last clone of the output is dropped. If the output is dropped while the thread is panicking,
+    /// the stream is left unfinished, so that decompressors report the truncation instead of silently accepting partial data.
+    /// Use [`AtomicOutput`](crate::AtomicOutput) to avoid leaving partial files at all.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::Unsupported`] if the cargo feature for `compression` is not enabled,
+    /// or if compressing to the format is not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::{Compression, Output};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Compress standard output with Zstandard.
+    ///     #[arg(long)]
+    ///     zstd: bool,
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let mut output = args.output;
+    ///     if args.zstd && output.is_stdout() {
+    ///         output = output.compress(Compression::Zstd)?;
+    ///     }
+    ///     writeln!(output.lock(), "Hello, world!")?;
+    ///     output.close()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn compress(self, compression: Compression) -> io::Result<Self> {
+        self.compress_with(compression, EncoderOptions::default())
+    }
+
+    /// Converts this [`Output`] into one that compresses the data written to it with the given format and level.
+    ///
+    /// This allows a command to offer options such as `--compress-level 9` with [`CompressionLevel`] arguments.
+    /// If this [`Output`] is already compressed, e.g. because the file has a `.zst` extension, the compression is replaced,
+    /// which is only possible before any data is written. See [`Output::compress`] for other details.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidInput`] if the level is out of the range of the format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::{Compression, CompressionLevel, Output};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Compresses the output with gzip at the given level.
+    ///     #[arg(long)]
+    ///     gzip: Option<CompressionLevel>,
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let mut output = args.output;
+    ///     if let Some(level) = args.gzip {
+    ///         output = output.with_compression(Compression::Gzip, level)?;
+    ///     }
+    ///     writeln!(output.lock(), "Hello, world!")?;
+    ///     output.close()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_compression(
+        self,
+        compression: Compression,
+        level: CompressionLevel,
+    ) -> io::Result<Self> {
+        self.compress_with(
+            compression,
+            EncoderOptions {
+                level: Some(level.get()),
+                ..EncoderOptions::default()
+            },
+        )
+    }
+
+    fn compress_with(self, compression: Compression, options: EncoderOptions) -> io::Result<Self> {
+        let this = match &self.0 {
+            OutputInner::Encoded { sink, writer, .. } => {
+                let mut writer = lock(writer);
+                if writer.is_written() {
+                    return Err(error::new(
+                        ErrorKind::InvalidInput,
+                        "cannot change the compression of an output that has already been written to",
+                    ));
+                }
+                writer.abandon();
+                Output::clone(sink)
+            }
+            _ => self,
+        };
+        let sink = Box::new(this.clone());
+        let encoder = compression.encoder(this, options)?;
+        debug!(
+            "compressing {} as {compression}",
+            describe(sink.is_tty(), sink.path())
+        );
+        let writer = Arc::new(Mutex::new(encoder));
+        Ok(Self(OutputInner::Encoded {
+            sink,
+            compression,
+            writer,
+        }))
+    }
+
+    /// Returns a machine-readable description of this [`Output`].
+    pub fn describe(&self) -> IoDescription {
+        match &self.0 {
+            OutputInner::Stdout => IoDescription {
+                kind: IoKind::Stdout,
+                path: None,
+                size: None,
+                is_tty: io::stdout().is_terminal(),
+                compression: None,
+                encoding: None,
+            },
+            #[cfg(feature = "test-util")]
+            OutputInner::InjectedStdout { .. } => IoDescription {
+                kind: IoKind::Stdout,
+                path: None,
+                size: None,
+                is_tty: false,
+                compression: None,
+                encoding: None,
+            },
+            OutputInner::RawStdout { writer } => IoDescription {
+                kind: IoKind::Stdout,
+                path: None,
+                size: None,
+                is_tty: lock(writer).get_ref().is_terminal(),
+                compression: None,
+                encoding: None,
+            },
+            OutputInner::Tty { .. } => IoDescription {
+                kind: IoKind::Tty,
+                path: None,
+                size: None,
+                is_tty: true,
+                compression: None,
+                encoding: None,
+            },
+            OutputInner::File { path, writer } => {
+                let writer = lock(writer);
+                let file = writer.get_ref();
+                IoDescription {
+                    kind: IoKind::File,
+                    path: Some(PathBuf::clone(path)),
+                    size: file.metadata().ok().map(|m| m.len()),
+                    is_tty: file.is_terminal(),
+                    compression: None,
+                    encoding: None,
+                }
+            }
+            OutputInner::Encoded {
+                sink, compression, ..
+            } => IoDescription {
+                compression: Some(compression.name()),
+                ..sink.describe()
+            },
+        }
+    }
+
+    /// Locks this [`Output`] and returns a writer that hexdumps the first `limit` bytes written to standard error.
+    ///
+    /// A `limit` of 0 disables dumping, so the limit can be derived from a debug flag.
+    pub fn debug_dump(&self, limit: usize) -> DebugDump<LockedOutput<'_>> {
+        let label = match (self.is_tty(), self.path()) {
+            (_, Some(path)) => format!("output `{}`", path.display()),
+            (true, None) => "tty".to_owned(),
+            (false, None) => "stdout".to_owned(),
+        };
+        DebugDump::new(self.lock(), label, limit)
+    }
+
+    /// Locks this [`Output`] and returns a writer that converts all line endings to `ending`.
+    ///
+    /// This lets cross-platform tools offer an option like `--eol crlf` without filtering the output by hand.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::{LineEnding, Output};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Line ending of the output: `lf`, `crlf`, `cr`, or `native`.
+    ///     #[arg(long, default_value = "native")]
+    ///     eol: LineEnding,
+    ///     #[arg(default_value = "-")]
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let mut writer = args.output.normalize_eol(args.eol);
+    ///     writeln!(writer, "Hello,")?;
+    ///     writeln!(writer, "world!")?;
+    ///     writer.flush()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn normalize_eol(&self, ending: LineEnding) -> EolWriter<LockedOutput<'_>> {
+        EolWriter::new(self.lock(), ending)
+    }
+
+    /// Flushes this [`Output`] and reports any error, then drops it.
+    ///
+    /// Dropping an [`Output`] also flushes the buffered data, but errors are silently ignored.
+    /// Call this method at the end of a command so that write errors, such as a full disk, can be reported
+    /// and the process can exit with a non-zero status.
+    ///
+    /// If clones of this [`Output`] exist, the file is closed when the last one is dropped.
+    /// Compressed outputs are finished by this method, so the clones can no longer be written to.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Output;
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Output file. If `-` is specified, writes to standard output.
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     writeln!(args.output.lock(), "Hello, world!")?;
+    ///     args.output.close()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn close(self) -> io::Result<()> {
+        self.lock().close()
+    }
+
+    /// Registers this [`Output`] to be flushed when the process is interrupted by `Ctrl-C`, until the returned guard is dropped.
+    ///
+    /// This installs the handler described in [`install_interrupt_handler`](crate::install_interrupt_handler), so that interrupted runs
+    /// do not lose the data still buffered in memory. Compressed outputs are flushed but not finished, so decompressors report the truncation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::{Input, Output};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     input: Input,
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let _guard = args.output.flush_on_interrupt()?;
+    ///     for line in args.input.lines() {
+    ///         writeln!(&args.output, "{}", line?.len())?;
+    ///     }
+    ///     args.output.close()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "signal")]
+    pub fn flush_on_interrupt(&self) -> io::Result<crate::InterruptGuard> {
+        let output = self.clone();
+        crate::InterruptGuard::register(move || (&output).flush())
+    }
+
+    /// Locks this [`Output`] and returns a CSV writer to it with the default [`CsvOptions`](crate::CsvOptions).
+    ///
+    /// Fields are separated by tabs if the path has the `.tsv` extension, and by commas otherwise.
+    /// The writer buffers records, so call [`csv::Writer::flush`] when done to report write errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Output;
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Output file. If `-` is specified, writes to standard output.
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let mut writer = args.output.csv_writer();
+    ///     writer.write_record(["name", "size"])?;
+    ///     writer.write_record(["Cargo.toml", "1024"])?;
+    ///     writer.flush()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "csv")]
+    pub fn csv_writer(&self) -> csv::Writer<LockedOutput<'_>> {
+        self.csv_writer_with(&crate::CsvOptions::new())
+    }
+
+    /// Locks this [`Output`] and returns a CSV writer to it configured with `options`.
+    ///
+    /// See [`CsvOptions`](crate::CsvOptions) for an example.
+    #[cfg(feature = "csv")]
+    pub fn csv_writer_with(&self, options: &crate::CsvOptions) -> csv::Writer<LockedOutput<'_>> {
+        options.writer(self.path(), self.lock())
+    }
+
+    /// Serializes `value` as compact JSON on a single line, followed by a newline, and flushes this [`Output`].
+    ///
+    /// Calling this for each record produces JSON Lines (NDJSON). Use [`LockedOutput::write_json`] to write many records without flushing each one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::Output;
+    ///
+    /// #[derive(Debug, serde::Serialize)]
+    /// struct Summary {
+    ///     files: usize,
+    ///     bytes: u64,
+    /// }
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Prints the summary as indented JSON.
+    ///     #[arg(long)]
+    ///     pretty: bool,
+    ///     /// Output file. If `-` is specified, writes to standard output.
+    ///     #[arg(default_value = "-")]
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     let summary = Summary { files: 3, bytes: 1024 };
+    ///     if args.pretty {
+    ///         args.output.write_json_pretty(&summary)?;
+    ///     } else {
+    ///         args.output.write_json(&summary)?;
+    ///     }
+    ///     args.output.close()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn write_json<T>(&self, value: &T) -> io::Result<()>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        let mut locked = self.lock();
+        locked.write_json(value)?;
+        locked.flush()
+    }
+
+    /// Serializes `value` as indented JSON, followed by a newline, and flushes this [`Output`].
+    #[cfg(feature = "serde")]
+    pub fn write_json_pretty<T>(&self, value: &T) -> io::Result<()>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        let mut locked = self.lock();
+        locked.write_json_pretty(value)?;
+        locked.flush()
+    }
+
+    /// Serializes `value` as a YAML document, and flushes this [`Output`].
+    #[cfg(feature = "yaml")]
+    pub fn write_yaml<T>(&self, value: &T) -> io::Result<()>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        let mut locked = self.lock();
+        locked.write_yaml(value)?;
+        locked.flush()
+    }
+
+    /// Flushes this [`Output`] and synchronizes the file to the storage device, if it writes to a file.
+    pub(crate) fn sync_all(&self) -> io::Result<()> {
+        let mut locked = self.lock();
+        locked.flush()?;
+        match &locked.0 {
+            LockedOutputInner::File { writer, .. } => writer.get_ref().sync_all()?,
+            LockedOutputInner::Encoded { sink, .. } => sink.sync_all()?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Locks this [`Output`] for writing and returns a writable guard.
+    ///
+    /// This lock is released when the returned [`LockedOutput`] instance is dropped.
+    /// The returned `LockedOutput` instance implements [`Write`] trait for writing data.
+    pub fn lock(&self) -> LockedOutput<'_> {
+        let inner = match &self.0 {
+            OutputInner::Stdout => {
+                let writer = io::stdout().lock();
+                debug!("locked standard output");
+                LockedOutputInner::Stdout { writer }
+            }
+            #[cfg(feature = "test-util")]
+            OutputInner::InjectedStdout { writer } => {
+                let writer = lock(writer);
+                debug!("locked injected standard output");
+                LockedOutputInner::InjectedStdout { writer }
+            }
+            OutputInner::RawStdout { writer } => {
+                let writer = lock(writer);
+                debug!("locked standard output");
+                LockedOutputInner::RawStdout { writer }
+            }
+            OutputInner::Tty { writer } => {
+                let writer = lock(writer);
+                debug!("locked terminal");
+                LockedOutputInner::Tty { writer }
+            }
+            OutputInner::File { path, writer: file } => {
+                let writer = lock(file);
+                debug!("locked output file `{}`", path.display());
+                LockedOutputInner::File {
+                    path: Arc::clone(path),
+                    writer,
+                }
+            }
+            OutputInner::Encoded { sink, writer, .. } => {
+                let writer = lock(writer);
+                debug!("locked compressed {}", describe(sink.is_tty(), sink.path()));
+                LockedOutputInner::Encoded { sink, writer }
+            }
+        };
+        LockedOutput(inner)
+    }
+
+    /// Converts this [`Output`] into a buffered writer of a boxed trait object, a type accepted by many libraries.
+    ///
+    /// The returned writer has no buffer of its own and writes straight through to this [`Output`],
+    /// so the data is buffered only once, as configured for this output, and terminals are still line-buffered.
+    ///
+    /// Like dropping an [`Output`], dropping the returned writer ignores errors.
+    /// Convert a clone instead, and call [`Output::close`] on the original after the writer is dropped
+    /// to report write errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap_file::Output;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-buf-writer-{}", std::process::id()));
+    ///     let output = Output::create(path.clone())?;
+    ///     let mut writer = output.clone().into_buf_writer();
+    ///     writeln!(writer, "Hello, world!")?;
+    ///     drop(writer);
+    ///     output.close()?;
+    ///     assert_eq!(std::fs::read_to_string(&path)?, "Hello, world!\n");
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_buf_writer(self) -> BufWriter<Box<dyn Write + Send>> {
+        // Writes at least as long as the capacity bypass the buffer of `BufWriter`, so a zero capacity disables it.
+        BufWriter::with_capacity(0, Box::new(self))
+    }
+}
+
+/// A builder for creating an [`Output`] with custom options.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io;
+///
+/// use clap_file::Output;
+///
+/// fn main() -> io::Result<()> {
+///     let output = Output::builder().capacity(1024 * 1024).open("output.txt".into())?;
+///     let log = Output::builder().append(true).open("output.log".into())?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OutputBuilder {
+    capacity: Option<usize>,
+    buffering: Buffering,
+    directory_policy: DirectoryPolicy,
+    sync_on_close: bool,
+    compress: bool,
+    compression_level: Option<CompressionLevel>,
+    compression_threads: Option<NonZeroUsize>,
+    zstd_frame_size: Option<usize>,
+    create: bool,
+    truncate: bool,
+    append: bool,
+    create_new: bool,
+    create_dirs: bool,
+    atomic: bool,
+}
+
+impl Default for OutputBuilder {
+    fn default() -> Self {
+        Self {
+            capacity: None,
+            buffering: Buffering::default(),
+            directory_policy: DirectoryPolicy::default(),
+            sync_on_close: false,
+            compress: true,
+            compression_level: None,
+            compression_threads: None,
+            zstd_frame_size: None,
+            create: true,
+            truncate: true,
+            append: false,
+            create_new: false,
+            create_dirs: false,
+            atomic: false,
+        }
+    }
+}
+
+/// Specifies when an [`Output`] writing to a file flushes its buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Buffering {
+    /// Flushes the buffer whenever a newline is written, like [`io::LineWriter`].
+    ///
+    /// This is suitable for interactive output, but slow when writing many lines.
+    Line,
+    /// Flushes the buffer only when it is full or explicitly flushed, like [`io::BufWriter`].
+    Block,
+    /// Uses line buffering for terminals and block buffering for other files, such as regular files and pipes.
+    ///
+    /// This mirrors the behavior of the C standard library.
+    #[default]
+    Auto,
+    /// Flushes the buffer whenever a newline is written or the given number of bytes are pending, whichever comes first.
+    ///
+    /// Like [`Buffering::Line`], complete lines are visible immediately, but long lines and output without newlines,
+    /// such as progress indicators, are also written out in chunks of bounded size instead of waiting for the buffer to fill.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap_file::{Buffering, Output};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let log = Output::builder()
+    ///         .buffering(Buffering::LineOrSize(4096))
+    ///         .open("output.log".into())?;
+    ///     Ok(())
+    /// }
+    /// ```
+    LineOrSize(usize),
+    /// Writes directly to the file without buffering.
+    ///
+    /// This is suitable for binary streaming, where data is written in large chunks. See also [`Output::raw`].
+    None,
+}
+
+/// Specifies what an [`OutputBuilder`] does when the output path refers to an existing directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum DirectoryPolicy {
+    /// Fails with an error that mentions the directory.
+    #[default]
+    Error,
+    /// Writes to a file in the directory that has the same name as the input file, like `cp`.
+    ///
+    /// This only works with [`OutputBuilder::open_for_input`] and a file-backed [`Input`];
+    /// otherwise, an error is returned as with [`DirectoryPolicy::Error`].
+    InputFileName,
+}
+
+impl OutputBuilder {
+    /// Creates a new [`OutputBuilder`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the capacity of the write buffer.
+    ///
+    /// If not set, the capacity is chosen based on the type of the created file:
+    /// a large buffer for regular files, a pipe-sized buffer for FIFOs, and a small buffer for terminals.
+    pub fn capacity(&mut self, capacity: usize) -> &mut Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Sets when the write buffer is flushed.
+    ///
+    /// Defaults to [`Buffering::Auto`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap_file::{Buffering, Output};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     // `tail -f` on the log shows each line as soon as it is written
+    ///     let log = Output::builder().buffering(Buffering::Line).open("output.log".into())?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn buffering(&mut self, buffering: Buffering) -> &mut Self {
+        self.buffering = buffering;
+        self
+    }
+
+    /// Sets what to do when the output path refers to an existing directory.
+    ///
+    /// Defaults to [`DirectoryPolicy::Error`].
+    pub fn directory_policy(&mut self, policy: DirectoryPolicy) -> &mut Self {
+        self.directory_policy = policy;
+        self
+    }
+
+    /// Sets whether [`Output::close`] synchronizes the file to the storage device before reporting success.
+    ///
+    /// Defaults to `false`. When enabled, closing the output calls [`File::sync_all`] after flushing,
+    /// so that the data survives a crash or power loss once `close` returns. Backup and export tools need this guarantee.
+    /// Dropping the output without closing it does not synchronize the file.
+    ///
+    /// See [`AtomicOutput::commit_durable`](crate::AtomicOutput::commit_durable) for replacing files durably.
+    pub fn sync_on_close(&mut self, sync: bool) -> &mut Self {
+        self.sync_on_close = sync;
+        self
+    }
+
+    /// Sets whether to compress files based on their extension (e.g. `.zst`).
+    ///
+    /// Defaults to `true`. Only the formats whose cargo feature is enabled and that support compression are compressed;
+    /// see [`Compression`]. Other files are written as is.
+    pub fn compress(&mut self, compress: bool) -> &mut Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Sets the compression level used when the file is compressed.
+    ///
+    /// The range and the default of the level depend on the compression format; see [`CompressionLevel`].
+    /// For gzip, the level defaults to 6. For Zstandard, the level defaults to 3.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap_file::Output;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let archive = Output::builder().compression_level(19).open("dump.sql.zst".into())?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn compression_level(&mut self, level: impl Into<CompressionLevel>) -> &mut Self {
+        self.compression_level = Some(level.into());
+        self
+    }
+
+    /// Sets the number of threads used to compress the file when it is compressed.
+    ///
+    /// If not set, the data is compressed on the thread that writes it. Zstandard uses its multi-threaded mode.
+    /// Gzip compresses blocks of 1 MiB on separate threads like `pigz`, writing each block as a gzip member,
+    /// which any gzip decompressor accepts. Flushing a gzip output compresses the data of the current block written so far
+    /// without ending its member, but the rest of that block is then compressed on the writing thread.
+    /// Custom codecs ignore this setting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::{io, num::NonZeroUsize, thread};
+    ///
+    /// use clap_file::Output;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let threads = thread::available_parallelism().unwrap_or(NonZeroUsize::MIN);
+    ///     let archive = Output::builder().compression_threads(threads).open("dump.sql.gz".into())?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Flushed data can be decompressed before the output is closed, and flushing does not split the gzip member:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "gzip")] {
+    /// use std::{io::{Read as _, Write as _}, num::NonZeroUsize};
+    ///
+    /// use clap_file::Output;
+    ///
+    /// let path = std::env::temp_dir().join(format!("clap-file-doc-gz-flush-{}.gz", std::process::id()));
+    /// let threads = NonZeroUsize::new(2).unwrap();
+    /// let output = Output::builder().compression_threads(threads).open(path.clone()).unwrap();
+    /// let decompress = || {
+    ///     let mut data = vec![];
+    ///     // The stream is not finished until the output is closed, so reading it fails at the end
+    ///     let file = std::fs::File::open(&path).unwrap();
+    ///     let _ = flate2::read::GzDecoder::new(file).read_to_end(&mut data);
+    ///     String::from_utf8(data).unwrap()
+    /// };
+    /// writeln!(output.lock(), "first").unwrap();
+    /// output.lock().flush().unwrap();
+    /// assert_eq!(decompress(), "first\n");
+    /// writeln!(output.lock(), "second").unwrap();
+    /// output.close().unwrap();
+    /// // `GzDecoder` only reads the first member
+    /// assert_eq!(decompress(), "first\nsecond\n");
+    /// # std::fs::remove_file(&path).unwrap();
+    /// # }
+    /// ```
+    pub fn compression_threads(&mut self, threads: NonZeroUsize) -> &mut Self {
+        self.compression_threads = Some(threads);
+        self
+    }
+
+    /// Writes Zstandard outputs in the [seekable format], compressing every `size` bytes of data into an independent frame.
+    ///
+    /// The frames are followed by a seek table of their sizes, so that readers can decompress any part of the output
+    /// without decompressing the data before it. Decompressors that do not know the format, including [`Input`],
+    /// skip the seek table and decompress the whole output as usual. Flushing the output also ends the current frame.
+    ///
+    /// Smaller frames allow finer random access but compress worse; a few hundred KiB to a few MiB is typical.
+    /// Opening a Zstandard output fails with [`io::ErrorKind::InvalidInput`] if `size` is 0 or does not fit in `u32`.
+    /// Other formats ignore this setting.
+    ///
+    /// [seekable format]: https://github.com/facebook/zstd/blob/dev/contrib/seekable_format/zstd_seekable_compression_format.md
+    ///
+    /// # Examples
+    ///
+    /// Each frame listed in the seek table decompresses on its own:
+    ///
+    /// ```rust
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap_file::Output;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-seekable-{}.zst", std::process::id()));
+    ///     let output = Output::builder().zstd_frame_size(1000).open(path.clone())?;
+    ///     for i in 0..1000 {
+    ///         writeln!(output.lock(), "line {i}")?;
+    ///     }
+    ///     output.close()?;
+    ///
+    ///     // Read the seek table from the end of the file
+    ///     let data = std::fs::read(&path)?;
+    ///     let u32_at = |pos: usize| u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+    ///     assert_eq!(u32_at(data.len() - 4), 0x8f92_eab1);
+    ///     let frames = u32_at(data.len() - 9);
+    ///     let entries = data.len() - 9 - frames * 8;
+    ///
+    ///     let mut offset = 0;
+    ///     let mut lines = 0;
+    ///     for i in 0..frames {
+    ///         let (compressed, decompressed) = (u32_at(entries + i * 8), u32_at(entries + i * 8 + 4));
+    ///         let frame = zstd::decode_all(&data[offset..offset + compressed])?;
+    ///         assert_eq!(frame.len(), decompressed);
+    ///         assert!(frame.len() <= 1000);
+    ///         lines += frame.iter().filter(|&&b| b == b'\n').count();
+    ///         offset += compressed;
+    ///     }
+    ///     assert!(frames > 1);
+    ///     assert_eq!(lines, 1000);
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "zstd")]
+    pub fn zstd_frame_size(&mut self, size: usize) -> &mut Self {
+        self.zstd_frame_size = Some(size);
+        self
+    }
+
+    /// Sets whether to create the file if it does not exist.
+    ///
+    /// Defaults to `true`. See [`OpenOptions::create`] for details.
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.create = create;
+        self
+    }
+
+    /// Sets whether to truncate the file if it already exists.
+    ///
+    /// Defaults to `true`. This is ignored when [`append`](Self::append) is enabled.
+    /// See [`OpenOptions::truncate`] for details.
+    pub fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Sets whether to append to the end of the file instead of overwriting it.
+    ///
+    /// Defaults to `false`. See [`OpenOptions::append`] for details.
+    pub fn append(&mut self, append: bool) -> &mut Self {
+        self.append = append;
+        self
+    }
+
+    /// Sets whether to create a new file, failing if it already exists.
+    ///
+    /// Defaults to `false`. When enabled, [`create`](Self::create) and [`truncate`](Self::truncate) are ignored.
+    /// See [`OpenOptions::create_new`] for details.
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Sets whether to create the missing parent directories of the file, like `mkdir -p`.
+    ///
+    /// Defaults to `false`, so opening a file in a directory that does not exist fails.
+    pub fn create_dirs(&mut self, create_dirs: bool) -> &mut Self {
+        self.create_dirs = create_dirs;
+        self
+    }
+
+    /// Sets whether to write to a temporary file next to the target and rename it over the target when closed.
+    ///
+    /// Defaults to `false`. When enabled, readers of the target never see partially written data:
+    /// [`Output::close`] renames the temporary file over the target, and so does dropping the output,
+    /// unless flushing the buffered data fails or the thread is panicking, in which case the temporary file is removed
+    /// and the target is left untouched. [`Output::path`] returns the path of the target.
+    /// Use [`AtomicOutput`](crate::AtomicOutput) to decide explicitly whether to commit the data.
+    ///
+    /// Since the target is always replaced, [`create`](Self::create) and [`truncate`](Self::truncate) are ignored.
+    /// Opening the file fails with [`io::ErrorKind::InvalidInput`] if [`append`](Self::append) or [`create_new`](Self::create_new) is also enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap_file::Output;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-doc-atomic-{}.txt", std::process::id()));
+    ///     let output = Output::builder().atomic(true).open(path.clone())?;
+    ///     assert_eq!(output.path(), Some(path.as_path()));
+    ///     writeln!(output.lock(), "Hello, world!")?;
+    ///     assert!(!path.exists());
+    ///     output.close()?;
+    ///     assert_eq!(std::fs::read_to_string(&path)?, "Hello, world!\n");
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn atomic(&mut self, atomic: bool) -> &mut Self {
+        self.atomic = atomic;
+        self
+    }
+
+    fn open_options(&self) -> OpenOptions {
+        let mut options = OpenOptions::new();
+        options
+            .write(true)
+            .append(self.append)
+            .truncate(self.truncate && !self.append)
+            .create(self.create)
+            .create_new(self.create_new);
+        options
+    }
+
+    /// Opens a file at the given path with the options specified by this builder.
+    ///
+    /// By default, the file is created if it does not exist and truncated if it already exists, like [`File::create`].
+    pub fn open(&self, path: PathBuf) -> io::Result<Output> {
+        self.open_inner(path, None, Location::Fs)
+    }
+
+    /// Takes a file descriptor inherited from the parent process and writes to it with the options specified by this builder.
+    ///
+    /// This allows service-style programs to write to sockets managed by systemd-style socket activation,
+    /// or to descriptors redirected by the shell (e.g. `mytool fd:3 3>output.txt`). `spec` is one of the following:
+    ///
+    /// * A descriptor number, e.g. `3`.
+    /// * `listen`, for the first descriptor passed by socket activation (`LISTEN_FDS`).
+    /// * A descriptor name passed by socket activation (`LISTEN_FDNAMES`), set by `FileDescriptorName=` in systemd.
+    ///
+    /// Arguments of the form `fd:<spec>` (e.g. `fd:3` or `fd:listen`) take an inherited descriptor
+    /// only if enabled with [`OutputValueParser::inherited_fds`](crate::OutputValueParser::inherited_fds).
+    /// The descriptor is duplicated and the original is left open, so only pass descriptors that this process inherited;
+    /// a descriptor of a file opened by this process would share its file offset. Each descriptor can be taken only once.
+    /// The options for opening files, such as [`append`](Self::append), have no effect.
+    /// Listening sockets cannot be written to directly; use `Accept=yes` in the socket unit to receive connected sockets.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::Unsupported`] on platforms other than Unix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap::Parser as _;
+    /// use clap_file::{Output, OutputValueParser};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     /// Output. `fd:listen` writes to the socket passed by systemd.
+    ///     #[arg(
+    ///         default_value = "fd:listen",
+    ///         value_parser = OutputValueParser::new().inherited_fds(true),
+    ///     )]
+    ///     output: Output,
+    /// }
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let args = Args::parse();
+    ///     writeln!(args.output.lock(), "Hello, world!")?;
+    ///     args.output.close()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn open_inherited(&self, spec: &str) -> io::Result<Output> {
+        self.open_inner(
+            PathBuf::from(format!("fd:{spec}")),
+            None,
+            Location::Inherited(spec),
+        )
+    }
+
+    /// Opens a file at the given path relative to `dir` with the options specified by this builder.
+    ///
+    /// The path is resolved with [`cap_std`], so it cannot escape `dir` (e.g. by `..` or absolute paths).
+    /// This allows capability-based sandboxed programs to create files specified by command-line arguments safely.
+    #[cfg(feature = "cap-std")]
+    pub fn open_at(&self, dir: &cap_std::fs::Dir, path: PathBuf) -> io::Result<Output> {
+        self.open_inner(path, None, Location::Dir(dir))
+    }
+
+    /// Opens a file at the given path with the options specified by this builder, as the output paired with `input`.
+    ///
+    /// If `path` refers to an existing directory and the directory policy is [`DirectoryPolicy::InputFileName`],
+    /// the output is written to a file in that directory named after the input file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::io;
+    ///
+    /// use clap_file::{DirectoryPolicy, Input, Output};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let input = Input::open("data/input.txt".into())?;
+    ///     // writes to `out/input.txt`
+    ///     let output = Output::builder()
+    ///         .directory_policy(DirectoryPolicy::InputFileName)
+    ///         .open_for_input("out".into(), &input)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn open_for_input(&self, path: PathBuf, input: &Input) -> io::Result<Output> {
+        self.open_inner(path, input.path().and_then(Path::file_name), Location::Fs)
+    }
+
+    fn open_inner(
+        &self,
+        path: PathBuf,
+        input_file_name: Option<&OsStr>,
+        location: Location<'_>,
+    ) -> io::Result<Output> {
+        let path = if location.is_dir(&path) {
+            match (self.directory_policy, input_file_name) {
+                (DirectoryPolicy::InputFileName, Some(file_name)) => path.join(file_name),
+                _ => {
+                    return Err(error::new(
+                        ErrorKind::IsDirectory,
+                        format!("output path `{}` is a directory", path.display()),
+                    ))
+                }
+            }
+        } else if paths::has_trailing_separator(&path) {
+            // Without this check, the error depends on the platform (e.g. `EISDIR` or `ENOENT`).
+            return Err(error::new(
+                ErrorKind::NotFound,
+                format!("output directory `{}` does not exist", path.display()),
+            ));
+        } else {
+            path
+        };
+        if self.create_dirs {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                location
+                    .create_dir_all(parent)
+                    .map_err(|e| hint::output_error(e, &path))?;
+            }
+        }
+        let (file, rename) = if self.atomic && !matches!(location, Location::Inherited(_)) {
+            if self.append || self.create_new {
+                return Err(error::new(
+                    ErrorKind::InvalidInput,
+                    "atomic outputs cannot be opened in append or create-new mode",
+                ));
+            }
+            let (temp, file) = loop {
+                let temp = atomic_output::temp_path(&path)?;
+                match location.create_temp(&temp) {
+                    Ok(file) => break (temp, file),
+                    // A file left by a previous process with the same ID
+                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                    Err(e) => return Err(hint::output_error(e, &path)),
+                }
+            };
+            debug!(
+                "writing `{}` through temporary file `{}`",
+                path.display(),
+                temp.display()
+            );
+            (file, Some(location.pending_rename(temp, path.clone())?))
+        } else {
+            let file = location
+                .open(self, &path)
+                .map_err(|e| hint::output_error(e, &path))?;
+            (file, None)
+        };
+        let path = Arc::new(path);
+        let capacity = self.capacity.unwrap_or_else(|| buf_size::for_file(&file));
+        let capacity = if self.buffering == Buffering::None {
+            0
+        } else {
+            capacity
+        };
+        let line_buffered = match self.buffering {
+            Buffering::Line | Buffering::LineOrSize(_) => true,
+            Buffering::Block | Buffering::None => false,
+            Buffering::Auto => file.is_terminal(),
+        };
+        debug!(
+            "opened output file `{}` (buffer capacity: {capacity}, line buffered: {line_buffered})",
+            path.display()
+        );
+        let mut writer = FileWriter::with_capacity(capacity, file, line_buffered)
+            .with_sync_on_close(self.sync_on_close);
+        if let Buffering::LineOrSize(threshold) = self.buffering {
+            writer = writer.with_flush_threshold(threshold);
+        }
+        if let Some(rename) = rename {
+            writer = writer.with_rename(rename);
+        }
+        let writer = Arc::new(Mutex::new(writer));
+        let compression = Compression::for_output_path(&path).filter(|_| self.compress);
+        let output = Output(OutputInner::File { path, writer });
+        match compression {
+            Some(compression) => output.compress_with(
+                compression,
+                EncoderOptions {
+                    level: self.compression_level.map(CompressionLevel::get),
+                    threads: self.compression_threads,
+                    frame_size: self.zstd_frame_size,
+                },
+            ),
+            None => Ok(output),
+        }
+    }
+}
+
+/// Where an [`OutputBuilder`] opens files.
+#[derive(Clone, Copy)]
+enum Location<'a> {
+    /// Paths relative to the current directory.
+    Fs,
+    /// A file descriptor inherited from the parent process.
+    Inherited(&'a str),
+    /// Paths relative to a directory that they cannot escape.
+    #[cfg(feature = "cap-std")]
+    Dir(&'a cap_std::fs::Dir),
+}
+
+impl Location<'_> {
+    fn is_dir(self, path: &Path) -> bool {
+        match self {
+            Self::Fs => path.is_dir(),
+            Self::Inherited(_) => false,
+            #[cfg(feature = "cap-std")]
+            Self::Dir(dir) => dir.is_dir(path),
+        }
+    }
+
+    fn create_dir_all(self, path: &Path) -> io::Result<()> {
+        match self {
+            Self::Fs => fs::create_dir_all(path),
+            Self::Inherited(_) => Ok(()),
+            #[cfg(feature = "cap-std")]
+            Self::Dir(dir) => dir.create_dir_all(path),
+        }
+    }
+
+    fn open(self, builder: &OutputBuilder, path: &Path) -> io::Result<File> {
+        match self {
+            Self::Fs => builder.open_options().open(path),
+            Self::Inherited(spec) => inherited_fd::open(spec),
+            #[cfg(feature = "cap-std")]
+            Self::Dir(dir) => {
+                let mut options = cap_std::fs::OpenOptions::new();
+                options
+                    .write(true)
+                    .append(builder.append)
+                    .truncate(builder.truncate && !builder.append)
+                    .create(builder.create)
+                    .create_new(builder.create_new);
+                Ok(dir.open_with(path, &options)?.into_std())
+            }
+        }
+    }
+
+    fn create_temp(self, path: &Path) -> io::Result<File> {
+        match self {
+            Self::Fs => OpenOptions::new().write(true).create_new(true).open(path),
+            Self::Inherited(_) => unreachable!("inherited descriptors are not written atomically"),
+            #[cfg(feature = "cap-std")]
+            Self::Dir(dir) => {
+                let mut options = cap_std::fs::OpenOptions::new();
+                options.write(true).create_new(true);
+                Ok(dir.open_with(path, &options)?.into_std())
+            }
+        }
+    }
+
+    fn pending_rename(self, temp: PathBuf, target: PathBuf) -> io::Result<PendingRename> {
+        match self {
+            #[cfg(feature = "cap-std")]
+            Self::Dir(dir) => match dir.try_clone() {
+                Ok(owned) => Ok(PendingRename::in_dir(owned, temp, target)),
+                Err(e) => {
+                    let _ = dir.remove_file(&temp);
+                    Err(e)
+                }
+            },
+            _ => Ok(PendingRename::new(temp, target)),
+        }
+    }
+}
+
+/// Duplicates the handle of standard output, so that it can be written to without the buffer of [`io::Stdout`].
+fn duplicate_stdout() -> io::Result<File> {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsFd as _;
+        Ok(File::from(io::stdout().as_fd().try_clone_to_owned()?))
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::io::AsHandle as _;
+        Ok(File::from(io::stdout().as_handle().try_clone_to_owned()?))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        Err(error::new(
+            ErrorKind::Unsupported,
+            "unbuffered standard output is not supported on this platform",
+        ))
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        match &self.0 {
+            #[cfg(feature = "log")]
+            OutputInner::File { path, writer } if Arc::strong_count(writer) == 1 => {
+                debug!("closing output file `{}`", path.display());
+            }
+            // Like `BufWriter`, errors are ignored on drop; use `close()` to report them.
+            // On panic, the stream is left unfinished so that the partial data is not mistaken for a complete archive.
+            OutputInner::Encoded { sink, writer, .. } if Arc::strong_count(writer) == 1 => {
+                if std::thread::panicking() {
+                    debug!(
+                        "leaving compressed {} unfinished due to panic",
+                        describe(sink.is_tty(), sink.path())
+                    );
+                    lock(writer).abandon();
+                } else {
+                    let _ = lock(writer).finish();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl FromStr for Output {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(PathBuf::from(s))
+    }
+}
+
+/// Creates an [`Output`] with the same rules as parsing it from a command-line argument.
+///
+/// `-` writes to standard output, `tty:` writes to the terminal, and any other path creates a file.
+/// Inherited file descriptors (`fd:<spec>`) are not taken; use [`Output::inherited`] for them.
+/// Use `./-` to create a file named `-`.
+/// This allows alternative argument parsers and programmatic construction to share the behavior of the clap value parser.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::{ffi::OsStr, io};
+///
+/// use clap_file::Output;
+///
+/// fn main() -> io::Result<()> {
+///     let output = Output::try_from(OsStr::new("-"))?;
+///     assert!(output.is_stdout());
+///     Ok(())
+/// }
+/// ```
+impl TryFrom<PathBuf> for Output {
+    type Error = io::Error;
+
+    fn try_from(value: PathBuf) -> Result<Self, Self::Error> {
+        if value.as_os_str() == "-" {
+            return Ok(Self::stdout());
+        }
+        if value.as_os_str() == "tty:" {
+            return Self::tty();
+        }
+        Self::create(value)
+    }
+}
+
+/// Creates an [`Output`] with the same rules as parsing it from a command-line argument.
+impl TryFrom<&Path> for Output {
+    type Error = io::Error;
+
+    fn try_from(value: &Path) -> Result<Self, Self::Error> {
+        Self::try_from(value.to_owned())
+    }
+}
+
+/// Creates an [`Output`] with the same rules as parsing it from a command-line argument.
+impl TryFrom<&OsStr> for Output {
+    type Error = io::Error;
+
+    fn try_from(value: &OsStr) -> Result<Self, Self::Error> {
+        Self::try_from(PathBuf::from(value))
+    }
+}
+
+macro_rules! with_writer {
+    ($inner:expr, $var:ident => $e:expr) => {
+        match $inner {
+            OutputInner::Stdout => {
+                let mut $var = io::stdout();
+                $e
+            }
+            #[cfg(feature = "test-util")]
+            OutputInner::InjectedStdout { writer } => {
+                let mut $var = lock(writer);
+                $e
+            }
+            OutputInner::RawStdout { writer }
+            | OutputInner::Tty { writer }
+            | OutputInner::File { writer, .. } => {
+                let mut $var = lock(writer);
+                $e
+            }
+            OutputInner::Encoded { writer, .. } => {
+                let mut $var = lock(writer);
+                $e
+            }
+        }
+    };
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        (&*self).write_vectored(bufs)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        (&*self).write_all(buf)
+    }
+}
+
+impl Output {
+    /// Reports a warning or applies the binary guard if `buf` looks binary and this [`Output`] writes to a terminal.
+    fn check_binary(&self, buf: &[u8]) -> io::Result<()> {
+        warning::check_binary(buf, || match &self.0 {
+            OutputInner::Stdout => io::stdout().is_terminal(),
+            #[cfg(feature = "test-util")]
+            OutputInner::InjectedStdout { .. } => false,
+            OutputInner::Tty { .. } => true,
+            OutputInner::RawStdout { writer } | OutputInner::File { writer, .. } => {
+                lock(writer).get_ref().is_terminal()
+            }
+            // The compressed data is checked when it is written to the sink.
+            OutputInner::Encoded { .. } => false,
+        })
+    }
+}
+
+// Like `&File` and `&Stdout`, writing through a shared reference is safe because the underlying
+// sink is protected by a mutex.
+impl Write for &Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_binary(buf)?;
+        with_writer!(&self.0, writer => writer.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        debug!("flushing {}", describe(self.is_tty(), self.path()));
+        with_writer!(&self.0, writer => writer.flush())
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        // Any of the slices may be written, so all of them are checked before writing.
+        for buf in bufs {
+            self.check_binary(buf)?;
+        }
+        with_writer!(&self.0, writer => writer.write_vectored(bufs))
+    }
+
+    // this method is not yet stable
+    // fn is_write_vectored(&self) -> bool {
+    //     with_writer!(&self.0, writer => writer.is_write_vectored())
+    // }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.check_binary(buf)?;
+        with_writer!(&self.0, writer => writer.write_all(buf))
+    }
+
+    // this method is not yet stable
+    // fn write_all_vectored(&mut self, bufs: &mut [io::IoSlice<'_>]) -> io::Result<()> {
+    //     with_writer!(&self.0, writer => writer.write_all_vectored(bufs))
+    // }
+}
+
+/// An output sink that only accepts standard output.
+///
+/// Parsing this type from a string only succeeds for `-`; any file path and `tty:` are rejected.
+/// Like [`Output`], clap applies the defaults installed with [`ClapFileConfig`](crate::ClapFileConfig), such as [`ClosedStdio`](crate::ClosedStdio).
+/// This is useful for arguments that currently only support standard output but may support files in the future.
+/// Since [`StdoutOnly`] dereferences to [`Output`], migrating the argument to [`Output`] later requires no code changes other than the type.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::StdoutOnly;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Output. Only `-` (standard output) is supported for now.
+///     #[arg(default_value = "-")]
+///     output: StdoutOnly,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     let mut output = args.output.lock();
+///     writeln!(&mut output, "Hello, world!")?;
+///     Ok(())
+/// }
+/// ```
+///
+/// File paths are rejected without creating the file:
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::StdoutOnly;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     output: StdoutOnly,
+/// }
+///
+/// assert!(Args::try_parse_from(["prog", "-"]).unwrap().output.is_stdout());
+/// let path = std::env::temp_dir().join(format!("clap-file-doc-stdout-only-{}.txt", std::process::id()));
+/// assert!(Args::try_parse_from(["prog".as_ref(), path.as_os_str()]).is_err());
+/// assert!(!path.exists());
+/// assert!(Args::try_parse_from(["prog", "tty:"]).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct StdoutOnly(pub(crate) Output);
+
+impl StdoutOnly {
+    /// Creates a new [`StdoutOnly`] instance that writes to standard output.
+    pub fn new() -> Self {
+        Self(Output::stdout())
+    }
+
+    /// Converts this [`StdoutOnly`] into an [`Output`].
+    pub fn into_output(self) -> Output {
+        self.0
+    }
+}
+
+impl Default for StdoutOnly {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for StdoutOnly {
+    type Target = Output;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<StdoutOnly> for Output {
+    fn from(value: StdoutOnly) -> Self {
+        value.into_output()
+    }
+}
+
+impl FromStr for StdoutOnly {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            return Ok(Self::new());
+        }
+        Err(error::new(
+            ErrorKind::InvalidInput,
+            "only standard output (`-`) is supported",
+        ))
+    }
+}
+
+impl Write for StdoutOnly {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.0.write_all(buf)
+    }
+}
+
+/// An output sink that appends to files instead of truncating them.
+///
+/// Parsing this type from a string opens the file with [`Output::append`], which is useful for log-style tools.
+/// As with [`Output`], `-` writes to standard output and `tty:` writes to the terminal.
+/// clap parses the value like an [`Output`], applying the defaults installed with [`ClapFileConfig`](crate::ClapFileConfig)
+/// and accepting non-UTF-8 paths, except that files are never written atomically, which would discard their contents.
+/// Since [`OutputAppend`] dereferences to [`Output`], the same `lock()` and [`Write`] API is available.
 ///
 /// # Examples
 ///
@@ -19,149 +1795,206 @@ fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
 /// use std::io::{self, Write as _};
 ///
 /// use clap::Parser as _;
-/// use clap_file::Output;
+/// use clap_file::OutputAppend;
 ///
 /// #[derive(Debug, clap::Parser)]
 /// struct Args {
-///     /// output file. If not provided, reads from standard output.
-///     output: Output,
+///     /// Log file. If `-` is specified, writes to standard output.
+///     #[arg(long, default_value = "-")]
+///     log: OutputAppend,
 /// }
 ///
 /// fn main() -> io::Result<()> {
 ///     let args = Args::parse();
-///     let mut output = args.output.lock();
-///     writeln!(&mut output, "Hello, world!")?;
+///     writeln!(args.log.lock(), "started")?;
+///     Ok(())
+/// }
+/// ```
+///
+/// The installed defaults apply to the parsed files:
+///
+/// ```rust
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::{ClapFileConfig, OutputAppend};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     #[arg(long)]
+///     log: OutputAppend,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     ClapFileConfig::new().create_dirs(true).atomic(true).install();
+///     let dir = std::env::temp_dir().join(format!("clap-file-doc-append-{}", std::process::id()));
+///     let path = dir.join("logs/app.log");
+///     for message in ["started", "stopped"] {
+///         let args = Args::parse_from(["prog".as_ref(), "--log".as_ref(), path.as_os_str()]);
+///         writeln!(args.log.lock(), "{message}")?;
+///     }
+///     assert_eq!(std::fs::read_to_string(&path)?, "started\nstopped\n");
+///     # ClapFileConfig::reset();
+///     # std::fs::remove_dir_all(&dir)?;
 ///     Ok(())
 /// }
 /// ```
-
-// This struct should not implement `Clone`, but clap-derive requires Clone [1].
-// So, I added `Clone` to the struct and wrap `File` with `Arc` and `Mutex`.
-// This is not the best way to handle this, but it works for now.
-//
-// [1]: https://github.com/clap-rs/clap/issues/4286
 #[derive(Debug, Clone)]
-pub struct Output(OutputInner);
+pub struct OutputAppend(pub(crate) Output);
 
-#[derive(Debug, Clone)]
-enum OutputInner {
-    Stdout,
-    File {
-        path: Arc<PathBuf>,
-        writer: Arc<Mutex<LineWriter<File>>>,
-    },
+impl OutputAppend {
+    /// Opens a file at the given path in append mode and creates a new [`OutputAppend`] instance that writes to it.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        Output::append(path).map(Self)
+    }
+
+    /// Converts this [`OutputAppend`] into an [`Output`].
+    pub fn into_output(self) -> Output {
+        self.0
+    }
 }
 
-impl Output {
-    /// Creates a new [`Output`] instance that writes to standard output.
-    pub fn stdout() -> Self {
-        Self(OutputInner::Stdout)
+impl Deref for OutputAppend {
+    type Target = Output;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
+}
 
-    /// Creates a file at the given path and creates a new [`Output`] instance that writes to it.
-    pub fn create(path: PathBuf) -> io::Result<Self> {
-        let path = Arc::new(path);
-        let file = File::create(&*path)?;
-        let writer = Arc::new(Mutex::new(LineWriter::new(file)));
-        Ok(Self(OutputInner::File { path, writer }))
+impl From<OutputAppend> for Output {
+    fn from(value: OutputAppend) -> Self {
+        value.into_output()
     }
+}
 
-    /// Returns `true` if this [`Output`] writes to standard output.
-    pub fn is_stdout(&self) -> bool {
-        matches!(self.0, OutputInner::Stdout)
+impl FromStr for OutputAppend {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" {
+            return Ok(Self(Output::stdout()));
+        }
+        if s == "tty:" {
+            return Output::tty().map(Self);
+        }
+        Self::open(PathBuf::from(s))
     }
+}
 
-    /// Returns `true` if this [`Output`] writes to a file.
+impl Write for OutputAppend {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
 
-    pub fn is_file(&self) -> bool {
-        matches!(self.0, OutputInner::File { .. })
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
     }
 
-    /// Returns the path of the file this [`Output`] writes to.
-    ///
-    /// Returns `None` if this [`Output`] writes to standard output.
-    pub fn path(&self) -> Option<&Path> {
-        match &self.0 {
-            OutputInner::Stdout => None,
-            OutputInner::File { path, .. } => Some(path),
-        }
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.0.write_vectored(bufs)
     }
 
-    /// Locks this [`Output`] for writing and returns a writable guard.
-    ///
-    /// This lock is released when the returned [`LockedOutput`] instance is dropped.
-    /// The returned `LockedOutput` instance implements [`Write`] trait for writing data.
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.0.write_all(buf)
+    }
+}
 
-    pub fn lock(&self) -> LockedOutput<'_> {
-        let inner = match &self.0 {
-            OutputInner::Stdout => {
-                let writer = io::stdout().lock();
-                LockedOutputInner::Stdout { writer }
-            }
-            OutputInner::File { path, writer: file } => {
-                let writer = lock(file);
-                LockedOutputInner::File {
-                    path: Arc::clone(path),
-                    writer,
-                }
-            }
-        };
-        LockedOutput(inner)
+/// An output sink that only accepts files.
+///
+/// Parsing this type from a string fails for `-` and `tty:`, so clap reports an error if standard output or the terminal is specified.
+/// Otherwise, clap parses the value like an [`Output`], applying the defaults installed with [`ClapFileConfig`](crate::ClapFileConfig)
+/// and accepting non-UTF-8 paths.
+/// This is useful for commands where streaming output makes no sense, e.g. tools that need to seek.
+/// [`FileOutput`] dereferences to [`Output`].
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::{ClapFileConfig, FileOutput};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Output file. Standard output is not supported.
+///     output: FileOutput,
+/// }
+///
+/// assert!(Args::try_parse_from(["prog", "-"]).is_err());
+/// assert!(Args::try_parse_from(["prog", "tty:"]).is_err());
+///
+/// ClapFileConfig::new().create_dirs(true).install();
+/// let dir = std::env::temp_dir().join(format!("clap-file-doc-file-output-{}", std::process::id()));
+/// let path = dir.join("out/report.txt");
+/// let args = Args::try_parse_from(["prog".as_ref(), path.as_os_str()]).unwrap();
+/// assert_eq!(args.output.path(), path);
+/// # ClapFileConfig::reset();
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileOutput(pub(crate) Output);
+
+impl FileOutput {
+    /// Creates a file at the given path and creates a new [`FileOutput`] instance that writes to it.
+    pub fn create(path: PathBuf) -> io::Result<Self> {
+        Output::create(path).map(Self)
+    }
+
+    /// Returns the path of the file this [`FileOutput`] writes to.
+    pub fn path(&self) -> &Path {
+        self.0.path().expect("file output must have a path")
+    }
+
+    /// Converts this [`FileOutput`] into an [`Output`].
+    pub fn into_output(self) -> Output {
+        self.0
     }
 }
 
-impl FromStr for Output {
+impl Deref for FileOutput {
+    type Target = Output;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<FileOutput> for Output {
+    fn from(value: FileOutput) -> Self {
+        value.into_output()
+    }
+}
+
+impl FromStr for FileOutput {
     type Err = io::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s == "-" {
-            return Ok(Self::stdout());
+        if s == "-" || s == "tty:" {
+            return Err(error::new(
+                ErrorKind::Unsupported,
+                "standard output (`-`) and the terminal (`tty:`) are not supported; specify a file",
+            ));
         }
         Self::create(PathBuf::from(s))
     }
 }
 
-macro_rules! with_writer {
-    ($inner:expr, $var:ident => $e:expr) => {
-        match $inner {
-            OutputInner::Stdout => {
-                let mut $var = io::stdout();
-                $e
-            }
-            OutputInner::File { writer, .. } => {
-                let mut $var = lock(writer);
-                $e
-            }
-        }
-    };
-}
-
-impl Write for Output {
+impl Write for FileOutput {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        with_writer!(&self.0, writer => writer.write(buf))
+        self.0.write(buf)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        with_writer!(&self.0, writer => writer.flush())
+        self.0.flush()
     }
 
     fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
-        with_writer!(&self.0, writer => writer.write_vectored(bufs))
+        self.0.write_vectored(bufs)
     }
 
-    // this method is not yet stable
-    // fn is_write_vectored(&self) -> bool {
-    //     with_writer!(&self.0, writer => writer.is_write_vectored())
-    // }
-
     fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
-        with_writer!(&self.0, writer => writer.write_all(buf))
+        self.0.write_all(buf)
     }
-
-    // this method is not yet stable
-    // fn write_all_vectored(&mut self, bufs: &mut [io::IoSlice<'_>]) -> io::Result<()> {
-    //     with_writer!(&self.0, writer => writer.write_all_vectored(bufs))
-    // }
 }
 
 /// A locked output sink that can be written to.
@@ -169,24 +2002,209 @@ impl Write for Output {
 pub struct LockedOutput<'a>(LockedOutputInner<'a>);
 
 impl LockedOutput<'_> {
+    /// Returns the number of bytes that have been written to this [`LockedOutput`] but not yet flushed.
+    ///
+    /// Returns `None` if this [`LockedOutput`] writes to standard output or is compressed, whose buffer is not accessible.
+    pub fn pending(&self) -> Option<usize> {
+        match &self.0 {
+            LockedOutputInner::Stdout { .. } => None,
+            #[cfg(feature = "test-util")]
+            LockedOutputInner::InjectedStdout { .. } => None,
+            LockedOutputInner::RawStdout { writer }
+            | LockedOutputInner::Tty { writer }
+            | LockedOutputInner::File { writer, .. } => Some(writer.buffer().len()),
+            LockedOutputInner::Encoded { .. } => None,
+        }
+    }
+
     /// Returns `true` if this [`LockedOutput`] writes to standard output.
     pub fn is_stdin(&self) -> bool {
-        matches!(self.0, LockedOutputInner::Stdout { .. })
+        match &self.0 {
+            LockedOutputInner::Stdout { .. } | LockedOutputInner::RawStdout { .. } => true,
+            #[cfg(feature = "test-util")]
+            LockedOutputInner::InjectedStdout { .. } => true,
+            LockedOutputInner::Tty { .. } | LockedOutputInner::File { .. } => false,
+            LockedOutputInner::Encoded { sink, .. } => sink.is_stdout(),
+        }
+    }
+
+    /// Returns `true` if this [`LockedOutput`] writes to the controlling terminal.
+    pub fn is_tty(&self) -> bool {
+        match &self.0 {
+            LockedOutputInner::Tty { .. } => true,
+            LockedOutputInner::Encoded { sink, .. } => sink.is_tty(),
+            _ => false,
+        }
     }
 
     /// Returns `true` if this [`LockedOutput`] writes to a file.
     pub fn is_file(&self) -> bool {
-        matches!(self.0, LockedOutputInner::File { .. })
+        match &self.0 {
+            LockedOutputInner::File { .. } => true,
+            LockedOutputInner::Encoded { sink, .. } => sink.is_file(),
+            _ => false,
+        }
     }
 
     /// Returns the path of the file this [`LockedOutput`] writes to.
     ///
-    /// Returns `None` if this [`LockedOutput`] writes to standard output.
+    /// Returns `None` if this [`LockedOutput`] writes to standard output or the terminal.
     pub fn path(&self) -> Option<&Path> {
         match &self.0 {
-            LockedOutputInner::Stdout { .. } => None,
+            LockedOutputInner::Stdout { .. }
+            | LockedOutputInner::RawStdout { .. }
+            | LockedOutputInner::Tty { .. } => None,
+            #[cfg(feature = "test-util")]
+            LockedOutputInner::InjectedStdout { .. } => None,
             LockedOutputInner::File { path, .. } => Some(path),
+            LockedOutputInner::Encoded { sink, .. } => sink.path(),
+        }
+    }
+
+    /// Flushes the buffered data and reports any error, then releases the lock.
+    ///
+    /// Dropping a [`LockedOutput`] does not flush it, and the data is flushed when the [`Output`] is dropped,
+    /// ignoring errors. Call this method to surface write errors, such as a full disk.
+    /// If the output was created with [`OutputBuilder::sync_on_close`], the file is also synchronized to the storage device.
+    /// Compressed outputs write the end of the compressed stream before the underlying output is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap_file::Output;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-close-{}", std::process::id()));
+    ///     let output = Output::create(path.clone())?;
+    ///     let mut locked = output.lock();
+    ///     writeln!(locked, "Hello, world!")?;
+    ///     locked.close()?;
+    ///     assert_eq!(std::fs::read_to_string(&path)?, "Hello, world!\n");
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn close(mut self) -> io::Result<()> {
+        match &mut self.0 {
+            LockedOutputInner::File { writer, .. } => writer.close()?,
+            LockedOutputInner::Encoded { sink, writer } => {
+                writer.finish()?;
+                sink.lock().close()?;
+            }
+            _ => self.flush()?,
         }
+        debug!("closing {}", describe(self.is_tty(), self.path()));
+        Ok(())
+    }
+
+    /// Flushes the buffered data and records the current position in the output.
+    ///
+    /// If `sync` is `true` and this [`LockedOutput`] writes to a file, the file data is also synchronized to the storage device
+    /// with [`File::sync_data`], so that everything written before the checkpoint survives a crash.
+    /// The returned [`Checkpoint`] can be logged by long-running exporters to resume after a crash.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io::{self, Write as _};
+    ///
+    /// use clap_file::Output;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-checkpoint-{}", std::process::id()));
+    ///     let output = Output::create(path.clone())?;
+    ///     let mut output = output.lock();
+    ///     write!(output, "first record")?;
+    ///     let checkpoint = output.checkpoint(true)?;
+    ///     assert_eq!(checkpoint.offset(), Some(12));
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn checkpoint(&mut self, sync: bool) -> io::Result<Checkpoint> {
+        self.flush()?;
+        let offset = match &mut self.0 {
+            LockedOutputInner::File { writer, .. } => {
+                let mut file = writer.get_ref();
+                if sync {
+                    file.sync_data()?;
+                }
+                Some(file.stream_position()?)
+            }
+            LockedOutputInner::Encoded { sink, .. } => {
+                sink.lock().checkpoint(sync)?;
+                None
+            }
+            _ => None,
+        };
+        Ok(Checkpoint { offset })
+    }
+
+    /// Serializes `value` as compact JSON on a single line, followed by a newline.
+    ///
+    /// Writing a value per call produces JSON Lines (NDJSON). Unlike [`Output::write_json`], this does not flush the output.
+    #[cfg(feature = "serde")]
+    pub fn write_json<T>(&mut self, value: &T) -> io::Result<()>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        let mut json = serde_json::to_vec(value).map_err(serialize_error)?;
+        json.push(b'\n');
+        self.write_all(&json)
+    }
+
+    /// Serializes `value` as indented JSON, followed by a newline.
+    ///
+    /// Unlike [`Output::write_json_pretty`], this does not flush the output.
+    #[cfg(feature = "serde")]
+    pub fn write_json_pretty<T>(&mut self, value: &T) -> io::Result<()>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        let mut json = serde_json::to_vec_pretty(value).map_err(serialize_error)?;
+        json.push(b'\n');
+        self.write_all(&json)
+    }
+
+    /// Serializes `value` as a YAML document.
+    ///
+    /// Unlike [`Output::write_yaml`], this does not flush the output.
+    #[cfg(feature = "yaml")]
+    pub fn write_yaml<T>(&mut self, value: &T) -> io::Result<()>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        let yaml = serde_yaml::to_string(value).map_err(serialize_error)?;
+        self.write_all(yaml.as_bytes())
+    }
+}
+
+// Values are serialized into memory before being written, so that a value that fails to serialize leaves nothing in the output.
+#[cfg(feature = "serde")]
+fn serialize_error(e: impl std::fmt::Display) -> io::Error {
+    error::new(
+        ErrorKind::InvalidInput,
+        format!("failed to serialize value: {e}"),
+    )
+}
+
+/// A position in an output recorded by [`LockedOutput::checkpoint`].
+///
+/// All data written before the checkpoint has been flushed (and synchronized, if requested) when it is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+    offset: Option<u64>,
+}
+
+impl Checkpoint {
+    /// Returns the byte offset in the output file at the checkpoint.
+    ///
+    /// Returns `None` if the output is standard output or the terminal, whose position is not known,
+    /// or if the output is compressed.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
     }
 }
 
@@ -195,9 +2213,23 @@ enum LockedOutputInner<'a> {
     Stdout {
         writer: io::StdoutLock<'a>,
     },
+    #[cfg(feature = "test-util")]
+    InjectedStdout {
+        writer: MutexGuard<'a, Vec<u8>>,
+    },
+    RawStdout {
+        writer: MutexGuard<'a, FileWriter>,
+    },
+    Tty {
+        writer: MutexGuard<'a, FileWriter>,
+    },
     File {
         path: Arc<PathBuf>,
-        writer: MutexGuard<'a, LineWriter<File>>,
+        writer: MutexGuard<'a, FileWriter>,
+    },
+    Encoded {
+        sink: &'a Output,
+        writer: MutexGuard<'a, Encoder>,
     },
 }
 
@@ -208,7 +2240,18 @@ macro_rules! with_locked_writer {
                 let $var = writer;
                 $e
             }
-            LockedOutputInner::File { writer, .. } => {
+            #[cfg(feature = "test-util")]
+            LockedOutputInner::InjectedStdout { writer } => {
+                let $var = writer;
+                $e
+            }
+            LockedOutputInner::RawStdout { writer }
+            | LockedOutputInner::Tty { writer }
+            | LockedOutputInner::File { writer, .. } => {
+                let $var = writer;
+                $e
+            }
+            LockedOutputInner::Encoded { writer, .. } => {
                 let $var = writer;
                 $e
             }
@@ -216,16 +2259,39 @@ macro_rules! with_locked_writer {
     };
 }
 
+impl LockedOutput<'_> {
+    /// Reports a warning or applies the binary guard if `buf` looks binary and this [`LockedOutput`] writes to a terminal.
+    fn check_binary(&self, buf: &[u8]) -> io::Result<()> {
+        warning::check_binary(buf, || match &self.0 {
+            LockedOutputInner::Stdout { writer } => writer.is_terminal(),
+            #[cfg(feature = "test-util")]
+            LockedOutputInner::InjectedStdout { .. } => false,
+            LockedOutputInner::Tty { .. } => true,
+            LockedOutputInner::RawStdout { writer } | LockedOutputInner::File { writer, .. } => {
+                writer.get_ref().is_terminal()
+            }
+            // The compressed data is checked when it is written to the sink.
+            LockedOutputInner::Encoded { .. } => false,
+        })
+    }
+}
+
 impl Write for LockedOutput<'_> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_binary(buf)?;
         with_locked_writer!(&mut self.0, writer => writer.write(buf))
     }
 
     fn flush(&mut self) -> io::Result<()> {
+        debug!("flushing {}", describe(self.is_tty(), self.path()));
         with_locked_writer!(&mut self.0, writer => writer.flush())
     }
 
     fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        // Any of the slices may be written, so all of them are checked before writing.
+        for buf in bufs {
+            self.check_binary(buf)?;
+        }
         with_locked_writer!(&mut self.0, writer => writer.write_vectored(bufs))
     }
 
@@ -235,6 +2301,7 @@ impl Write for LockedOutput<'_> {
     // }
 
     fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.check_binary(buf)?;
         with_locked_writer!(&mut self.0, writer => writer.write_all(buf))
     }
 
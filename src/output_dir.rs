@@ -0,0 +1,488 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write as _},
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{error, logging::debug, warning, ErrorKind, Output, OutputBuilder};
+
+#[track_caller]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Specifies what [`OutputDir::create`] does when the generated file name collides with an existing file
+/// or a file created earlier by the same [`OutputDir`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum CollisionPolicy {
+    /// Truncates and overwrites the existing file.
+    #[default]
+    Overwrite,
+    /// Fails with an error of kind [`io::ErrorKind::AlreadyExists`].
+    Error,
+    /// Appends a numeric suffix to the file stem (`name-1.ext`, `name-2.ext`, ...) until the name is unused.
+    Suffix,
+    /// Skips the file; [`OutputDir::create`] returns `Ok(None)`.
+    Skip,
+}
+
+/// Represents an output directory in which a tool writes multiple generated files.
+///
+/// When parsed from a string, the directory is created if it does not exist.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::OutputDir;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Output directory.
+///     out_dir: OutputDir,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     for name in ["a.txt", "b.txt"] {
+///         if let Some(output) = args.out_dir.create(name)? {
+///             writeln!(output.lock(), "{name}")?;
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OutputDir {
+    path: Arc<PathBuf>,
+    collision_policy: CollisionPolicy,
+    output_builder: OutputBuilder,
+    created: Arc<Mutex<HashSet<PathBuf>>>,
+    pool: Option<Arc<WritePool>>,
+}
+
+impl OutputDir {
+    /// Creates the directory at the given path (if it does not exist) and creates a new [`OutputDir`] instance for it.
+    pub fn create_dir(path: PathBuf) -> io::Result<Self> {
+        Self::builder().open(path)
+    }
+
+    /// Creates a new [`OutputDirBuilder`] to configure an [`OutputDir`].
+    pub fn builder() -> OutputDirBuilder {
+        OutputDirBuilder::new()
+    }
+
+    /// Returns the path of this directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the collision policy of this directory.
+    pub fn collision_policy(&self) -> CollisionPolicy {
+        self.collision_policy
+    }
+
+    /// Creates a file with the given name in this directory and returns an [`Output`] that writes to it.
+    ///
+    /// If the name collides with an existing file or a file created earlier by this [`OutputDir`],
+    /// the collision policy decides what happens. Returns `Ok(None)` if the file is skipped.
+    ///
+    /// `name` must be a relative path below the directory; absolute paths and paths with `..` components
+    /// are rejected with an error of kind [`io::ErrorKind::InvalidInput`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// use clap_file::OutputDir;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-doc-out-dir-{}", std::process::id()));
+    ///     let out_dir = OutputDir::create_dir(path.clone())?;
+    ///     assert!(out_dir.create("index.html")?.is_some());
+    ///
+    ///     let e = out_dir.create("../index.html").unwrap_err();
+    ///     assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    ///     assert!(out_dir.create(std::env::temp_dir().join("index.html")).is_err());
+    ///     # std::fs::remove_dir_all(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn create(&self, name: impl AsRef<Path>) -> io::Result<Option<Output>> {
+        let output = self.open_new(name.as_ref(), |path| self.output_builder.open(path))?;
+        Ok(output.map(|(_, output)| output))
+    }
+
+    /// Writes `data` to a file with the given name in this directory and returns the path of the file.
+    ///
+    /// The file name is checked and resolved with the collision policy in the same way as [`OutputDir::create`],
+    /// and `None` is returned if the file is skipped.
+    /// If the directory has worker threads (see [`OutputDirBuilder::workers`]), the file is opened, written,
+    /// and closed on a worker thread, and this method returns as soon as the write is queued;
+    /// call [`OutputDir::wait`] to wait for the writes and collect their errors.
+    ///
+    /// **Errors of queued writes are only returned by [`OutputDir::wait`].** If the directory is dropped
+    /// without calling it, the queued files are still written, but a failure is only reported as
+    /// [`Warning::WriteErrorDiscarded`](crate::Warning::WriteErrorDiscarded) to the warning handler.
+    ///
+    /// Otherwise, the file is written before this method returns.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::{io, num::NonZeroUsize};
+    ///
+    /// use clap_file::OutputDir;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let out_dir = OutputDir::builder()
+    ///         .workers(NonZeroUsize::new(8).unwrap())
+    ///         .open("out".into())?;
+    ///     for i in 0..10000 {
+    ///         out_dir.write_file(format!("{i}.txt"), format!("{i}\n"))?;
+    ///     }
+    ///     out_dir.wait()
+    /// }
+    /// ```
+    pub fn write_file(
+        &self,
+        name: impl AsRef<Path>,
+        data: impl Into<Vec<u8>>,
+    ) -> io::Result<Option<PathBuf>> {
+        let data = data.into();
+        let Some(pool) = &self.pool else {
+            let written = self.open_new(name.as_ref(), |path| {
+                write_data(&self.output_builder, path, &data)
+            })?;
+            return Ok(written.map(|(path, ())| path));
+        };
+        let Some(path) = self.reserve(name.as_ref())? else {
+            return Ok(None);
+        };
+        pool.submit(Job {
+            path: path.clone(),
+            data,
+        });
+        Ok(Some(path))
+    }
+
+    /// Waits until all files queued by [`OutputDir::write_file`] have been written.
+    ///
+    /// Returns the first error that occurred on the worker threads since the last call, if any.
+    /// Returns immediately if the directory has no worker threads.
+    pub fn wait(&self) -> io::Result<()> {
+        match &self.pool {
+            Some(pool) => pool.wait(),
+            None => Ok(()),
+        }
+    }
+
+    /// Reserves the path of a new file with the given name and calls `open` with it.
+    ///
+    /// With [`CollisionPolicy::Suffix`], if the file was created by another process after the path was chosen,
+    /// the next suffix is tried, so that the file is never overwritten.
+    fn open_new<T>(
+        &self,
+        name: &Path,
+        open: impl Fn(PathBuf) -> io::Result<T>,
+    ) -> io::Result<Option<(PathBuf, T)>> {
+        loop {
+            let Some(path) = self.reserve(name)? else {
+                return Ok(None);
+            };
+            match open(path.clone()) {
+                Ok(value) => return Ok(Some((path, value))),
+                // The path stays reserved, as the file exists now.
+                Err(e)
+                    if self.collision_policy == CollisionPolicy::Suffix
+                        && e.kind() == io::ErrorKind::AlreadyExists =>
+                {
+                    debug!(
+                        "`{}` was created concurrently, trying another name",
+                        path.display()
+                    );
+                }
+                Err(e) => {
+                    lock(&self.created).remove(&path);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Resolves the path of a new file with the given name according to the collision policy, and
+    /// records it as created. Returns `None` if the file is skipped.
+    fn reserve(&self, name: &Path) -> io::Result<Option<PathBuf>> {
+        let is_relative = name.components().next().is_some()
+            && name
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !is_relative {
+            return Err(error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "output file name `{}` must be a relative path inside the output directory",
+                    name.display()
+                ),
+            ));
+        }
+        let mut created = lock(&self.created);
+        let path = self.path.join(name);
+        let collides = |path: &Path| created.contains(path) || path.exists();
+        let path = if collides(&path) {
+            match self.collision_policy {
+                CollisionPolicy::Overwrite => path,
+                CollisionPolicy::Error => {
+                    return Err(error::new(
+                        ErrorKind::AlreadyExists,
+                        format!("output file `{}` already exists", path.display()),
+                    ))
+                }
+                CollisionPolicy::Suffix => (1..)
+                    .map(|n| with_suffix(&path, n))
+                    .find(|path| !collides(path))
+                    .expect("infinite iterator"),
+                CollisionPolicy::Skip => return Ok(None),
+            }
+        } else {
+            path
+        };
+        created.insert(path.clone());
+        Ok(Some(path))
+    }
+}
+
+/// A file to be written by [`OutputDir::write_file`].
+#[derive(Debug)]
+struct Job {
+    path: PathBuf,
+    data: Vec<u8>,
+}
+
+impl Job {
+    fn run(self, builder: &OutputBuilder) -> io::Result<()> {
+        write_data(builder, self.path, &self.data)
+    }
+}
+
+fn write_data(builder: &OutputBuilder, path: PathBuf, data: &[u8]) -> io::Result<()> {
+    let output = builder.open(path)?;
+    let mut output = output.lock();
+    output.write_all(data)?;
+    output.flush()
+}
+
+/// Worker threads that write the files queued by [`OutputDir::write_file`].
+#[derive(Debug)]
+struct WritePool {
+    // `None` after the pool starts shutting down, which makes the workers exit.
+    sender: Option<SyncSender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    state: Arc<(Mutex<PoolState>, Condvar)>,
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    pending: usize,
+    // The first error since the last `wait`, with the path of the file that failed.
+    error: Option<(PathBuf, io::Error)>,
+}
+
+impl WritePool {
+    fn new(workers: NonZeroUsize, builder: &OutputBuilder) -> Self {
+        // The queue is bounded so that a fast producer cannot buffer an unbounded amount of data.
+        let (sender, receiver) = mpsc::sync_channel(workers.get());
+        let receiver = Arc::new(Mutex::new(receiver));
+        let state = Arc::new((Mutex::new(PoolState::default()), Condvar::new()));
+        let workers = (0..workers.get())
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                let state = Arc::clone(&state);
+                let builder = builder.clone();
+                thread::spawn(move || Self::work(&receiver, &state, &builder))
+            })
+            .collect();
+        Self {
+            sender: Some(sender),
+            workers,
+            state,
+        }
+    }
+
+    fn work(
+        receiver: &Mutex<Receiver<Job>>,
+        state: &(Mutex<PoolState>, Condvar),
+        builder: &OutputBuilder,
+    ) {
+        // Release the receiver lock before running the job, so that other workers can take jobs.
+        while let Ok(job) = { lock(receiver).recv() } {
+            let path = job.path.clone();
+            // A panicking job must still be counted as done, or `wait` would never return.
+            let result =
+                panic::catch_unwind(AssertUnwindSafe(|| job.run(builder))).unwrap_or_else(|_| {
+                    Err(error::new(
+                        ErrorKind::Other,
+                        format!("writing `{}` panicked", path.display()),
+                    ))
+                });
+            let (state, done) = state;
+            let mut state = lock(state);
+            if let Err(e) = result {
+                debug!("failed to write `{}`: {e}", path.display());
+                state.error.get_or_insert((path, e));
+            }
+            state.pending -= 1;
+            done.notify_all();
+        }
+    }
+
+    fn submit(&self, job: Job) {
+        lock(&self.state.0).pending += 1;
+        let sender = self.sender.as_ref().expect("pool is not shut down");
+        sender
+            .send(job)
+            .expect("workers exit only after the pool is shut down");
+    }
+
+    fn wait(&self) -> io::Result<()> {
+        let (state, done) = &*self.state;
+        let mut state = lock(state);
+        while state.pending > 0 {
+            state = done.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.error.take().map_or(Ok(()), |(_, e)| Err(e))
+    }
+}
+
+impl Drop for WritePool {
+    fn drop(&mut self) {
+        // Let the workers finish the queued files before exiting.
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+        if let Some((path, e)) = lock(&self.state.0).error.take() {
+            debug!(
+                "discarding the error of writing `{}` that was not collected by `wait`: {e}",
+                path.display()
+            );
+            warning::write_error_discarded(&path, &e);
+        }
+    }
+}
+
+fn with_suffix(path: &Path, n: u32) -> PathBuf {
+    let mut file_name = path.file_stem().unwrap_or_default().to_owned();
+    file_name.push(format!("-{n}"));
+    if let Some(ext) = path.extension() {
+        file_name.push(".");
+        file_name.push(ext);
+    }
+    path.with_file_name(file_name)
+}
+
+impl FromStr for OutputDir {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::create_dir(PathBuf::from(s))
+    }
+}
+
+/// A builder for creating an [`OutputDir`] with custom options.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io;
+///
+/// use clap_file::{CollisionPolicy, OutputDir};
+///
+/// fn main() -> io::Result<()> {
+///     let out_dir = OutputDir::builder()
+///         .collision_policy(CollisionPolicy::Suffix)
+///         .open("out".into())?;
+///     let first = out_dir.create("page.html")?; // out/page.html
+///     let second = out_dir.create("page.html")?; // out/page-1.html
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OutputDirBuilder {
+    collision_policy: CollisionPolicy,
+    output_builder: OutputBuilder,
+    workers: Option<NonZeroUsize>,
+}
+
+impl OutputDirBuilder {
+    /// Creates a new [`OutputDirBuilder`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets what to do when a generated file name collides.
+    ///
+    /// Defaults to [`CollisionPolicy::Overwrite`].
+    pub fn collision_policy(&mut self, policy: CollisionPolicy) -> &mut Self {
+        self.collision_policy = policy;
+        self
+    }
+
+    /// Sets the [`OutputBuilder`] used to create files in the directory.
+    pub fn output_builder(&mut self, builder: OutputBuilder) -> &mut Self {
+        self.output_builder = builder;
+        self
+    }
+
+    /// Sets the number of worker threads that write the files queued by [`OutputDir::write_file`].
+    ///
+    /// At most `workers` files are opened and written concurrently, so that the caller does not wait for them.
+    /// Handing files over to the workers has a cost of its own: on a local disk, writing many small files on the
+    /// calling thread is usually faster, and workers mainly help when opening and closing files is slow, such as
+    /// on network file systems. Measure before enabling this.
+    /// If not set, files are written on the calling thread.
+    ///
+    /// **Errors of the workers are only returned by [`OutputDir::wait`]**, which must be called before the
+    /// directory is dropped; an error that is not collected is only reported as
+    /// [`Warning::WriteErrorDiscarded`](crate::Warning::WriteErrorDiscarded) to the warning handler.
+    pub fn workers(&mut self, workers: NonZeroUsize) -> &mut Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    /// Creates the directory at the given path (if it does not exist) with the options specified by this builder.
+    pub fn open(&self, path: PathBuf) -> io::Result<OutputDir> {
+        fs::create_dir_all(&path)?;
+        let mut output_builder = self.output_builder.clone();
+        if matches!(
+            self.collision_policy,
+            CollisionPolicy::Error | CollisionPolicy::Suffix
+        ) {
+            // Checking for collisions and creating the file are not atomic, so a file created by another process
+            // in between must not be overwritten.
+            output_builder.create_new(true);
+        }
+        Ok(OutputDir {
+            path: Arc::new(path),
+            collision_policy: self.collision_policy,
+            pool: self
+                .workers
+                .map(|workers| Arc::new(WritePool::new(workers, &output_builder))),
+            output_builder,
+            created: Arc::default(),
+        })
+    }
+}
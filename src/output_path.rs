@@ -0,0 +1,79 @@
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
+
+/// Rules for deriving a default output path from an input path by transforming its extensions.
+///
+/// This reduces boilerplate in converter tools, e.g. a decompressor that writes `data.json.gz` to `data.json`.
+///
+/// The rules are applied in the following order:
+///
+/// 1. If the file name ends with one of the extensions registered by [`strip_extension`](Self::strip_extension), the first matching one is removed.
+/// 2. If an extension is registered by [`add_extension`](Self::add_extension), it is appended.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+///
+/// use clap_file::OutputPathRule;
+///
+/// let rule = OutputPathRule::new().strip_extension("gz").strip_extension("zst");
+/// assert_eq!(rule.apply(Path::new("data.json.gz")), Path::new("data.json"));
+/// assert_eq!(rule.apply(Path::new("data.json.zst")), Path::new("data.json"));
+///
+/// let rule = OutputPathRule::new().strip_extension("yaml").add_extension("json");
+/// assert_eq!(rule.apply(Path::new("config.yaml")), Path::new("config.json"));
+/// assert_eq!(rule.apply(Path::new("config")), Path::new("config.json"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OutputPathRule {
+    strip: Vec<OsString>,
+    add: Option<OsString>,
+}
+
+impl OutputPathRule {
+    /// Creates a new [`OutputPathRule`] that does not transform paths.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`OutputPathRule`] that strips the extensions of the compression formats (`gz`, `zst`, `xz`, and `bz2`).
+    pub fn decompress() -> Self {
+        Self::new()
+            .strip_extension("gz")
+            .strip_extension("zst")
+            .strip_extension("xz")
+            .strip_extension("bz2")
+    }
+
+    /// Registers an extension (without the leading `.`) to strip from the input path.
+    pub fn strip_extension(mut self, extension: impl Into<OsString>) -> Self {
+        self.strip.push(extension.into());
+        self
+    }
+
+    /// Sets the extension (without the leading `.`) to append to the output path.
+    pub fn add_extension(mut self, extension: impl Into<OsString>) -> Self {
+        self.add = Some(extension.into());
+        self
+    }
+
+    /// Derives an output path from `input` by applying the rules.
+    pub fn apply(&self, input: &Path) -> PathBuf {
+        let mut path = input.to_owned();
+        if let Some(ext) = path.extension() {
+            if self.strip.iter().any(|strip| strip.as_os_str() == ext) {
+                path.set_extension("");
+            }
+        }
+        if let Some(add) = &self.add {
+            let mut file_name = path.file_name().map(OsStr::to_owned).unwrap_or_default();
+            file_name.push(".");
+            file_name.push(add);
+            path.set_file_name(file_name);
+        }
+        path
+    }
+}
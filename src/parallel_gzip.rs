@@ -0,0 +1,150 @@
+use std::{
+    io::{self, Write},
+    mem,
+    num::NonZeroUsize,
+    thread,
+};
+
+use crate::compression::Encode;
+
+/// The size of the uncompressed data in each gzip member, which is also the unit of work of a thread.
+///
+/// This is larger than the 128 KiB blocks of `pigz`, since members do not share their history and each one
+/// starts compressing without a dictionary.
+const BLOCK_SIZE: usize = 1024 * 1024;
+
+/// A gzip encoder that compresses blocks of data on multiple threads, like `pigz`.
+///
+/// Each block is compressed into an independent gzip member. Multi-member files are valid gzip files,
+/// and are decompressed by `gzip -d` and by [`Input`](crate::Input) as a whole.
+///
+/// Flushing the encoder compresses the data of the current block written so far with a sync flush, like
+/// [`flate2::write::GzEncoder`], so that it can be decompressed without ending the member. Members still end
+/// only at block boundaries and when the encoder is finished. The rest of a flushed block is compressed on
+/// the writing thread, so frequent flushes reduce the parallelism as well as the compression ratio.
+pub(crate) struct ParallelGzEncoder<W> {
+    writer: W,
+    level: flate2::Compression,
+    threads: NonZeroUsize,
+    // Full blocks that are compressed together once there is one for each thread
+    blocks: Vec<Vec<u8>>,
+    current: Vec<u8>,
+    // The member of the current block that was started by a flush, and the amount of data written to it
+    partial: Option<(flate2::write::GzEncoder<Vec<u8>>, usize)>,
+    members: usize,
+}
+
+impl<W: Write> ParallelGzEncoder<W> {
+    pub(crate) fn new(writer: W, level: flate2::Compression, threads: NonZeroUsize) -> Self {
+        Self {
+            writer,
+            level,
+            threads,
+            blocks: Vec::with_capacity(threads.get()),
+            current: Vec::with_capacity(BLOCK_SIZE),
+            partial: None,
+            members: 0,
+        }
+    }
+
+    /// Returns the amount of data in the current block.
+    fn block_len(&self) -> usize {
+        self.partial.as_ref().map_or(0, |(_, len)| *len) + self.current.len()
+    }
+
+    /// Compresses the pending blocks in parallel and writes them in order.
+    fn compress_blocks(&mut self) -> io::Result<()> {
+        let level = self.level;
+        let members = thread::scope(|scope| {
+            let handles = self
+                .blocks
+                .iter()
+                .map(|block| scope.spawn(move || compress_member(block, level)))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .collect::<io::Result<Vec<_>>>()
+        })?;
+        for member in &members {
+            self.writer.write_all(member)?;
+        }
+        self.members += members.len();
+        self.blocks.clear();
+        Ok(())
+    }
+
+    /// Ends the member of the current block, even if the block is not full.
+    fn end_block(&mut self) -> io::Result<()> {
+        let block = mem::replace(&mut self.current, Vec::with_capacity(BLOCK_SIZE));
+        match self.partial.take() {
+            // The blocks before a flushed block have already been written
+            Some((mut encoder, _)) => {
+                encoder.write_all(&block)?;
+                self.writer.write_all(&encoder.finish()?)?;
+                self.members += 1;
+            }
+            None if !block.is_empty() => self.blocks.push(block),
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+fn compress_member(block: &[u8], level: flate2::Compression) -> io::Result<Vec<u8>> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::with_capacity(block.len() / 2), level);
+    encoder.write_all(block)?;
+    encoder.finish()
+}
+
+impl<W: Write> Write for ParallelGzEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(BLOCK_SIZE - self.block_len());
+        self.current.extend_from_slice(&buf[..n]);
+        if self.block_len() == BLOCK_SIZE {
+            self.end_block()?;
+            if self.blocks.len() == self.threads.get() {
+                self.compress_blocks()?;
+            }
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.compress_blocks()?;
+        if !self.current.is_empty() {
+            let level = self.level;
+            let (encoder, len) = self.partial.get_or_insert_with(|| {
+                (
+                    flate2::write::GzEncoder::new(Vec::with_capacity(BLOCK_SIZE / 2), level),
+                    0,
+                )
+            });
+            encoder.write_all(&self.current)?;
+            encoder.flush()?;
+            *len += self.current.len();
+            self.current.clear();
+            self.writer.write_all(encoder.get_ref())?;
+            encoder.get_mut().clear();
+        }
+        self.writer.flush()
+    }
+}
+
+impl<W: Write + Send> Encode for ParallelGzEncoder<W> {
+    fn finish(&mut self) -> io::Result<()> {
+        self.end_block()?;
+        self.compress_blocks()?;
+        // An empty gzip file is not valid, so write an empty member if nothing was written
+        if self.members == 0 {
+            let member = compress_member(&[], self.level)?;
+            self.writer.write_all(&member)?;
+            self.members += 1;
+        }
+        self.writer.flush()
+    }
+}
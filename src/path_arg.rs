@@ -0,0 +1,193 @@
+use std::{
+    fs::{self, File},
+    io,
+    ops::Deref,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{error, ErrorKind, Input, Output};
+
+/// A path to an existing readable file, validated at parse time but not kept open.
+///
+/// When parsed from a string, the path must refer to a file that can be opened for reading;
+/// otherwise, clap reports an error. Unlike [`Input`], the program receives the path instead of an open handle,
+/// which is useful when the file is passed to a subprocess or opened later with custom options.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::InputPath;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Configuration file.
+///     config: InputPath,
+/// }
+///
+/// let args = Args::try_parse_from(["prog", "Cargo.toml"]).unwrap();
+/// assert_eq!(args.config.as_path(), std::path::Path::new("Cargo.toml"));
+/// assert!(Args::try_parse_from(["prog", "no-such-file"]).is_err());
+/// assert!(Args::try_parse_from(["prog", "src"]).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputPath(PathBuf);
+
+impl InputPath {
+    /// Validates that the given path refers to a readable file and creates a new [`InputPath`].
+    pub fn new(path: PathBuf) -> io::Result<Self> {
+        if fs::metadata(&path)?.is_dir() {
+            return Err(error::new(
+                ErrorKind::IsDirectory,
+                format!("input path `{}` is a directory", path.display()),
+            ));
+        }
+        File::open(&path)?;
+        Ok(Self(path))
+    }
+
+    /// Returns the path.
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Converts this [`InputPath`] into a [`PathBuf`].
+    pub fn into_path_buf(self) -> PathBuf {
+        self.0
+    }
+
+    /// Opens the file and creates a new [`Input`] instance that reads from it.
+    pub fn open(&self) -> io::Result<Input> {
+        Input::open(self.0.clone())
+    }
+}
+
+impl Deref for InputPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for InputPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<InputPath> for PathBuf {
+    fn from(value: InputPath) -> Self {
+        value.into_path_buf()
+    }
+}
+
+impl FromStr for InputPath {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(PathBuf::from(s))
+    }
+}
+
+/// A path where an output file can be created, validated at parse time but not created.
+///
+/// When parsed from a string, the parent directory of the path must exist and must not be read-only,
+/// and the path must not refer to a directory; otherwise, clap reports an error.
+/// Unlike [`Output`], the file is not created (or truncated), and the program receives the path instead of an open handle.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::OutputPath;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Report file.
+///     report: OutputPath,
+/// }
+///
+/// let args = Args::try_parse_from(["prog", "target/report.txt"]).unwrap();
+/// assert!(Args::try_parse_from(["prog", "no-such-dir/report.txt"]).is_err());
+/// assert!(Args::try_parse_from(["prog", "src"]).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutputPath(PathBuf);
+
+impl OutputPath {
+    /// Validates that an output file can be created at the given path and creates a new [`OutputPath`].
+    pub fn new(path: PathBuf) -> io::Result<Self> {
+        if path.is_dir() {
+            return Err(error::new(
+                ErrorKind::IsDirectory,
+                format!("output path `{}` is a directory", path.display()),
+            ));
+        }
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let metadata = fs::metadata(dir)?;
+        if !metadata.is_dir() {
+            return Err(error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "parent of output path `{}` is not a directory",
+                    path.display()
+                ),
+            ));
+        }
+        if metadata.permissions().readonly() {
+            return Err(error::new(
+                ErrorKind::PermissionDenied,
+                format!("directory `{}` is read-only", dir.display()),
+            ));
+        }
+        Ok(Self(path))
+    }
+
+    /// Returns the path.
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Converts this [`OutputPath`] into a [`PathBuf`].
+    pub fn into_path_buf(self) -> PathBuf {
+        self.0
+    }
+
+    /// Creates the file and creates a new [`Output`] instance that writes to it.
+    pub fn create(&self) -> io::Result<Output> {
+        Output::create(self.0.clone())
+    }
+}
+
+impl Deref for OutputPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for OutputPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<OutputPath> for PathBuf {
+    fn from(value: OutputPath) -> Self {
+        value.into_path_buf()
+    }
+}
+
+impl FromStr for OutputPath {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(PathBuf::from(s))
+    }
+}
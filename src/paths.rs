@@ -0,0 +1,170 @@
+//! Path helpers that follow the rules this crate uses when opening the paths given on the command line.
+//!
+//! This crate opens paths as given, without resolving `..` or symbolic links, so that error messages show the paths users typed.
+//! A leading `~` is only expanded when enabled with [`InputValueParser::expand_tilde`](crate::InputValueParser::expand_tilde).
+//! Applications can use these helpers to validate and display paths consistently with what will be opened.
+//! Separators are platform-specific: `/` on Unix, and both `/` and `\` on Windows.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use std::path::Path;
+//!
+//! use clap_file::paths;
+//!
+//! assert!(paths::has_trailing_separator(Path::new("out/")));
+//! assert_eq!(paths::trim_trailing_separators(Path::new("out//")), Path::new("out"));
+//! assert_eq!(paths::normalize_lexically(Path::new("a/./b/../c")), Path::new("a/c"));
+//! ```
+
+use std::{
+    borrow::Cow,
+    env, io,
+    path::{self, Component, Path, PathBuf},
+};
+
+use crate::{error, ErrorKind};
+
+/// Returns `true` if `path` ends with a path separator, e.g. `out/`.
+///
+/// Such paths refer to directories, so output paths ending with a separator are rejected unless the directory exists.
+pub fn has_trailing_separator(path: &Path) -> bool {
+    path.as_os_str()
+        .as_encoded_bytes()
+        .last()
+        .is_some_and(|&b| path::is_separator(char::from(b)))
+}
+
+/// Returns `path` without trailing path separators, e.g. `out` for `out//`.
+///
+/// Root paths, such as `/`, are returned unchanged.
+pub fn trim_trailing_separators(path: &Path) -> &Path {
+    let mut path = path;
+    while has_trailing_separator(path) {
+        // `parent` and `file_name` ignore trailing separators, so rebuild the path from its bytes.
+        let bytes = path.as_os_str().as_encoded_bytes();
+        let trimmed = &bytes[..bytes.len() - 1];
+        // SAFETY: Only an ASCII separator is removed from the end, so `trimmed` is valid encoded bytes of an `OsStr`.
+        let trimmed = Path::new(unsafe { std::ffi::OsStr::from_encoded_bytes_unchecked(trimmed) });
+        if trimmed.components().ne(path.components()) {
+            break;
+        }
+        path = trimmed;
+    }
+    path
+}
+
+/// Resolves `.` and `..` components of `path` lexically, without accessing the file system.
+///
+/// `..` removes the preceding normal component. Leading `..` components of relative paths are kept,
+/// and `..` directly under the root is removed, as the root is its own parent. An empty result is returned as `.`.
+///
+/// Unlike [`std::fs::canonicalize`], this does not follow symbolic links, so the result may refer to a different file than `path`
+/// if a component before `..` is a symbolic link. This crate never opens normalized paths for this reason;
+/// use the result only for display and comparison.
+pub fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) => {}
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+    if normalized.as_os_str().is_empty() {
+        normalized.push(".");
+    }
+    normalized
+}
+
+/// Expands a leading `~` or `~user` in `path` to the home directory of the current user or of `user`, as shells do.
+///
+/// Paths that do not start with `~` are returned unchanged, as are paths like `~foo` on platforms where other users' home directories cannot be resolved.
+/// The home directory of the current user is taken from `HOME` (`USERPROFILE` on Windows).
+/// On Unix, the home directory of `user` is looked up in `/etc/passwd`.
+///
+/// Returns an error of kind [`io::ErrorKind::NotFound`] if the home directory cannot be resolved.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(unix)] {
+/// use std::path::Path;
+///
+/// use clap_file::paths;
+///
+/// std::env::set_var("HOME", "/home/alice");
+/// assert_eq!(paths::expand_tilde(Path::new("~/notes.txt")).unwrap(), Path::new("/home/alice/notes.txt"));
+/// assert_eq!(paths::expand_tilde(Path::new("notes.txt")).unwrap(), Path::new("notes.txt"));
+/// # }
+/// ```
+pub fn expand_tilde(path: &Path) -> io::Result<Cow<'_, Path>> {
+    let mut components = path.components();
+    let Some(Component::Normal(first)) = components.next() else {
+        return Ok(Cow::Borrowed(path));
+    };
+    let Some(user) = first.to_str().and_then(|first| first.strip_prefix('~')) else {
+        return Ok(Cow::Borrowed(path));
+    };
+    let home = if user.is_empty() {
+        home_dir()?
+    } else {
+        match user_home_dir(user)? {
+            Some(home) => home,
+            None => return Ok(Cow::Borrowed(path)),
+        }
+    };
+    let mut expanded = home;
+    let rest = components.as_path();
+    if !rest.as_os_str().is_empty() {
+        expanded.push(rest);
+    }
+    if has_trailing_separator(path) {
+        // Pushing an empty path appends a separator, which `components` dropped
+        expanded.push("");
+    }
+    Ok(Cow::Owned(expanded))
+}
+
+fn home_dir() -> io::Result<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    env::var_os(var)
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| {
+            error::new(
+                ErrorKind::NotFound,
+                format!("cannot expand `~`: the home directory is unknown (`{var}` is not set)"),
+            )
+        })
+}
+
+/// Returns the home directory of `user`, or `None` if it cannot be resolved on this platform.
+#[cfg(unix)]
+fn user_home_dir(user: &str) -> io::Result<Option<PathBuf>> {
+    let passwd = std::fs::read_to_string("/etc/passwd").unwrap_or_default();
+    // Each entry is `name:password:uid:gid:gecos:home:shell`
+    let home = passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.first() == Some(&user))
+        .and_then(|fields| fields.get(5).map(PathBuf::from));
+    match home {
+        Some(home) => Ok(Some(home)),
+        None => Err(error::new(
+            ErrorKind::NotFound,
+            format!("cannot expand `~{user}`: no such user"),
+        )),
+    }
+}
+
+#[cfg(not(unix))]
+fn user_home_dir(_user: &str) -> io::Result<Option<PathBuf>> {
+    Ok(None)
+}
@@ -0,0 +1,14 @@
+//! Re-exports the commonly used types and extension traits.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use clap_file::prelude::*;
+//! ```
+
+pub use crate::{
+    binary::{BinaryReadExt, BinaryWriteExt},
+    ext::{InputExt, OutputExt},
+    FileInput, FileOutput, Input, Inputs, LockedInput, LockedOutput, Output, OutputAppend,
+    StdinOnly, StdoutOnly,
+};
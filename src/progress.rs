@@ -0,0 +1,143 @@
+use std::{
+    io::{self, BufRead, Read},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// A handle to observe how much of an input has been read.
+///
+/// The same interface is used for all sources: for files whose size is known, [`Progress::fraction`] and
+/// [`Progress::eta`] return estimates; for standard input and pipes, they return `None`, and only
+/// the bytes read so far and the throughput are available.
+///
+/// This is created by [`ProgressReader::progress`] and can be cloned and sent to another thread to display a progress bar.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    bytes: Arc<AtomicU64>,
+    total: Option<u64>,
+    start: Instant,
+}
+
+impl Progress {
+    /// Returns the number of bytes read so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of bytes, or `None` if it is unknown.
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Returns the time elapsed since reading started.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Returns the average throughput in bytes per second.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed().as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        self.bytes() as f64 / secs
+    }
+
+    /// Returns the fraction of the input that has been read, between `0.0` and `1.0`.
+    ///
+    /// Returns `None` if the total size is unknown.
+    pub fn fraction(&self) -> Option<f64> {
+        let total = self.total?;
+        if total == 0 {
+            return Some(1.0);
+        }
+        Some((self.bytes() as f64 / total as f64).min(1.0))
+    }
+
+    /// Returns the estimated time remaining, based on the average throughput.
+    ///
+    /// Returns `None` if the total size is unknown or nothing has been read yet.
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total?;
+        let throughput = self.throughput();
+        if throughput == 0.0 {
+            return None;
+        }
+        let remaining = total.saturating_sub(self.bytes());
+        Some(Duration::from_secs_f64(remaining as f64 / throughput))
+    }
+}
+
+/// A reader adapter that counts the bytes read through it.
+///
+/// This struct is created by [`Input::track_progress`](crate::Input::track_progress).
+#[derive(Debug)]
+pub struct ProgressReader<R> {
+    inner: R,
+    progress: Progress,
+}
+
+impl<R> ProgressReader<R> {
+    pub(crate) fn new(inner: R, total: Option<u64>) -> Self {
+        Self {
+            inner,
+            progress: Progress {
+                bytes: Arc::default(),
+                total,
+                start: Instant::now(),
+            },
+        }
+    }
+
+    /// Returns a [`Progress`] handle that observes this reader.
+    pub fn progress(&self) -> Progress {
+        self.progress.clone()
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes this adapter and returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn advance(&self, n: usize) {
+        self.progress.bytes.fetch_add(n as u64, Ordering::Relaxed);
+    }
+}
+
+impl<R> Read for ProgressReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.advance(n);
+        Ok(n)
+    }
+}
+
+impl<R> BufRead for ProgressReader<R>
+where
+    R: BufRead,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.advance(amt);
+        self.inner.consume(amt);
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    io::{self, Write},
+    ops::Deref,
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{LockedOutput, Output};
+
+#[track_caller]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// An event recorded by [`RecordingOutput`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordedEvent {
+    /// Bytes that were written to the output.
+    Write(Vec<u8>),
+    /// The output was flushed.
+    Flush,
+}
+
+/// An output sink that mirrors all writes into an in-memory log alongside the real [`Output`].
+///
+/// The recorded events can be retrieved after the run, which is useful for generating golden files for tests
+/// and for debugging the order of writes.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap_file::{Output, RecordingOutput};
+///
+/// fn main() -> io::Result<()> {
+///     let output = RecordingOutput::new(Output::stdout());
+///     writeln!(output.lock(), "Hello, world!")?;
+///     assert_eq!(output.recorded_bytes(), b"Hello, world!\n");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordingOutput {
+    output: Output,
+    events: Arc<Mutex<Vec<RecordedEvent>>>,
+}
+
+impl RecordingOutput {
+    /// Creates a new [`RecordingOutput`] that writes to `output` and records all writes.
+    pub fn new(output: Output) -> Self {
+        Self {
+            output,
+            events: Arc::default(),
+        }
+    }
+
+    /// Returns the events recorded so far.
+    pub fn events(&self) -> Vec<RecordedEvent> {
+        lock(&self.events).clone()
+    }
+
+    /// Returns all bytes written so far, concatenated in order.
+    pub fn recorded_bytes(&self) -> Vec<u8> {
+        lock(&self.events)
+            .iter()
+            .filter_map(|event| match event {
+                RecordedEvent::Write(bytes) => Some(bytes.as_slice()),
+                RecordedEvent::Flush => None,
+            })
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Clears the events recorded so far.
+    pub fn clear(&self) {
+        lock(&self.events).clear();
+    }
+
+    /// Converts this [`RecordingOutput`] into the underlying [`Output`].
+    pub fn into_output(self) -> Output {
+        self.output
+    }
+
+    /// Locks this [`RecordingOutput`] for writing and returns a writable guard.
+    pub fn lock(&self) -> LockedRecordingOutput<'_> {
+        LockedRecordingOutput {
+            output: self.output.lock(),
+            events: &self.events,
+        }
+    }
+
+    fn record(&self, event: RecordedEvent) {
+        lock(&self.events).push(event);
+    }
+}
+
+impl Deref for RecordingOutput {
+    type Target = Output;
+
+    fn deref(&self) -> &Self::Target {
+        &self.output
+    }
+}
+
+impl From<Output> for RecordingOutput {
+    fn from(output: Output) -> Self {
+        Self::new(output)
+    }
+}
+
+impl FromStr for RecordingOutput {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self::new)
+    }
+}
+
+impl Write for RecordingOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+}
+
+impl Write for &RecordingOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = (&self.output).write(buf)?;
+        self.record(RecordedEvent::Write(buf[..n].to_vec()));
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&self.output).flush()?;
+        self.record(RecordedEvent::Flush);
+        Ok(())
+    }
+}
+
+/// A locked [`RecordingOutput`] that can be written to.
+#[derive(Debug)]
+pub struct LockedRecordingOutput<'a> {
+    output: LockedOutput<'a>,
+    events: &'a Mutex<Vec<RecordedEvent>>,
+}
+
+impl<'a> Deref for LockedRecordingOutput<'a> {
+    type Target = LockedOutput<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.output
+    }
+}
+
+impl Write for LockedRecordingOutput<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.output.write(buf)?;
+        lock(self.events).push(RecordedEvent::Write(buf[..n].to_vec()));
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()?;
+        lock(self.events).push(RecordedEvent::Flush);
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use std::{io, num::ParseIntError, str::FromStr};
+
+use crate::{error, logging::debug, ErrorKind, Input};
+
+/// A byte offset from which an interrupted job resumes reading its input.
+///
+/// This is intended to be used as the value of a `--resume-from` option:
+/// [`ResumeFrom::apply`] skips the already-processed bytes with [`Input::skip`],
+/// seeking on regular files and discarding data on pipes.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, BufRead as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::{Input, ResumeFrom};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Byte offset to resume from.
+///     #[arg(long)]
+///     resume_from: Option<ResumeFrom>,
+///     /// Input file. If `-` is specified, reads from standard input.
+///     input: Input,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     if let Some(resume_from) = args.resume_from {
+///         resume_from.apply(&args.input)?;
+///     }
+///     for line in args.input.lock().lines() {
+///         println!("{}", line?);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ResumeFrom(u64);
+
+impl ResumeFrom {
+    /// Creates a new [`ResumeFrom`] with the given byte offset.
+    pub fn new(offset: u64) -> Self {
+        Self(offset)
+    }
+
+    /// Returns the byte offset.
+    pub fn offset(self) -> u64 {
+        self.0
+    }
+
+    /// Skips `input` to the byte offset.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::UnexpectedEof`] if the input is shorter than the offset.
+    pub fn apply(self, input: &Input) -> io::Result<()> {
+        let skipped = input.skip(self.0)?;
+        if skipped < self.0 {
+            return Err(error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "cannot resume from byte {}: input has only {skipped} bytes",
+                    self.0
+                ),
+            ));
+        }
+        debug!("resumed input from byte {}", self.0);
+        Ok(())
+    }
+}
+
+impl From<u64> for ResumeFrom {
+    fn from(offset: u64) -> Self {
+        Self(offset)
+    }
+}
+
+impl FromStr for ResumeFrom {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
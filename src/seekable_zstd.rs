@@ -0,0 +1,127 @@
+use std::{
+    io::{self, Write},
+    num::NonZeroUsize,
+};
+
+use crate::{compression::Encode, error, ErrorKind};
+
+/// The magic number of the skippable frame that holds the seek table.
+const SKIPPABLE_MAGIC: u32 = 0x184d_2a5e;
+/// The magic number at the end of the seek table.
+const SEEKABLE_MAGIC: u32 = 0x8f92_eab1;
+/// The size of the seek table footer: the number of frames, the descriptor, and the magic number.
+const FOOTER_SIZE: usize = 9;
+
+/// A Zstandard encoder that writes the [seekable format] of the Zstandard project.
+///
+/// The data is compressed into independent frames of a fixed uncompressed size, followed by a skippable frame
+/// holding the sizes of all frames, so readers can locate and decompress any frame without decompressing the ones before it.
+/// Decompressors that do not know the format skip the seek table and decompress the frames as a whole.
+/// Flushing the encoder ends the current frame.
+///
+/// [seekable format]: https://github.com/facebook/zstd/blob/dev/contrib/seekable_format/zstd_seekable_compression_format.md
+pub(crate) struct SeekableZstdEncoder<W> {
+    writer: W,
+    compressor: zstd::bulk::Compressor<'static>,
+    frame_size: usize,
+    current: Vec<u8>,
+    // The compressed and decompressed sizes of the frames written so far
+    frames: Vec<(u32, u32)>,
+}
+
+impl<W: Write> SeekableZstdEncoder<W> {
+    pub(crate) fn new(
+        writer: W,
+        level: i32,
+        frame_size: usize,
+        threads: Option<NonZeroUsize>,
+    ) -> io::Result<Self> {
+        if frame_size == 0 || u32::try_from(frame_size).is_err() {
+            return Err(error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "zstd frame size must be between 1 and {}, but {frame_size} was given",
+                    u32::MAX
+                ),
+            ));
+        }
+        let mut compressor = zstd::bulk::Compressor::new(level)?;
+        if let Some(threads) = threads.filter(|threads| threads.get() > 1) {
+            let workers = u32::try_from(threads.get()).unwrap_or(u32::MAX);
+            compressor.set_parameter(zstd::zstd_safe::CParameter::NbWorkers(workers))?;
+        }
+        Ok(Self {
+            writer,
+            compressor,
+            frame_size,
+            current: Vec::new(),
+            frames: vec![],
+        })
+    }
+
+    /// Compresses the buffered data into a frame, even if it is shorter than the frame size.
+    fn end_frame(&mut self) -> io::Result<()> {
+        if self.current.is_empty() {
+            return Ok(());
+        }
+        let frame = self.compressor.compress(&self.current)?;
+        let compressed = u32::try_from(frame.len()).map_err(|_| {
+            error::new(
+                ErrorKind::Unsupported,
+                "compressed zstd frame is too large for the seek table",
+            )
+        })?;
+        self.writer.write_all(&frame)?;
+        // The frame size is checked to fit in `u32` when the encoder is created
+        self.frames.push((compressed, self.current.len() as u32));
+        self.current.clear();
+        Ok(())
+    }
+
+    fn write_seek_table(&mut self) -> io::Result<()> {
+        let entries = self.frames.len() * 8;
+        let mut table = Vec::with_capacity(8 + entries + FOOTER_SIZE);
+        table.extend_from_slice(&SKIPPABLE_MAGIC.to_le_bytes());
+        let size = u32::try_from(entries + FOOTER_SIZE).map_err(|_| {
+            error::new(
+                ErrorKind::Unsupported,
+                "too many zstd frames for the seek table",
+            )
+        })?;
+        table.extend_from_slice(&size.to_le_bytes());
+        for (compressed, decompressed) in &self.frames {
+            table.extend_from_slice(&compressed.to_le_bytes());
+            table.extend_from_slice(&decompressed.to_le_bytes());
+        }
+        // `frames.len()` fits in `u32`, as `size` does
+        table.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        // No checksums are stored
+        table.push(0);
+        table.extend_from_slice(&SEEKABLE_MAGIC.to_le_bytes());
+        self.writer.write_all(&table)
+    }
+}
+
+impl<W: Write> Write for SeekableZstdEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.frame_size - self.current.len());
+        self.current.extend_from_slice(&buf[..n]);
+        if self.current.len() == self.frame_size {
+            self.end_frame()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.end_frame()?;
+        self.writer.flush()
+    }
+}
+
+impl<W: Write + Send> Encode for SeekableZstdEncoder<W> {
+    fn finish(&mut self) -> io::Result<()> {
+        self.end_frame()?;
+        self.write_seek_table()?;
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,33 @@
+use std::{fmt, io};
+
+/// The reason an input was not processed, reported alongside the inputs that were.
+///
+/// Skipped inputs are reported by [`DirInputs::skipped`](crate::DirInputs::skipped),
+/// `GlobInput::skipped`, and [`InputErrors::into_skipped`](crate::InputErrors::into_skipped),
+/// so that tools can account for every path they were given.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The file or directory is hidden (its name starts with `.`).
+    Hidden,
+    /// The directory is deeper than the maximum depth, so its entries were not visited.
+    MaxDepth,
+    /// The path does not refer to a regular file.
+    NotFile,
+    /// The file did not pass the [`InputFilter`](crate::InputFilter).
+    Filtered,
+    /// The input failed to open or to be processed.
+    Failed(io::Error),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hidden => write!(f, "hidden"),
+            Self::MaxDepth => write!(f, "exceeds maximum depth"),
+            Self::NotFile => write!(f, "not a regular file"),
+            Self::Filtered => write!(f, "filtered out"),
+            Self::Failed(error) => write!(f, "{error}"),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::{
+    fs, io,
+    io::Read,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+use crate::{logging::debug, temp::TempFile, Input};
+
+/// A private copy of an input, created by [`Input::snapshot`].
+///
+/// Reads from the copy give a stable view of the input, even if the original file keeps changing (e.g. live logs).
+/// The copy is removed when the [`InputSnapshot`] is dropped.
+#[derive(Debug)]
+pub struct InputSnapshot {
+    // Declared before `_temp` so that the copy is closed before it is removed.
+    input: Input,
+    source: Option<PathBuf>,
+    _temp: TempFile,
+}
+
+impl InputSnapshot {
+    pub(crate) fn new(source: &Input) -> io::Result<Self> {
+        let temp = TempFile::new()?;
+        // Compressed inputs are copied after decompression, so that the copy reads the same data.
+        match source.path().filter(|_| source.compression().is_none()) {
+            // `fs::copy` clones the file (reflink) on file systems that support it, such as Btrfs, XFS, and APFS.
+            Some(path) => {
+                fs::copy(path, temp.path())?;
+            }
+            None => {
+                io::copy(&mut source.lock(), &mut temp.file())?;
+            }
+        }
+        debug!(
+            "copied {} to `{}`",
+            source.path().map_or_else(
+                || "standard input".to_owned(),
+                |path| format!("`{}`", path.display())
+            ),
+            temp.path().display()
+        );
+        Ok(Self {
+            input: Input::open(temp.path().to_owned())?,
+            source: source.path().map(Path::to_owned),
+            _temp: temp,
+        })
+    }
+
+    /// Returns the path of the original file.
+    ///
+    /// Returns `None` if the snapshot was taken from standard input. Use [`Input::path`] (through `Deref`)
+    /// to get the path of the copy.
+    pub fn source_path(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    /// Returns the [`Input`] that reads from the copy.
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+}
+
+impl Deref for InputSnapshot {
+    type Target = Input;
+
+    fn deref(&self) -> &Self::Target {
+        &self.input
+    }
+}
+
+impl Read for InputSnapshot {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buf)
+    }
+}
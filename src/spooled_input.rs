@@ -0,0 +1,154 @@
+use std::io::{self, Read, Seek as _, SeekFrom, Write as _};
+
+use crate::{logging::debug, memory::MemoryCharge, temp::TempFile, Input};
+
+const DEFAULT_MEMORY_LIMIT: usize = 1024 * 1024;
+
+/// An input source that can be rewound, even if it is standard input or a pipe.
+///
+/// Data read from a non-seekable source is kept in memory, and spilled to a temporary file once it
+/// exceeds the memory limit (1 MiB by default) or the process-wide budget set with [`set_memory_budget`](crate::set_memory_budget).
+/// Seekable files are rewound by seeking, without spooling.
+/// This allows two-pass algorithms to read the input twice regardless of where it comes from.
+///
+/// The temporary file is removed when the [`SpooledInput`] is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::{self, Read as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::{Input, SpooledInput};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Input file. If `-` is specified, reads from standard input.
+///     input: Input,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse_from(["prog", "Cargo.toml"]);
+///     let mut input = SpooledInput::new(args.input);
+///     let lines = io::read_to_string(&mut input)?.lines().count();
+///     input.rewind()?;
+///     let text = io::read_to_string(&mut input)?;
+///     assert_eq!(text.lines().count(), lines);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SpooledInput {
+    input: Input,
+    memory_limit: usize,
+    spool: Option<Spool>,
+    // Position in the spool. Reads beyond the spooled data are served from `input`.
+    pos: u64,
+}
+
+#[derive(Debug)]
+enum Spool {
+    Memory(Vec<u8>, MemoryCharge),
+    File { temp: TempFile, len: u64 },
+}
+
+impl SpooledInput {
+    /// Creates a new [`SpooledInput`] that reads from `input`.
+    pub fn new(input: Input) -> Self {
+        Self::with_memory_limit(input, DEFAULT_MEMORY_LIMIT)
+    }
+
+    /// Creates a new [`SpooledInput`] that keeps up to `limit` bytes in memory before spilling to a temporary file.
+    pub fn with_memory_limit(input: Input, limit: usize) -> Self {
+        let spool = (!input.is_seekable()).then(|| Spool::Memory(vec![], MemoryCharge::default()));
+        Self {
+            input,
+            memory_limit: limit,
+            spool,
+            pos: 0,
+        }
+    }
+
+    /// Returns the underlying [`Input`].
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Returns `true` if the spooled data has been spilled to a temporary file.
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.spool, Some(Spool::File { .. }))
+    }
+
+    /// Moves the read position back to the start of the input.
+    pub fn rewind(&mut self) -> io::Result<()> {
+        match &self.spool {
+            Some(_) => self.pos = 0,
+            None => {
+                self.input.seek(SeekFrom::Start(0))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self, data: &[u8]) -> io::Result<()> {
+        match &mut self.spool {
+            Some(Spool::Memory(buf, charge))
+                if buf.len() + data.len() <= self.memory_limit && charge.try_grow(data.len()) =>
+            {
+                buf.extend_from_slice(data);
+            }
+            Some(Spool::Memory(buf, _)) => {
+                let temp = TempFile::new()?;
+                debug!(
+                    "spilling {} bytes of input to `{}`",
+                    buf.len() + data.len(),
+                    temp.path().display()
+                );
+                let mut file = temp.file();
+                file.write_all(buf)?;
+                file.write_all(data)?;
+                let len = (buf.len() + data.len()) as u64;
+                self.spool = Some(Spool::File { temp, len });
+            }
+            Some(Spool::File { temp, len }) => {
+                let mut file = temp.file();
+                file.seek(SeekFrom::Start(*len))?;
+                file.write_all(data)?;
+                *len += data.len() as u64;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+impl Read for SpooledInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = match &self.spool {
+            None => return self.input.read(buf),
+            Some(Spool::Memory(data, _)) if self.pos < data.len() as u64 => {
+                (&data[self.pos as usize..]).read(buf)?
+            }
+            Some(Spool::File { temp, len }) if self.pos < *len => {
+                let mut file = temp.file();
+                file.seek(SeekFrom::Start(self.pos))?;
+                let max = usize::try_from(*len - self.pos).unwrap_or(usize::MAX);
+                let end = buf.len().min(max);
+                file.read(&mut buf[..end])?
+            }
+            Some(_) => {
+                let n = self.input.read(buf)?;
+                self.spill(&buf[..n])?;
+                n
+            }
+        };
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl From<Input> for SpooledInput {
+    fn from(value: Input) -> Self {
+        Self::new(value)
+    }
+}
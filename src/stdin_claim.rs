@@ -0,0 +1,86 @@
+use std::{
+    cell::Cell,
+    io,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{error, ErrorKind};
+
+// Standard input can only be consumed once per process, so `-` is accepted at most once among the inputs
+// parsed from command-line arguments that are alive at the same time.
+// This holds the ID of the current claim, or 0 if standard input is not claimed.
+static CLAIMED: AtomicU64 = AtomicU64::new(0);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    // When `Some`, claims on this thread are recorded here instead of in `CLAIMED`.
+    static SCOPED: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// A claim of standard input by an input parsed from `-`, which is released when dropped.
+#[derive(Debug)]
+pub(crate) struct StdinClaim {
+    id: u64,
+    scoped: bool,
+}
+
+impl Drop for StdinClaim {
+    fn drop(&mut self) {
+        // A newer claim made after a reset is not released
+        if self.scoped {
+            let _ = SCOPED.try_with(|scoped| {
+                if scoped.get() == Some(self.id) {
+                    scoped.set(Some(0));
+                }
+            });
+        } else {
+            let _ = CLAIMED.compare_exchange(self.id, 0, Ordering::SeqCst, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Claims standard input for an [`Input`](crate::Input) parsed from `-`.
+///
+/// Returns an error if standard input is already claimed by another input that is still alive.
+pub(crate) fn claim() -> io::Result<StdinClaim> {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let (claimed, scoped) = SCOPED.with(|scoped| match scoped.get() {
+        Some(0) => {
+            scoped.set(Some(id));
+            (true, true)
+        }
+        Some(_) => (false, true),
+        None => {
+            let claimed = CLAIMED
+                .compare_exchange(0, id, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok();
+            (claimed, false)
+        }
+    });
+    if !claimed {
+        return Err(error::new(
+            ErrorKind::StdinConflict,
+            "standard input (`-`) can be specified at most once",
+        ));
+    }
+    Ok(StdinClaim { id, scoped })
+}
+
+#[cfg(feature = "test-util")]
+pub(crate) fn reset() {
+    CLAIMED.store(0, Ordering::SeqCst);
+}
+
+#[cfg(feature = "test-util")]
+pub(crate) fn scoped<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<u64>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED.with(|scoped| scoped.set(self.0));
+        }
+    }
+
+    let _restore = Restore(SCOPED.with(|scoped| scoped.replace(Some(0))));
+    f()
+}
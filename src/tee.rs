@@ -0,0 +1,96 @@
+use std::io::{self, BufRead, Read, Write};
+
+use crate::Output;
+
+/// A reader adapter that copies everything read to a secondary [`Output`].
+///
+/// This struct is created by [`Input::tee_to`](crate::Input::tee_to).
+/// It allows tools to archive the raw input while processing it, without a second pass.
+///
+/// Bytes are copied as they are consumed: on every [`Read::read`] call, or on [`BufRead::consume`] when used as a buffered reader.
+/// Since [`BufRead::consume`] cannot fail, an error while copying consumed bytes is returned from the next read.
+#[derive(Debug)]
+pub struct Tee<R> {
+    inner: R,
+    output: Output,
+    error: Option<io::Error>,
+}
+
+impl<R> Tee<R> {
+    pub(crate) fn new(inner: R, output: Output) -> Self {
+        Self {
+            inner,
+            output,
+            error: None,
+        }
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the secondary output.
+    pub fn output(&self) -> &Output {
+        &self.output
+    }
+
+    /// Consumes this adapter and returns the underlying reader and the secondary output.
+    pub fn into_inner(self) -> (R, Output) {
+        (self.inner, self.output)
+    }
+
+    /// Flushes the secondary output and returns the underlying reader.
+    ///
+    /// Returns an error if copying to or flushing the secondary output failed.
+    pub fn finish(mut self) -> io::Result<R> {
+        self.take_error()?;
+        (&self.output).flush()?;
+        Ok(self.inner)
+    }
+
+    fn take_error(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<R> Read for Tee<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.take_error()?;
+        let n = self.inner.read(buf)?;
+        (&self.output).write_all(&buf[..n])?;
+        Ok(n)
+    }
+}
+
+impl<R> BufRead for Tee<R>
+where
+    R: BufRead,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.take_error()?;
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The buffer has already been filled, so this does not perform any IO on the reader.
+        if let Ok(buf) = self.inner.fill_buf() {
+            let amt = amt.min(buf.len());
+            if let Err(e) = (&self.output).write_all(&buf[..amt]) {
+                self.error.get_or_insert(e);
+            }
+        }
+        self.inner.consume(amt);
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{cleanup::Registration, logging::debug};
+
+/// A file in the temporary directory that is removed when dropped.
+#[derive(Debug)]
+pub(crate) struct TempFile {
+    // Fields are dropped in declaration order, so the file is closed before it is removed,
+    // which is required on Windows.
+    file: File,
+    path: RemoveOnDrop,
+    _registration: Registration,
+}
+
+#[derive(Debug)]
+struct RemoveOnDrop(PathBuf);
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        debug!("removing temporary file `{}`", self.0.display());
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+impl TempFile {
+    /// Creates a new empty file in the temporary directory, opened for reading and writing.
+    pub(crate) fn new() -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        loop {
+            let name = format!(
+                "clap-file.{}.{}.tmp",
+                process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = env::temp_dir().join(name);
+            match OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => {
+                    debug!("created temporary file `{}`", path.display());
+                    return Ok(Self {
+                        file,
+                        _registration: Registration::new(&path),
+                        path: RemoveOnDrop(path),
+                    });
+                }
+                // A file left by a previous process with the same ID
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path.0
+    }
+
+    pub(crate) fn file(&self) -> &File {
+        &self.file
+    }
+}
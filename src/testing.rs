@@ -0,0 +1,129 @@
+//! Utilities for testing programs that use this crate.
+//!
+//! This module is available only when the `test-util` feature is enabled.
+
+use std::{
+    cell::RefCell,
+    io::Cursor,
+    sync::{Arc, Mutex},
+};
+
+use crate::stdin_claim;
+
+type InjectedStdin = Arc<Mutex<Cursor<Vec<u8>>>>;
+type InjectedStdout = Arc<Mutex<Vec<u8>>>;
+
+thread_local! {
+    static INJECTED: RefCell<Option<(InjectedStdin, InjectedStdout)>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn injected_stdin() -> Option<InjectedStdin> {
+    INJECTED.with(|injected| {
+        injected
+            .borrow()
+            .as_ref()
+            .map(|(stdin, _)| Arc::clone(stdin))
+    })
+}
+
+pub(crate) fn injected_stdout() -> Option<InjectedStdout> {
+    INJECTED.with(|injected| {
+        injected
+            .borrow()
+            .as_ref()
+            .map(|(_, stdout)| Arc::clone(stdout))
+    })
+}
+
+/// Resets the process-global record of whether standard input has been claimed by an [`Input`](crate::Input) parsed from `-`.
+///
+/// Since tests usually run in parallel, prefer [`with_stdin_claim_scope`], which does not affect other tests.
+pub fn reset_stdin_claim() {
+    stdin_claim::reset();
+}
+
+/// Runs `f` with a fresh record of whether standard input has been claimed, local to the current thread.
+///
+/// Claims made by `f` do not affect the process-global record or other threads,
+/// so test cases that parse `-` many times do not interfere with each other.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::{testing, Input};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     input: Input,
+/// }
+///
+/// for _ in 0..3 {
+///     testing::with_stdin_claim_scope(|| {
+///         let args = Args::try_parse_from(["prog", "-"]).unwrap();
+///         assert!(args.input.is_stdin());
+///     });
+/// }
+/// ```
+pub fn with_stdin_claim_scope<R>(f: impl FnOnce() -> R) -> R {
+    stdin_claim::scoped(f)
+}
+
+/// Runs `f` with standard input and standard output substituted, and returns its result along with the captured output.
+///
+/// Within `f`, [`Input::stdin`](crate::Input::stdin) (and `-` for [`Input`](crate::Input)) reads from `stdin`,
+/// and [`Output::stdout`](crate::Output::stdout) (and `-` for [`Output`](crate::Output)) writes to an in-memory buffer,
+/// which is returned after `f` finishes.
+/// The substitution is local to the current thread, and `f` also runs within [`with_stdin_claim_scope`].
+///
+/// This allows library-level tests to simulate piped standard input without spawning a subprocess.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::io::{self, BufRead as _, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::{testing, Input, Output};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     input: Input,
+///     output: Output,
+/// }
+///
+/// fn run(args: Args) -> io::Result<()> {
+///     let mut output = args.output.lock();
+///     for line in args.input.lock().lines() {
+///         writeln!(output, "{}", line?.to_uppercase())?;
+///     }
+///     Ok(())
+/// }
+///
+/// let (res, stdout) = testing::with_stdio("hello\nworld\n", || {
+///     run(Args::try_parse_from(["prog", "-", "-"]).unwrap())
+/// });
+/// res.unwrap();
+/// assert_eq!(stdout, b"HELLO\nWORLD\n");
+/// ```
+pub fn with_stdio<R>(stdin: impl Into<Vec<u8>>, f: impl FnOnce() -> R) -> (R, Vec<u8>) {
+    struct Restore(Option<(InjectedStdin, InjectedStdout)>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            INJECTED.with(|injected| *injected.borrow_mut() = self.0.take());
+        }
+    }
+
+    let stdout = InjectedStdout::default();
+    let injection = (
+        Arc::new(Mutex::new(Cursor::new(stdin.into()))),
+        Arc::clone(&stdout),
+    );
+    let restore = Restore(INJECTED.with(|injected| injected.borrow_mut().replace(injection)));
+    let res = with_stdin_claim_scope(f);
+    drop(restore);
+
+    let stdout = std::mem::take(&mut *stdout.lock().unwrap_or_else(|e| e.into_inner()));
+    (res, stdout)
+}
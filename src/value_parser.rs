@@ -0,0 +1,762 @@
+use std::{any::TypeId, borrow::Cow, ffi::OsStr, fmt, path::Path, sync::Arc};
+
+use clap::{
+    builder::{MapValueParser, TypedValueParser, ValueParserFactory},
+    error::ErrorKind,
+    Command, ValueHint,
+};
+
+use crate::{
+    closed_stdio, error, inherited_fd, paths, ClapFileConfig, ClosedStdio, Error, EscapedPath,
+    FileInput, FileOutput, Input, Operation, Output, OutputAppend, StdinOnly, StdoutOnly,
+};
+
+type PathValidator = Arc<dyn Fn(&Path) -> Result<(), String> + Send + Sync>;
+
+/// A value that refers to something other than standard input or output, or a file path.
+enum Special<'a> {
+    /// `tty:`, the terminal.
+    Tty,
+    /// `fd:<spec>`, an inherited file descriptor.
+    Inherited(&'a str),
+}
+
+impl Special<'_> {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Tty => "the terminal",
+            Self::Inherited(_) => "inherited file descriptors",
+        }
+    }
+}
+
+/// The kinds of values a parser accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Accept {
+    /// Standard input or output, the terminal, and files.
+    Any,
+    /// Only files, for [`FileInput`] and [`FileOutput`].
+    Files,
+    /// Only standard input or output, for [`StdinOnly`] and [`StdoutOnly`].
+    Stdio,
+}
+
+/// Options shared by [`InputValueParser`] and [`OutputValueParser`].
+#[derive(Clone)]
+struct ParserOptions {
+    sentinel: Option<String>,
+    accept: Accept,
+    append: bool,
+    tty: bool,
+    closed_stdio: Option<ClosedStdio>,
+    capacity: Option<usize>,
+    expand_tilde: Option<bool>,
+    create_dirs: Option<bool>,
+    atomic: Option<bool>,
+    detect_compression: Option<bool>,
+    inherited_fds: bool,
+    validators: Vec<PathValidator>,
+    #[cfg(feature = "cap-std")]
+    dir: Option<Arc<cap_std::fs::Dir>>,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            sentinel: Some("-".to_owned()),
+            accept: Accept::Any,
+            append: false,
+            tty: false,
+            closed_stdio: None,
+            capacity: None,
+            expand_tilde: None,
+            create_dirs: None,
+            atomic: None,
+            detect_compression: None,
+            inherited_fds: false,
+            validators: vec![],
+            #[cfg(feature = "cap-std")]
+            dir: None,
+        }
+    }
+}
+
+impl fmt::Debug for ParserOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("ParserOptions");
+        f.field("sentinel", &self.sentinel);
+        f.field("accept", &self.accept);
+        f.field("append", &self.append);
+        f.field("tty", &self.tty);
+        f.field("closed_stdio", &self.closed_stdio);
+        f.field("capacity", &self.capacity);
+        f.field("expand_tilde", &self.expand_tilde);
+        f.field("create_dirs", &self.create_dirs);
+        f.field("atomic", &self.atomic);
+        f.field("detect_compression", &self.detect_compression);
+        f.field("inherited_fds", &self.inherited_fds);
+        f.field("validators", &self.validators.len());
+        #[cfg(feature = "cap-std")]
+        f.field("dir", &self.dir);
+        f.finish()
+    }
+}
+
+impl ParserOptions {
+    #[allow(clippy::too_many_arguments)]
+    fn parse<T>(
+        &self,
+        config: &ClapFileConfig,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+        operation: Operation,
+        stdio: impl FnOnce(&str) -> std::io::Result<T>,
+        special: impl FnOnce(Special<'_>) -> std::io::Result<T>,
+        open: impl FnOnce(&Path) -> std::io::Result<T>,
+    ) -> Result<T, clap::Error> {
+        let stream = match operation {
+            Operation::Create => "standard output",
+            _ => "standard input",
+        };
+        // Compare as `OsStr` so that non-UTF-8 paths are passed through unchanged
+        if self.sentinel.as_deref().map(OsStr::new) == Some(value) {
+            if self.accept == Accept::Files {
+                let e = error::new(
+                    crate::ErrorKind::Unsupported,
+                    format!(
+                        "{stream} (`{}`) is not supported; specify a file",
+                        value.to_string_lossy()
+                    ),
+                );
+                return Err(value_error(cmd, arg, value, e));
+            }
+            return stdio("-").map_err(|e| value_error(cmd, arg, value, e));
+        }
+        if self.accept == Accept::Stdio {
+            let sentinel = self.sentinel.as_deref().unwrap_or("-");
+            let e = error::new(
+                crate::ErrorKind::InvalidInput,
+                format!("only {stream} (`{sentinel}`) is supported"),
+            );
+            return Err(value_error(cmd, arg, value, e));
+        }
+        if let Some(value_kind) = self.special(value) {
+            if self.accept == Accept::Files {
+                let e = error::new(
+                    crate::ErrorKind::Unsupported,
+                    format!("{} is not supported; specify a file", value_kind.name()),
+                );
+                return Err(value_error(cmd, arg, value, e));
+            }
+            self.validate(Path::new(value))
+                .map_err(|e| value_error(cmd, arg, value, e))?;
+            #[cfg(feature = "cap-std")]
+            if self.dir.is_some() {
+                let e = crate::error::new(
+                    crate::ErrorKind::PermissionDenied,
+                    format!(
+                        "{} cannot be used when files are restricted to a directory",
+                        value_kind.name()
+                    ),
+                );
+                return Err(value_error(cmd, arg, value, e));
+            }
+            return special(value_kind).map_err(|e| value_error(cmd, arg, value, e));
+        }
+        let path = Path::new(value);
+        let path = if self.expand_tilde.unwrap_or(config.expand_tilde) {
+            paths::expand_tilde(path).map_err(|e| value_error(cmd, arg, value, e))?
+        } else {
+            Cow::Borrowed(path)
+        };
+        let path = &*path;
+        self.validate(path)
+            .map_err(|e| value_error(cmd, arg, value, e))?;
+        open(path).map_err(|e| value_error(cmd, arg, value, Error::new(operation, path, e)))
+    }
+
+    fn special<'a>(&self, value: &'a OsStr) -> Option<Special<'a>> {
+        // Like the sentinel, `tty:` is disabled by `no_sentinel()`
+        if self.tty && self.sentinel.is_some() && value == "tty:" {
+            return Some(Special::Tty);
+        }
+        inherited_fd::strip_prefix(value)
+            .filter(|_| self.inherited_fds)
+            .map(Special::Inherited)
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), String> {
+        self.validators
+            .iter()
+            .try_for_each(|validator| validator(path))
+    }
+}
+
+fn value_error(
+    cmd: &clap::Command,
+    arg: Option<&clap::Arg>,
+    value: &OsStr,
+    error: impl fmt::Display,
+) -> clap::Error {
+    let arg = arg.map_or_else(|| "...".to_owned(), |arg| arg.to_string());
+    clap::Error::raw(
+        ErrorKind::ValueValidation,
+        format!(
+            "invalid value '{}' for '{arg}': {error}",
+            EscapedPath::new(Path::new(value))
+        ),
+    )
+    .with_cmd(cmd)
+}
+
+/// A clap value parser for [`Input`] with additional options.
+///
+/// Options that are not set fall back to the defaults installed with [`ClapFileConfig`].
+///
+/// Values are parsed as platform paths rather than strings, so files with non-UTF-8 names can be opened.
+/// This is also the parser used for [`Input`] fields without an explicit `value_parser`.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser as _;
+/// use clap_file::{Input, InputValueParser};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     #[arg(value_parser = InputValueParser::new().validate_path(|path| {
+///         if path.starts_with("/etc") {
+///             return Err("reading system files is not allowed".to_owned());
+///         }
+///         Ok(())
+///     }))]
+///     input: Input,
+/// }
+///
+/// assert!(Args::try_parse_from(["prog", "Cargo.toml"]).is_ok());
+/// assert!(Args::try_parse_from(["prog", "/etc/passwd"]).is_err());
+/// ```
+///
+/// Non-UTF-8 file names are accepted on Unix:
+///
+/// ```rust
+/// # #[cfg(unix)] {
+/// use std::{ffi::OsStr, os::unix::ffi::OsStrExt as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::Input;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     input: Input,
+/// }
+///
+/// let dir = std::env::temp_dir().join(format!("clap-file-doc-{}", std::process::id()));
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let path = dir.join(OsStr::from_bytes(b"caf\xe9.txt"));
+/// std::fs::write(&path, "hello").unwrap();
+///
+/// let args = Args::try_parse_from([OsStr::new("prog"), path.as_os_str()]).unwrap();
+/// assert_eq!(args.input.path(), Some(path.as_path()));
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InputValueParser {
+    options: ParserOptions,
+}
+
+impl InputValueParser {
+    /// Creates a new [`InputValueParser`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value that stands for standard input, instead of `-`.
+    ///
+    /// With a sentinel such as `/dev/stdin`, `-` is treated as a file path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use clap::Parser as _;
+    /// use clap_file::{Input, InputValueParser};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     #[arg(value_parser = InputValueParser::new().sentinel("/dev/stdin"))]
+    ///     input: Input,
+    /// }
+    ///
+    /// let args = Args::try_parse_from(["prog", "/dev/stdin"]).unwrap();
+    /// assert!(args.input.is_stdin());
+    /// // `-` is now a file path, which does not exist
+    /// assert!(Args::try_parse_from(["prog", "-"]).is_err());
+    /// ```
+    pub fn sentinel(mut self, sentinel: impl Into<String>) -> Self {
+        self.options.sentinel = Some(sentinel.into());
+        self
+    }
+
+    /// Disables the value that stands for standard input, so that every value is treated as a file path.
+    pub fn no_sentinel(mut self) -> Self {
+        self.options.sentinel = None;
+        self
+    }
+
+    /// Sets what to do when standard input is specified but closed.
+    ///
+    /// Defaults to [`ClosedStdio::Error`].
+    pub fn closed_stdio(mut self, closed_stdio: ClosedStdio) -> Self {
+        self.options.closed_stdio = Some(closed_stdio);
+        self
+    }
+
+    /// Sets the capacity of the read buffer of opened files.
+    ///
+    /// If not set, the capacity is chosen based on the type of the opened file. See [`InputBuilder::capacity`](crate::InputBuilder::capacity).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use clap::Parser as _;
+    /// use clap_file::{Input, InputValueParser};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     #[arg(value_parser = InputValueParser::new().capacity(4 * 1024 * 1024))]
+    ///     input: Input,
+    /// }
+    ///
+    /// let args = Args::parse_from(["prog", "Cargo.toml"]);
+    /// assert!(args.input.is_file());
+    /// ```
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.options.capacity = Some(capacity);
+        self
+    }
+
+    /// Sets whether to expand a leading `~` or `~user` in paths to the home directory, as shells do.
+    ///
+    /// Defaults to `false`. This helps when the program is invoked without a shell, e.g. by another program
+    /// that passes `~/input.txt` literally. See [`paths::expand_tilde`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use clap::Parser as _;
+    /// use clap_file::{Input, InputValueParser};
+    ///
+    /// #[derive(Debug, clap::Parser)]
+    /// struct Args {
+    ///     #[arg(value_parser = InputValueParser::new().expand_tilde(true))]
+    ///     input: Input,
+    /// }
+    ///
+    /// let args = Args::parse_from(["prog", "~/input.txt"]);
+    /// ```
+    pub fn expand_tilde(mut self, expand: bool) -> Self {
+        self.options.expand_tilde = Some(expand);
+        self
+    }
+
+    /// Sets whether to detect the compression of opened files from their contents, regardless of their extension.
+    ///
+    /// Defaults to `false`. See [`InputBuilder::detect_compression`](crate::InputBuilder::detect_compression).
+    pub fn detect_compression(mut self, detect: bool) -> Self {
+        self.options.detect_compression = Some(detect);
+        self
+    }
+
+    /// Sets whether `fd:<spec>` values take file descriptors inherited from the parent process, e.g. `fd:3` or `fd:listen`.
+    ///
+    /// Defaults to `false`, so such values are treated as file paths. Enable this only for programs that are started
+    /// with descriptors to read from, such as services using socket activation. See [`InputBuilder::open_inherited`](crate::InputBuilder::open_inherited).
+    /// Parsing an `fd:<spec>` value fails if files are restricted to a directory with `dir` (`cap-std` feature).
+    pub fn inherited_fds(mut self, enable: bool) -> Self {
+        self.options.inherited_fds = enable;
+        self
+    }
+
+    /// Adds a hook that validates the path before the file is opened.
+    ///
+    /// If the hook returns an error, parsing fails with a clap validation error containing the returned message.
+    /// Hooks are not called for the value that stands for standard input (`-` by default),
+    /// and are called with `fd:<spec>` values as is if [`inherited_fds`](Self::inherited_fds) is enabled.
+    pub fn validate_path<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Path) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.options.validators.push(Arc::new(f));
+        self
+    }
+
+    /// Restricts files opened by this parser to the tree under `dir`.
+    ///
+    /// Paths are resolved relative to `dir` with [`cap_std`] and cannot escape it.
+    #[cfg(feature = "cap-std")]
+    pub fn dir(mut self, dir: cap_std::fs::Dir) -> Self {
+        self.options.dir = Some(Arc::new(dir));
+        self
+    }
+}
+
+impl TypedValueParser for InputValueParser {
+    type Value = Input;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let config = ClapFileConfig::current();
+        let builder = || {
+            let mut builder = Input::builder();
+            builder.detect_compression(
+                self.options
+                    .detect_compression
+                    .unwrap_or(config.detect_compression),
+            );
+            if let Some(capacity) = self.options.capacity.or(config.input_capacity) {
+                builder.capacity(capacity);
+            }
+            builder
+        };
+        let stdin = |s: &str| {
+            let closed = closed_stdio::is_stdin_closed();
+            match self
+                .options
+                .closed_stdio
+                .unwrap_or(config.closed_stdio)
+                .fallback(closed, "standard input")?
+            {
+                Some(null) => Input::open(null),
+                None => s.parse(),
+            }
+        };
+        self.options.parse(
+            &config,
+            cmd,
+            arg,
+            value,
+            Operation::Open,
+            stdin,
+            |special| match special {
+                Special::Inherited(spec) => builder().open_inherited(spec),
+                Special::Tty => unreachable!("`tty:` is only accepted for outputs"),
+            },
+            |path| {
+                let builder = builder();
+                #[cfg(feature = "cap-std")]
+                if let Some(dir) = &self.options.dir {
+                    return builder.open_at(dir, path.to_owned());
+                }
+                builder.open(path.to_owned())
+            },
+        )
+    }
+}
+
+/// A clap value parser for [`Output`] with additional options.
+///
+/// Options that are not set fall back to the defaults installed with [`ClapFileConfig`].
+///
+/// Values are parsed as platform paths rather than strings, so files with non-UTF-8 names can be created.
+/// This is also the parser used for [`Output`] fields without an explicit `value_parser`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use clap::Parser as _;
+/// use clap_file::{Output, OutputValueParser};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     #[arg(value_parser = OutputValueParser::new().validate_path(|path| {
+///         if path.is_absolute() {
+///             return Err("output must be a relative path".to_owned());
+///         }
+///         Ok(())
+///     }))]
+///     output: Output,
+/// }
+///
+/// let args = Args::parse();
+/// ```
+#[derive(Debug, Clone)]
+pub struct OutputValueParser {
+    options: ParserOptions,
+}
+
+impl Default for OutputValueParser {
+    fn default() -> Self {
+        Self {
+            options: ParserOptions {
+                tty: true,
+                ..ParserOptions::default()
+            },
+        }
+    }
+}
+
+impl OutputValueParser {
+    /// Creates a new [`OutputValueParser`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the value that stands for standard output, instead of `-`.
+    ///
+    /// With a sentinel such as `/dev/stdout`, `-` is treated as a file path.
+    pub fn sentinel(mut self, sentinel: impl Into<String>) -> Self {
+        self.options.sentinel = Some(sentinel.into());
+        self
+    }
+
+    /// Disables the value that stands for standard output and `tty:`, so that every value is treated as a file path.
+    pub fn no_sentinel(mut self) -> Self {
+        self.options.sentinel = None;
+        self
+    }
+
+    /// Sets what to do when standard output is specified but closed.
+    ///
+    /// Defaults to [`ClosedStdio::Error`].
+    pub fn closed_stdio(mut self, closed_stdio: ClosedStdio) -> Self {
+        self.options.closed_stdio = Some(closed_stdio);
+        self
+    }
+
+    /// Sets the capacity of the write buffer of created files.
+    ///
+    /// If not set, the capacity is chosen based on the type of the created file. See [`OutputBuilder::capacity`](crate::OutputBuilder::capacity).
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.options.capacity = Some(capacity);
+        self
+    }
+
+    /// Sets whether to expand a leading `~` or `~user` in paths to the home directory, as shells do.
+    ///
+    /// Defaults to `false`. See [`InputValueParser::expand_tilde`].
+    pub fn expand_tilde(mut self, expand: bool) -> Self {
+        self.options.expand_tilde = Some(expand);
+        self
+    }
+
+    /// Sets whether to create the missing parent directories of created files.
+    ///
+    /// Defaults to `false`. See [`OutputBuilder::create_dirs`](crate::OutputBuilder::create_dirs).
+    pub fn create_dirs(mut self, create_dirs: bool) -> Self {
+        self.options.create_dirs = Some(create_dirs);
+        self
+    }
+
+    /// Sets whether created files are written to a temporary file and renamed over the target when closed.
+    ///
+    /// Defaults to `false`. See [`OutputBuilder::atomic`](crate::OutputBuilder::atomic).
+    pub fn atomic(mut self, atomic: bool) -> Self {
+        self.options.atomic = Some(atomic);
+        self
+    }
+
+    /// Sets whether `fd:<spec>` values take file descriptors inherited from the parent process, e.g. `fd:3` or `fd:listen`.
+    ///
+    /// Defaults to `false`. See [`InputValueParser::inherited_fds`] and [`OutputBuilder::open_inherited`](crate::OutputBuilder::open_inherited).
+    pub fn inherited_fds(mut self, enable: bool) -> Self {
+        self.options.inherited_fds = enable;
+        self
+    }
+
+    /// Adds a hook that validates the path before the file is created.
+    ///
+    /// If the hook returns an error, parsing fails with a clap validation error containing the returned message.
+    /// Hooks are not called for the value that stands for standard output (`-` by default),
+    /// and are called with `tty:` and `fd:<spec>` values as is, so that they can reject the terminal and inherited descriptors.
+    pub fn validate_path<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Path) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.options.validators.push(Arc::new(f));
+        self
+    }
+
+    /// Restricts files created by this parser to the tree under `dir`.
+    ///
+    /// Paths are resolved relative to `dir` with [`cap_std`] and cannot escape it.
+    /// `tty:` and `fd:<spec>` values are rejected, since they refer to outside of `dir`.
+    #[cfg(feature = "cap-std")]
+    pub fn dir(mut self, dir: cap_std::fs::Dir) -> Self {
+        self.options.dir = Some(Arc::new(dir));
+        self
+    }
+}
+
+impl TypedValueParser for OutputValueParser {
+    type Value = Output;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let config = ClapFileConfig::current();
+        let builder = || {
+            let mut builder = Output::builder();
+            builder
+                .buffering(config.output_buffering)
+                .sync_on_close(config.sync_on_close)
+                .create_dirs(self.options.create_dirs.unwrap_or(config.create_dirs))
+                .append(self.options.append)
+                // Appending to a temporary file would discard the existing contents
+                .atomic(!self.options.append && self.options.atomic.unwrap_or(config.atomic));
+            if let Some(capacity) = self.options.capacity.or(config.output_capacity) {
+                builder.capacity(capacity);
+            }
+            builder
+        };
+        let stdout = |s: &str| {
+            let closed = closed_stdio::is_stdout_closed();
+            match self
+                .options
+                .closed_stdio
+                .unwrap_or(config.closed_stdio)
+                .fallback(closed, "standard output")?
+            {
+                Some(null) => Output::create(null),
+                None => s.parse(),
+            }
+        };
+        self.options.parse(
+            &config,
+            cmd,
+            arg,
+            value,
+            Operation::Create,
+            stdout,
+            |special| match special {
+                Special::Tty => Output::tty(),
+                Special::Inherited(spec) => builder().open_inherited(spec),
+            },
+            |path| {
+                let builder = builder();
+                #[cfg(feature = "cap-std")]
+                if let Some(dir) = &self.options.dir {
+                    return builder.open_at(dir, path.to_owned());
+                }
+                builder.open(path.to_owned())
+            },
+        )
+    }
+}
+
+impl ValueParserFactory for Input {
+    type Parser = InputValueParser;
+
+    fn value_parser() -> Self::Parser {
+        InputValueParser::new()
+    }
+}
+
+impl ValueParserFactory for Output {
+    type Parser = OutputValueParser;
+
+    fn value_parser() -> Self::Parser {
+        OutputValueParser::new()
+    }
+}
+
+impl ValueParserFactory for StdinOnly {
+    type Parser = MapValueParser<InputValueParser, fn(Input) -> StdinOnly>;
+
+    fn value_parser() -> Self::Parser {
+        let mut parser = InputValueParser::new();
+        parser.options.accept = Accept::Stdio;
+        parser.map(StdinOnly as fn(Input) -> StdinOnly)
+    }
+}
+
+impl ValueParserFactory for StdoutOnly {
+    type Parser = MapValueParser<OutputValueParser, fn(Output) -> StdoutOnly>;
+
+    fn value_parser() -> Self::Parser {
+        let mut parser = OutputValueParser::new();
+        parser.options.accept = Accept::Stdio;
+        parser.map(StdoutOnly as fn(Output) -> StdoutOnly)
+    }
+}
+
+impl ValueParserFactory for OutputAppend {
+    type Parser = MapValueParser<OutputValueParser, fn(Output) -> OutputAppend>;
+
+    fn value_parser() -> Self::Parser {
+        let mut parser = OutputValueParser::new();
+        parser.options.append = true;
+        parser.map(OutputAppend as fn(Output) -> OutputAppend)
+    }
+}
+
+impl ValueParserFactory for FileInput {
+    type Parser = MapValueParser<InputValueParser, fn(Input) -> FileInput>;
+
+    fn value_parser() -> Self::Parser {
+        let mut parser = InputValueParser::new();
+        parser.options.accept = Accept::Files;
+        parser.map(FileInput as fn(Input) -> FileInput)
+    }
+}
+
+impl ValueParserFactory for FileOutput {
+    type Parser = MapValueParser<OutputValueParser, fn(Output) -> FileOutput>;
+
+    fn value_parser() -> Self::Parser {
+        let mut parser = OutputValueParser::new();
+        parser.options.accept = Accept::Files;
+        parser.map(FileOutput as fn(Output) -> FileOutput)
+    }
+}
+
+/// Sets [`ValueHint::FilePath`] on the arguments of `cmd` and its subcommands that are parsed as [`Input`], [`Output`], [`OutputAppend`], [`FileInput`] or [`FileOutput`].
+///
+/// clap only infers value hints for `PathBuf` arguments, and a value parser cannot provide one,
+/// so arguments of these types have no hint unless one is set explicitly.
+/// Call this on the command passed to a shell completion generator to complete file names for them.
+/// Arguments that already have a value hint are left unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::{CommandFactory as _, ValueHint};
+/// use clap_file::Input;
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     input: Input,
+/// }
+///
+/// let cmd = clap_file::value_hints(Args::command());
+/// let input = cmd.get_arguments().find(|arg| arg.get_id() == "input").unwrap();
+/// assert_eq!(input.get_value_hint(), ValueHint::FilePath);
+/// ```
+pub fn value_hints(cmd: Command) -> Command {
+    cmd.mut_args(|arg| {
+        let type_id = arg.get_value_parser().type_id();
+        let is_file = [
+            TypeId::of::<Input>(),
+            TypeId::of::<Output>(),
+            TypeId::of::<OutputAppend>(),
+            TypeId::of::<FileInput>(),
+            TypeId::of::<FileOutput>(),
+        ]
+        .into_iter()
+        .any(|id| type_id == id);
+        if is_file && arg.get_value_hint() == ValueHint::Unknown {
+            arg.value_hint(ValueHint::FilePath)
+        } else {
+            arg
+        }
+    })
+    .mut_subcommands(value_hints)
+}
@@ -0,0 +1,332 @@
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufRead as _, BufReader, Write as _},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use crate::{error, logging::debug, ErrorKind, EscapedPath};
+
+#[cfg(windows)]
+const TTY_INPUT_PATH: &str = "CONIN$";
+#[cfg(windows)]
+const TTY_OUTPUT_PATH: &str = "CONOUT$";
+#[cfg(not(windows))]
+const TTY_INPUT_PATH: &str = "/dev/tty";
+#[cfg(not(windows))]
+const TTY_OUTPUT_PATH: &str = "/dev/tty";
+
+type Handler = Arc<dyn Fn(&Warning) + Send + Sync>;
+
+static HANDLER: RwLock<Option<Handler>> = RwLock::new(None);
+// Checked before doing any work to detect warnings, so that applications without a handler pay nothing.
+static HAS_HANDLER: AtomicBool = AtomicBool::new(false);
+static BINARY_WARNED: AtomicBool = AtomicBool::new(false);
+static BINARY_GUARD: AtomicU8 = AtomicU8::new(BinaryGuard::Off as u8);
+// Set when the user allows binary data to be written to the terminal at the prompt.
+static BINARY_ALLOWED: AtomicBool = AtomicBool::new(false);
+
+/// A potential problem detected while reading inputs or writing outputs, which does not prevent the operation.
+///
+/// Warnings are passed to the handler installed with [`set_warning_handler`], so that applications can surface them to users.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// Data that looks binary (contains NUL bytes) was written to a terminal, which may corrupt the terminal.
+    ///
+    /// This is reported at most once per process. To prevent the write, use [`set_binary_guard`].
+    BinaryToTerminal,
+    /// The size of an input file changed between opening it and reaching its end, e.g. because another process appended to it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::{
+    ///     io::{self, Read as _},
+    ///     sync::{Arc, Mutex},
+    /// };
+    ///
+    /// use clap_file::{Input, Warning};
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let path = std::env::temp_dir().join(format!("clap-file-doc-size-{}", std::process::id()));
+    ///     std::fs::write(&path, "abc")?;
+    ///     let warnings = Arc::new(Mutex::new(vec![]));
+    ///     let handler_warnings = Arc::clone(&warnings);
+    ///     clap_file::set_warning_handler(move |warning: &Warning| {
+    ///         handler_warnings.lock().unwrap().push(warning.clone());
+    ///     });
+    ///
+    ///     let input = Input::open(path.clone())?;
+    ///     std::fs::write(&path, "abcdef")?;
+    ///     let mut buf = [0; 16];
+    ///     let e = (&input).read_exact(&mut buf).unwrap_err();
+    ///     assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+    ///     assert!(matches!(
+    ///         &warnings.lock().unwrap()[..],
+    ///         [Warning::InputSizeChanged { before: 3, after: 6, .. }],
+    ///     ));
+    ///     # clap_file::clear_warning_handler();
+    ///     # drop(input);
+    ///     # std::fs::remove_file(&path)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    InputSizeChanged {
+        /// The path of the input file.
+        path: PathBuf,
+        /// The size of the file when it was opened.
+        before: u64,
+        /// The size of the file when its end was reached.
+        after: u64,
+    },
+    /// A file queued by [`OutputDir::write_file`](crate::OutputDir::write_file) failed to be written, and the error
+    /// was discarded because the [`OutputDir`](crate::OutputDir) was dropped without calling
+    /// [`OutputDir::wait`](crate::OutputDir::wait).
+    WriteErrorDiscarded {
+        /// The path of the output file.
+        path: PathBuf,
+        /// The message of the discarded error.
+        message: String,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BinaryToTerminal => write!(f, "output is a terminal and data looks binary"),
+            Self::InputSizeChanged {
+                path,
+                before,
+                after,
+            } => write!(
+                f,
+                "input file `{}` changed size during read (from {before} to {after} bytes)",
+                EscapedPath::new(path)
+            ),
+            Self::WriteErrorDiscarded { path, message } => write!(
+                f,
+                "failed to write output file `{}`: {message}",
+                EscapedPath::new(path)
+            ),
+        }
+    }
+}
+
+/// Installs `handler` to be called with every [`Warning`] detected by this crate, replacing the previous one.
+///
+/// Without a handler, warnings are not detected at all.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap_file::Warning;
+///
+/// clap_file::set_warning_handler(|warning: &Warning| eprintln!("warning: {warning}"));
+/// # clap_file::clear_warning_handler();
+/// ```
+pub fn set_warning_handler<F>(handler: F)
+where
+    F: Fn(&Warning) + Send + Sync + 'static,
+{
+    *HANDLER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(handler));
+    HAS_HANDLER.store(true, Ordering::Relaxed);
+}
+
+/// Removes the handler installed with [`set_warning_handler`].
+pub fn clear_warning_handler() {
+    HAS_HANDLER.store(false, Ordering::Relaxed);
+    *HANDLER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn has_handler() -> bool {
+    HAS_HANDLER.load(Ordering::Relaxed)
+}
+
+fn emit(warning: &Warning) {
+    debug!("warning: {warning}");
+    // Clone the handler so that it can install another handler without deadlocking
+    let handler = HANDLER.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(handler) = handler {
+        handler(warning);
+    }
+}
+
+/// Specifies what happens when data that looks binary (contains NUL bytes) is about to be written to a terminal.
+///
+/// Like the binary file warning of `grep`, this prevents the terminal from being corrupted by escape sequences
+/// when a binary file is printed by mistake. Set it with [`set_binary_guard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum BinaryGuard {
+    /// Writes the data as is. [`Warning::BinaryToTerminal`] is still reported to the warning handler.
+    #[default]
+    Off,
+    /// Fails the write with an error of kind [`ErrorKind::InvalidInput`], without writing any of the data.
+    Error,
+    /// Asks the user on the terminal whether to write the data, and fails as with [`BinaryGuard::Error`] if the user declines.
+    ///
+    /// The user is asked only once: after accepting, binary data is written as is, and after declining, writes fail.
+    /// If the terminal cannot be opened for the prompt, the write fails.
+    Prompt,
+}
+
+/// Sets what happens when an [`Output`](crate::Output) is about to write data that looks binary to a terminal.
+///
+/// The guard applies to all outputs of the process, and defaults to [`BinaryGuard::Off`].
+/// Outputs that are not terminals, such as files and pipes, are never affected.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::io::{self, Write as _};
+///
+/// use clap::Parser as _;
+/// use clap_file::{BinaryGuard, Output};
+///
+/// #[derive(Debug, clap::Parser)]
+/// struct Args {
+///     /// Writes binary data to the terminal without asking.
+///     #[arg(long)]
+///     force: bool,
+///     #[arg(default_value = "-")]
+///     output: Output,
+/// }
+///
+/// fn main() -> io::Result<()> {
+///     let args = Args::parse();
+///     if !args.force {
+///         clap_file::set_binary_guard(BinaryGuard::Prompt);
+///     }
+///     args.output.lock().write_all(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR")?;
+///     Ok(())
+/// }
+/// ```
+pub fn set_binary_guard(guard: BinaryGuard) {
+    BINARY_GUARD.store(guard as u8, Ordering::Relaxed);
+    BINARY_ALLOWED.store(false, Ordering::Relaxed);
+}
+
+fn binary_guard() -> BinaryGuard {
+    if BINARY_ALLOWED.load(Ordering::Relaxed) {
+        return BinaryGuard::Off;
+    }
+    match BINARY_GUARD.load(Ordering::Relaxed) {
+        x if x == BinaryGuard::Error as u8 => BinaryGuard::Error,
+        x if x == BinaryGuard::Prompt as u8 => BinaryGuard::Prompt,
+        _ => BinaryGuard::Off,
+    }
+}
+
+/// Reports [`Warning::BinaryToTerminal`] and applies the [`BinaryGuard`] if `buf` looks binary and the output is a terminal.
+pub(crate) fn check_binary(buf: &[u8], is_terminal: impl FnOnce() -> bool) -> io::Result<()> {
+    let guard = binary_guard();
+    let warn = has_handler() && !BINARY_WARNED.load(Ordering::Relaxed);
+    if (guard == BinaryGuard::Off && !warn) || !buf.contains(&0) || !is_terminal() {
+        return Ok(());
+    }
+    if warn && !BINARY_WARNED.swap(true, Ordering::Relaxed) {
+        emit(&Warning::BinaryToTerminal);
+    }
+    match guard {
+        BinaryGuard::Off => Ok(()),
+        BinaryGuard::Prompt if confirm_binary() => {
+            BINARY_ALLOWED.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        BinaryGuard::Prompt => {
+            BINARY_GUARD.store(BinaryGuard::Error as u8, Ordering::Relaxed);
+            Err(refused())
+        }
+        BinaryGuard::Error => Err(refused()),
+    }
+}
+
+fn refused() -> io::Error {
+    error::new(
+        ErrorKind::InvalidInput,
+        "refusing to write binary data to the terminal; redirect the output to a file",
+    )
+}
+
+/// Asks the user on the terminal whether to write binary data to it.
+fn confirm_binary() -> bool {
+    debug!("asking whether to write binary data to the terminal");
+    let prompt = || -> io::Result<String> {
+        let mut output = OpenOptions::new().write(true).open(TTY_OUTPUT_PATH)?;
+        let input = File::open(TTY_INPUT_PATH)?;
+        write!(
+            output,
+            "binary data is about to be written to the terminal; continue? [y/N] "
+        )?;
+        output.flush()?;
+        let mut answer = String::new();
+        BufReader::new(input).read_line(&mut answer)?;
+        Ok(answer)
+    };
+    match prompt() {
+        Ok(answer) => {
+            let answer = answer.trim();
+            answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes")
+        }
+        Err(e) => {
+            debug!("failed to ask on the terminal: {e}");
+            false
+        }
+    }
+}
+
+/// Reports [`Warning::WriteErrorDiscarded`] for an error of a queued write that was never collected.
+pub(crate) fn write_error_discarded(path: &Path, error: &io::Error) {
+    if has_handler() {
+        emit(&Warning::WriteErrorDiscarded {
+            path: path.to_owned(),
+            message: error.to_string(),
+        });
+    }
+}
+
+/// Records the size of an input file at open, to report [`Warning::InputSizeChanged`] when its end is reached.
+#[derive(Debug)]
+pub(crate) struct SizeCheck {
+    size: Option<u64>,
+    done: AtomicBool,
+}
+
+impl SizeCheck {
+    pub(crate) fn new(file: &File) -> Self {
+        let size = file
+            .metadata()
+            .ok()
+            .filter(|m| m.is_file())
+            .map(|m| m.len());
+        Self {
+            size,
+            done: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn at_eof(&self, path: &Path, file: &File) {
+        let Some(before) = self.size else {
+            return;
+        };
+        if !has_handler() || self.done.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let Ok(metadata) = file.metadata() else {
+            return;
+        };
+        let after = metadata.len();
+        if after != before {
+            emit(&Warning::InputSizeChanged {
+                path: path.to_owned(),
+                before,
+                after,
+            });
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::{io, ops::ControlFlow, path::Path, sync::mpsc, time::Duration};
+
+use notify::{EventKind, RecursiveMode, Watcher as _};
+
+use crate::{error, logging::debug, ErrorKind, Input};
+
+// Editors often emit several events for a single save (e.g. truncate + write, or write + rename),
+// so events arriving within this period are coalesced into one rerun.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+fn notify_error(e: notify::Error) -> io::Error {
+    match e.kind {
+        notify::ErrorKind::Io(e) => e,
+        _ => error::new(
+            ErrorKind::Other,
+            format!("failed to watch the input file: {e}"),
+        ),
+    }
+}
+
+pub(crate) fn watch<F>(path: &Path, mut f: F) -> io::Result<()>
+where
+    F: FnMut(Input) -> io::Result<ControlFlow<()>>,
+{
+    if f(Input::open(path.to_owned())?)?.is_break() {
+        return Ok(());
+    }
+
+    // Watch the parent directory rather than the file itself, so that the watch survives
+    // editors that save by writing a new file and renaming it over the original.
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let file_name = path.file_name();
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(notify_error)?;
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(notify_error)?;
+    debug!("watching input file `{}`", path.display());
+
+    let is_relevant = |event: &notify::Result<notify::Event>| match event {
+        Ok(event) => {
+            matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                && event.paths.iter().any(|p| p.file_name() == file_name)
+        }
+        Err(_) => true,
+    };
+
+    while let Ok(event) = rx.recv() {
+        if !is_relevant(&event) {
+            continue;
+        }
+        event.map_err(notify_error)?;
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            event.map_err(notify_error)?;
+        }
+        debug!("input file `{}` changed", path.display());
+        let input = match Input::open(path.to_owned()) {
+            Ok(input) => input,
+            // The file may be temporarily missing while it is being replaced.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        if f(input)?.is_break() {
+            break;
+        }
+    }
+    Ok(())
+}
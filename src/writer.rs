@@ -0,0 +1,217 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    thread,
+};
+
+use crate::{cleanup::Registration, logging::debug};
+
+/// A line-buffered or block-buffered file writer.
+///
+/// When line-buffered, this behaves like [`io::LineWriter`], flushing its buffer whenever a newline is written,
+/// but also gives access to the buffered data, which `LineWriter` does not.
+/// When block-buffered, this behaves like [`BufWriter`], flushing its buffer only when it is full.
+/// With a capacity of 0, every write goes directly to the file.
+/// A flush threshold additionally flushes the buffer once that many bytes are pending, even without a newline.
+#[derive(Debug)]
+pub(crate) struct FileWriter {
+    inner: BufWriter<File>,
+    line_buffered: bool,
+    flush_threshold: Option<usize>,
+    sync_on_close: bool,
+    rename: Option<PendingRename>,
+}
+
+/// A temporary file that replaces its target file when the [`FileWriter`] writing to it is closed.
+///
+/// The temporary file is removed if it is dropped without being renamed.
+#[derive(Debug)]
+pub(crate) struct PendingRename {
+    temp: PathBuf,
+    target: PathBuf,
+    #[cfg(feature = "cap-std")]
+    dir: Option<cap_std::fs::Dir>,
+    committed: bool,
+    _registration: Option<Registration>,
+}
+
+impl PendingRename {
+    /// Creates a [`PendingRename`] for a temporary file and its target, both relative to the current directory.
+    pub(crate) fn new(temp: PathBuf, target: PathBuf) -> Self {
+        Self {
+            _registration: Some(Registration::new(&temp)),
+            temp,
+            target,
+            #[cfg(feature = "cap-std")]
+            dir: None,
+            committed: false,
+        }
+    }
+
+    /// Creates a [`PendingRename`] for a temporary file and its target, both relative to `dir`.
+    #[cfg(feature = "cap-std")]
+    pub(crate) fn in_dir(dir: cap_std::fs::Dir, temp: PathBuf, target: PathBuf) -> Self {
+        // The panic hook removes files relative to the current directory, so the file is not registered.
+        Self {
+            temp,
+            target,
+            dir: Some(dir),
+            committed: false,
+            _registration: None,
+        }
+    }
+
+    fn commit(mut self) -> io::Result<()> {
+        debug!(
+            "renaming `{}` to `{}`",
+            self.temp.display(),
+            self.target.display()
+        );
+        #[cfg(feature = "cap-std")]
+        if let Some(dir) = &self.dir {
+            dir.rename(&self.temp, dir, &self.target)?;
+            self.committed = true;
+            return Ok(());
+        }
+        fs::rename(&self.temp, &self.target)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for PendingRename {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        debug!(
+            "removing uncommitted temporary file `{}`",
+            self.temp.display()
+        );
+        #[cfg(feature = "cap-std")]
+        if let Some(dir) = &self.dir {
+            let _ = dir.remove_file(&self.temp);
+            return;
+        }
+        let _ = fs::remove_file(&self.temp);
+    }
+}
+
+impl FileWriter {
+    pub(crate) fn with_capacity(capacity: usize, file: File, line_buffered: bool) -> Self {
+        Self {
+            inner: BufWriter::with_capacity(capacity, file),
+            line_buffered,
+            flush_threshold: None,
+            sync_on_close: false,
+            rename: None,
+        }
+    }
+
+    /// Makes [`FileWriter::close`] rename the file over its target.
+    ///
+    /// If the writer is dropped without being closed, the file is renamed after flushing the buffer,
+    /// unless the flush fails or the thread is panicking, in which case the file is removed.
+    pub(crate) fn with_rename(mut self, rename: PendingRename) -> Self {
+        self.rename = Some(rename);
+        self
+    }
+
+    /// Makes [`FileWriter::close`] synchronize the file to the storage device.
+    pub(crate) fn with_sync_on_close(mut self, sync: bool) -> Self {
+        self.sync_on_close = sync;
+        self
+    }
+
+    /// Flushes the buffer, synchronizes the file if configured to do so, and renames it over its target if any.
+    pub(crate) fn close(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        if self.sync_on_close {
+            self.inner.get_ref().sync_all()?;
+        }
+        if let Some(rename) = self.rename.take() {
+            rename.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Makes the writer flush its buffer once `threshold` bytes are pending.
+    pub(crate) fn with_flush_threshold(mut self, threshold: usize) -> Self {
+        self.flush_threshold = Some(threshold);
+        self
+    }
+
+    /// Returns a reference to the underlying file.
+    pub(crate) fn get_ref(&self) -> &File {
+        self.inner.get_ref()
+    }
+
+    /// Flushes the buffer and makes every subsequent write go directly to the file.
+    pub(crate) fn unbuffer(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        let file = self.inner.get_ref().try_clone()?;
+        self.inner = BufWriter::with_capacity(0, file);
+        self.line_buffered = false;
+        self.flush_threshold = None;
+        Ok(())
+    }
+
+    /// Returns the data that has been written but not yet flushed to the file.
+    pub(crate) fn buffer(&self) -> &[u8] {
+        self.inner.buffer()
+    }
+
+    /// Writes `buf`, flushing the buffer after the last newline if line-buffered.
+    fn write_lines(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.line_buffered {
+            return self.inner.write(buf);
+        }
+
+        // A previous flush after a newline may have failed; retry it before buffering more data.
+        if self.inner.buffer().last() == Some(&b'\n') {
+            self.inner.flush()?;
+        }
+
+        let Some(newline) = buf.iter().rposition(|&b| b == b'\n') else {
+            return self.inner.write(buf);
+        };
+
+        let lines = &buf[..=newline];
+        let n = self.inner.write(lines)?;
+        if n == lines.len() {
+            // The data has already been accepted, so a flush error is reported on the next write or flush.
+            let _ = self.inner.flush();
+        }
+        Ok(n)
+    }
+}
+
+impl Drop for FileWriter {
+    fn drop(&mut self) {
+        let Some(rename) = self.rename.take() else {
+            return;
+        };
+        // A partially written file must not replace the target.
+        if !thread::panicking() && self.inner.flush().is_ok() {
+            let _ = rename.commit();
+        }
+    }
+}
+
+impl Write for FileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.write_lines(buf)?;
+        if let Some(threshold) = self.flush_threshold {
+            if self.inner.buffer().len() >= threshold {
+                // As with newlines, a flush error is reported on the next write or flush.
+                let _ = self.inner.flush();
+            }
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}